@@ -162,7 +162,30 @@
 //! Multi stream is supported via [CudaStream], however it automatically
 //! synchronizes with the main stream on creation & on drop. It is still possible
 //! to be unsafe in a multi stream context though.
+//!
+//! # CUDA versions
+//!
+//! [sys] is generated from the CUDA 11.8 headers (see [sys::CUDA_VERSION]), and every
+//! function in [result] links directly against one of those symbols, so the driver
+//! that is loaded must support at least CUDA 11.8.
+//!
+//! Wrappers of functions added after 11.8 are only compiled with the cargo feature
+//! for the CUDA version that added them. Each feature includes the older ones, and
+//! none are enabled by default, so the default build only uses 11.8 APIs:
+//!
+//! | API | Requires | Feature |
+//! |---|---|---|
+//! | `CudaStream::id()` | CUDA 12.0 | `cuda-12000` |
+//! | `CudaFunction::name()` | CUDA 12.3 | `cuda-12030` |
+//! | `CudaStream::begin_capture_to_graph()` | CUDA 12.3 | `cuda-12030` |
+//!
+//! These functions are not linked either. They are looked up at runtime with
+//! `cuGetProcAddress`, and return `CUDA_ERROR_NOT_FOUND` on older drivers even when
+//! the feature is enabled.
+//!
+//! Any other symbol can be looked up the same way with [result::get_proc_address()].
 
+pub mod prelude;
 pub mod result;
 pub mod safe;
 #[allow(warnings)]
@@ -0,0 +1,54 @@
+//! The most commonly used items of [crate::driver], for glob importing:
+//!
+//! ```rust
+//! use cudarc::driver::prelude::*;
+//! ```
+//!
+//! This includes:
+//! - [CudaDevice], [CudaStream], [CudaEvent], and [CudaFunction]
+//! - [CudaSlice] and its views [CudaView] & [CudaViewMut]
+//! - [LaunchConfig] and the [LaunchAsync] trait for launching kernels
+//! - the [DevicePtr], [DevicePtrMut], [DeviceSlice], [DeviceRepr], and [ValidAsZeroBits] traits
+//! - [DriverError], and the [Result] alias that defaults to it
+
+pub use super::safe::{
+    CudaDevice, CudaEvent, CudaFunction, CudaSlice, CudaStream, CudaView, CudaViewMut, DevicePtr,
+    DevicePtrMut, DeviceRepr, DeviceSlice, DriverError, LaunchAsync, LaunchConfig, ValidAsZeroBits,
+};
+
+pub use super::result::Result;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nvrtc::compile_ptx;
+
+    #[test]
+    fn test_prelude_items() {
+        fn assert_traits<T: DeviceRepr + ValidAsZeroBits>() {}
+        assert_traits::<f32>();
+
+        let dev: std::sync::Arc<CudaDevice> = CudaDevice::new(0).unwrap();
+        let stream: CudaStream = dev.fork_default_stream().unwrap();
+        let event: CudaEvent = dev
+            .create_event(crate::driver::sys::CUevent_flags::CU_EVENT_DEFAULT)
+            .unwrap();
+
+        let ptx = compile_ptx("extern \"C\" __global__ void kernel(float *out) { }").unwrap();
+        dev.load_ptx(ptx, "prelude", &["kernel"]).unwrap();
+        let f: CudaFunction = dev.get_func("prelude", "kernel").unwrap();
+
+        let mut a: CudaSlice<f32> = dev.alloc_zeros(10).unwrap();
+        let view: CudaView<f32> = a.slice(..5);
+        assert_eq!(*view.device_ptr(), *a.device_ptr());
+        let mut view_mut: CudaViewMut<f32> = a.slice_mut(5..);
+        assert_eq!(view_mut.len(), 5);
+        let cfg: LaunchConfig = LaunchConfig::for_num_elems(5);
+        unsafe { f.launch_on_stream(&stream, cfg, (&mut view_mut,)) }.unwrap();
+        let _ = *view_mut.device_ptr_mut();
+
+        event.record_on_stream(&stream).unwrap();
+        let result: Result<()> = event.synchronize();
+        result.unwrap();
+    }
+}
@@ -12,7 +12,9 @@
 //! turns into [stream::create()], where [stream] is a module.
 
 use super::sys;
-use core::ffi::{c_uchar, c_uint, c_void, CStr};
+use core::ffi::{c_int, c_uchar, c_uint, c_void, CStr};
+#[cfg(feature = "cuda-12000")]
+use std::ffi::CString;
 use std::mem::MaybeUninit;
 
 /// Wrapper around [sys::CUresult]. See
@@ -20,6 +22,9 @@ use std::mem::MaybeUninit;
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct DriverError(pub sys::CUresult);
 
+/// A [core::result::Result] that defaults to [DriverError] as the error type.
+pub type Result<T, E = DriverError> = core::result::Result<T, E>;
+
 impl sys::CUresult {
     #[inline]
     pub fn result(self) -> Result<(), DriverError> {
@@ -52,6 +57,35 @@ impl DriverError {
             Ok(CStr::from_ptr(err_str.assume_init()))
         }
     }
+
+    /// Whether this is `CUDA_ERROR_ECC_UNCORRECTABLE`, i.e. an uncorrectable ECC error was
+    /// detected during execution.
+    ///
+    /// This error is sticky: the context is unusable afterwards, every later call in it
+    /// returns the same error, and the device must be reset (e.g. with `nvidia-smi -r`).
+    pub fn is_ecc_uncorrectable(&self) -> bool {
+        self.0 == sys::CUresult::CUDA_ERROR_ECC_UNCORRECTABLE
+    }
+
+    /// Whether this is `CUDA_ERROR_DEINITIALIZED`, i.e. the driver is shutting down.
+    ///
+    /// This happens when values are dropped during process exit after the driver has
+    /// already been torn down (e.g. from a `static` or thread local). All of the driver's
+    /// resources are released at that point anyway, so the safe api's [Drop] impls
+    /// ignore this error instead of panicking.
+    pub fn is_deinitialized(&self) -> bool {
+        self.0 == sys::CUresult::CUDA_ERROR_DEINITIALIZED
+    }
+
+    /// Whether this is `CUDA_ERROR_UNSUPPORTED_PTX_VERSION`, i.e. PTX was produced by a
+    /// newer toolchain (usually nvrtc) than the installed driver supports.
+    ///
+    /// When loading PTX from nvrtc this is what the runtime reports as
+    /// `cudaErrorInsufficientDriver`: the driver must be updated. See [check_version_compatibility()] to detect this
+    /// before loading any PTX.
+    pub fn is_unsupported_ptx_version(&self) -> bool {
+        self.0 == sys::CUresult::CUDA_ERROR_UNSUPPORTED_PTX_VERSION
+    }
 }
 
 impl std::fmt::Debug for DriverError {
@@ -82,14 +116,139 @@ pub fn init() -> Result<(), DriverError> {
     unsafe { sys::cuInit(0).result() }
 }
 
+/// Returns the version of the installed driver, e.g. `12030` for 12.3.
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__VERSION.html#group__CUDA__VERSION)
+pub fn driver_version() -> Result<c_int, DriverError> {
+    let mut version = 0;
+    unsafe { sys::cuDriverGetVersion(&mut version) }.result()?;
+    Ok(version)
+}
+
+/// The installed driver is older than the loaded nvrtc, returned by [check_version_compatibility()].
+///
+/// Both versions are encoded like [driver_version()], e.g. `12030` for 12.3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionMismatch {
+    pub driver_version: c_int,
+    pub nvrtc_version: c_int,
+}
+
+impl VersionMismatch {
+    /// The error loading PTX from nvrtc fails with, see [DriverError::is_unsupported_ptx_version()].
+    pub fn error(&self) -> DriverError {
+        DriverError(sys::CUresult::CUDA_ERROR_UNSUPPORTED_PTX_VERSION)
+    }
+}
+
+impl From<VersionMismatch> for DriverError {
+    fn from(value: VersionMismatch) -> Self {
+        value.error()
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for VersionMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the installed CUDA driver supports CUDA {}.{}, which is older than nvrtc's CUDA {}.{}, so PTX compiled by nvrtc can't be loaded ({:?}). Update the driver, or use an nvrtc no newer than the driver.",
+            self.driver_version / 1000,
+            (self.driver_version % 1000) / 10,
+            self.nvrtc_version / 1000,
+            (self.nvrtc_version % 1000) / 10,
+            self.error().0,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VersionMismatch {}
+
+/// Checks that the installed driver is at least as new as the loaded nvrtc library.
+///
+/// This is the most common cause of `CUDA_ERROR_UNSUPPORTED_PTX_VERSION`
+/// ([DriverError::is_unsupported_ptx_version()]) when loading PTX from [crate::nvrtc::compile_ptx()]:
+/// nvrtc emits PTX for its own CUDA version, which an older driver can't JIT compile.
+/// The returned [VersionMismatch] includes both versions in its message.
+///
+/// Returns `Ok(None)` if the versions are compatible, or if nvrtc can't be loaded and
+/// so there is nothing to compare against. An error querying the driver version (e.g.
+/// no driver is installed) is returned as is.
+pub fn check_version_compatibility() -> Result<Option<VersionMismatch>, DriverError> {
+    let driver_version = driver_version()?;
+    let nvrtc_version = match crate::nvrtc::result::version() {
+        Ok((major, minor)) => major * 1000 + minor * 10,
+        Err(_) => return Ok(None),
+    };
+    if driver_version < nvrtc_version {
+        return Ok(Some(VersionMismatch {
+            driver_version,
+            nvrtc_version,
+        }));
+    }
+    Ok(None)
+}
+
+/// Returns the driver's function pointer for `symbol` (e.g. `"cuStreamSynchronize"`), or
+/// `CUDA_ERROR_NOT_FOUND` if the installed driver doesn't provide it. This allows calling
+/// driver functions that may be newer than the ones linked against.
+///
+/// The version of the symbol requested is [sys::CUDA_VERSION], i.e. the version
+/// these bindings were generated for.
+///
+/// `flags` selects which default stream semantics the returned function uses:
+/// - `CU_GET_PROC_ADDRESS_DEFAULT` follows the compiler's `--default-stream` setting
+/// - `CU_GET_PROC_ADDRESS_LEGACY_STREAM` uses the legacy default stream
+/// - `CU_GET_PROC_ADDRESS_PER_THREAD_DEFAULT_STREAM` uses the per-thread default stream
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__DRIVER__ENTRY__POINT.html#group__CUDA__DRIVER__ENTRY__POINT)
+pub fn get_proc_address(
+    symbol: &CStr,
+    flags: sys::CUdriverProcAddress_flags,
+) -> Result<*mut c_void, DriverError> {
+    let mut pfn = MaybeUninit::uninit();
+    unsafe {
+        sys::cuGetProcAddress(
+            symbol.as_ptr(),
+            pfn.as_mut_ptr(),
+            sys::CUDA_VERSION as c_int,
+            flags as sys::cuuint64_t,
+        )
+        .result()?;
+        Ok(pfn.assume_init())
+    }
+}
+
+/// Like [get_proc_address()], but looks up the version of `symbol` for the installed driver
+/// rather than [sys::CUDA_VERSION], for calling functions that are newer than these bindings.
+#[cfg(feature = "cuda-12000")]
+pub(crate) fn get_driver_proc_address(symbol: &str) -> Result<*mut c_void, DriverError> {
+    let symbol = CString::new(symbol).unwrap();
+    let mut pfn = std::ptr::null_mut();
+    unsafe {
+        sys::cuGetProcAddress(
+            symbol.as_ptr(),
+            &mut pfn,
+            driver_version()?,
+            sys::CUdriverProcAddress_flags::CU_GET_PROC_ADDRESS_DEFAULT as sys::cuuint64_t,
+        )
+        .result()?;
+    }
+    if pfn.is_null() {
+        return Err(DriverError(sys::CUresult::CUDA_ERROR_NOT_FOUND));
+    }
+    Ok(pfn)
+}
+
 pub mod device {
     //! Device management functions (`cuDevice*`).
     //!
     //! See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__DEVICE.html#group__CUDA__DEVICE)
 
     use super::{sys, DriverError};
-    use core::ffi::c_int;
-    use std::mem::MaybeUninit;
+    use core::ffi::{c_char, c_int, c_void, CStr};
+    use std::{mem::MaybeUninit, string::String};
 
     /// Get a device for a specific ordinal.
     /// See [cuDeviceGet() docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__DEVICE.html#group__CUDA__DEVICE_1g8bdd1cc7201304b01357b8034f6587cb).
@@ -111,6 +270,18 @@ pub mod device {
         }
     }
 
+    /// Returns the name of the device, e.g. `"NVIDIA GeForce RTX 3090"`.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__DEVICE.html#group__CUDA__DEVICE)
+    ///
+    /// # Safety
+    /// Must be a device returned from [get].
+    pub unsafe fn get_name(dev: sys::CUdevice) -> Result<String, DriverError> {
+        let mut name = [0 as c_char; 256];
+        sys::cuDeviceGetName(name.as_mut_ptr(), name.len() as c_int, dev).result()?;
+        Ok(CStr::from_ptr(name.as_ptr()).to_string_lossy().into_owned())
+    }
+
     /// Returns the total amount of memory in bytes on the device.
     ///
     /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__DEVICE.html#group__CUDA__DEVICE_1gc6a0d6551335a3780f9f3c967a0fde5d)
@@ -137,6 +308,161 @@ pub mod device {
         sys::cuDeviceGetAttribute(value.as_mut_ptr(), attrib, dev).result()?;
         Ok(value.assume_init())
     }
+
+    /// Blocks until all GPUDirect RDMA writes to `target` that are visible to `scope`
+    /// have been completed.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__DEVICE.html#group__CUDA__DEVICE_1g87904937c1a16fe9bf07e361fd3e5da4)
+    ///
+    /// # Safety
+    /// 1. A context must be bound to the calling thread
+    /// 2. The device must support `CU_FLUSH_GPU_DIRECT_RDMA_WRITES_OPTION_HOST`
+    ///    (see `CU_DEVICE_ATTRIBUTE_GPU_DIRECT_RDMA_FLUSH_WRITES_OPTIONS`)
+    pub unsafe fn flush_gpu_direct_rdma_writes(
+        target: sys::CUflushGPUDirectRDMAWritesTarget,
+        scope: sys::CUflushGPUDirectRDMAWritesScope,
+    ) -> Result<(), DriverError> {
+        sys::cuFlushGPUDirectRDMAWrites(target, scope).result()
+    }
+
+    /// Whether `dev` can directly access memory allocated on `peer_dev`.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__PEER__ACCESS.html#group__CUDA__PEER__ACCESS_1g496bdaae1f632ebfb695b99d2c40f19e)
+    ///
+    /// # Safety
+    /// Both devices must be returned from [get].
+    pub unsafe fn can_access_peer(
+        dev: sys::CUdevice,
+        peer_dev: sys::CUdevice,
+    ) -> Result<bool, DriverError> {
+        let mut can_access = MaybeUninit::uninit();
+        sys::cuDeviceCanAccessPeer(can_access.as_mut_ptr(), dev, peer_dev).result()?;
+        Ok(can_access.assume_init() != 0)
+    }
+
+    /// Get an attribute of the link between `src` and `dst`.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__PEER__ACCESS.html#group__CUDA__PEER__ACCESS_1g4c55c60508f8eba4546b51f2ee545393)
+    ///
+    /// # Safety
+    /// Both devices must be returned from [get].
+    pub unsafe fn get_p2p_attribute(
+        attrib: sys::CUdevice_P2PAttribute,
+        src: sys::CUdevice,
+        dst: sys::CUdevice,
+    ) -> Result<i32, DriverError> {
+        let mut value = MaybeUninit::uninit();
+        sys::cuDeviceGetP2PAttribute(value.as_mut_ptr(), attrib, src, dst).result()?;
+        Ok(value.assume_init())
+    }
+
+    /// Gets the current memory pool of `dev`, which stream ordered allocations
+    /// like [super::malloc_async] allocate from.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__DEVICE.html#group__CUDA__DEVICE)
+    ///
+    /// # Safety
+    /// Must be a device returned from [get].
+    pub unsafe fn get_mem_pool(dev: sys::CUdevice) -> Result<sys::CUmemoryPool, DriverError> {
+        let mut pool = MaybeUninit::uninit();
+        sys::cuDeviceGetMemPool(pool.as_mut_ptr(), dev).result()?;
+        Ok(pool.assume_init())
+    }
+
+    /// Frees the memory cached by `dev` for allocations made by graph memory nodes
+    /// back to the OS.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH)
+    ///
+    /// # Safety
+    /// Must be a device returned from [get].
+    pub unsafe fn graph_mem_trim(dev: sys::CUdevice) -> Result<(), DriverError> {
+        sys::cuDeviceGraphMemTrim(dev).result()
+    }
+
+    /// Gets an attribute of the memory used by graph memory nodes on `dev`.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH)
+    ///
+    /// # Safety
+    /// Must be a device returned from [get].
+    pub unsafe fn get_graph_mem_attribute(
+        dev: sys::CUdevice,
+        attr: sys::CUgraphMem_attribute,
+    ) -> Result<u64, DriverError> {
+        let mut value: u64 = 0;
+        sys::cuDeviceGetGraphMemAttribute(dev, attr, &mut value as *mut u64 as *mut _).result()?;
+        Ok(value)
+    }
+
+    /// Sets an attribute of the memory used by graph memory nodes on `dev`. Only the
+    /// `*_HIGH` attributes can be set, and only to 0 to reset them.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH)
+    ///
+    /// # Safety
+    /// Must be a device returned from [get].
+    pub unsafe fn set_graph_mem_attribute(
+        dev: sys::CUdevice,
+        attr: sys::CUgraphMem_attribute,
+        value: u64,
+    ) -> Result<(), DriverError> {
+        let mut value = value;
+        sys::cuDeviceSetGraphMemAttribute(dev, attr, &mut value as *mut u64 as *mut _).result()
+    }
+
+    /// The maximum number of elements in a 1D linear texture with `num_channels`
+    /// channels of `format` on `dev`.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__DEVICE.html#group__CUDA__DEVICE)
+    ///
+    /// # Safety
+    /// Must be a device returned from [get].
+    pub unsafe fn get_texture_1d_linear_max_width(
+        dev: sys::CUdevice,
+        format: sys::CUarray_format,
+        num_channels: u32,
+    ) -> Result<usize, DriverError> {
+        let mut width = MaybeUninit::uninit();
+        sys::cuDeviceGetTexture1DLinearMaxWidth(width.as_mut_ptr(), format, num_channels, dev)
+            .result()?;
+        Ok(width.assume_init())
+    }
+
+    /// Fills `attr_list`, an `NvSciSyncAttrList`, with the attributes `dev` needs to
+    /// signal and/or wait on (`flags` is a combination of [sys::CUDA_NVSCISYNC_ATTR_SIGNAL]
+    /// and [sys::CUDA_NVSCISYNC_ATTR_WAIT]) NvSciSync objects.
+    ///
+    /// NvSciSync only exists on Tegra platforms, so the driver is only called with the
+    /// `tegra` feature. Without it this always returns `CUDA_ERROR_NOT_SUPPORTED`.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__DEVICE.html#group__CUDA__DEVICE)
+    ///
+    /// # Safety
+    /// With the `tegra` feature:
+    /// 1. Must be a device returned from [get].
+    /// 2. `attr_list` must be a valid `NvSciSyncAttrList`.
+    #[cfg(feature = "tegra")]
+    pub unsafe fn get_nvsci_sync_attributes(
+        attr_list: *mut c_void,
+        dev: sys::CUdevice,
+        flags: c_int,
+    ) -> Result<(), DriverError> {
+        sys::cuDeviceGetNvSciSyncAttributes(attr_list, dev, flags).result()
+    }
+
+    /// See the `tegra` version of this function.
+    ///
+    /// # Safety
+    /// This never calls the driver, so any arguments are allowed.
+    #[cfg(not(feature = "tegra"))]
+    pub unsafe fn get_nvsci_sync_attributes(
+        _attr_list: *mut c_void,
+        _dev: sys::CUdevice,
+        _flags: c_int,
+    ) -> Result<(), DriverError> {
+        Err(DriverError(sys::CUresult::CUDA_ERROR_NOT_SUPPORTED))
+    }
 }
 
 pub mod occupancy {
@@ -285,12 +611,64 @@ pub mod occupancy {
     }
 }
 
+pub mod function {
+    //! Function management functions (`cuFunc*`).
+    //!
+    //! See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXEC.html#group__CUDA__EXEC)
+
+    use super::{sys, DriverError};
+    #[cfg(feature = "cuda-12030")]
+    use core::ffi::{c_char, CStr};
+    #[cfg(feature = "cuda-12030")]
+    use std::string::String;
+
+    /// Sets the shared memory bank size for a function.
+    ///
+    /// > On devices with configurable shared memory banks, this function will force
+    /// > all subsequent launches of the specified device function to have the given
+    /// > shared memory bank size configuration.
+    ///
+    /// On devices without configurable shared memory banks this is a no-op.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXEC.html#group__CUDA__EXEC_1g430b913f24970e63869635395df6d9f5)
+    ///
+    /// # Safety
+    /// Function must exist.
+    pub unsafe fn set_shared_mem_config(
+        f: sys::CUfunction,
+        config: sys::CUsharedconfig,
+    ) -> Result<(), DriverError> {
+        sys::cuFuncSetSharedMemConfig(f, config).result()
+    }
+
+    /// Returns the (mangled) name of a function.
+    ///
+    /// `cuFuncGetName` was added in CUDA 12.3, after these bindings were generated, so it
+    /// is looked up at runtime and this returns `CUDA_ERROR_NOT_FOUND` on older drivers.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXEC.html#group__CUDA__EXEC)
+    ///
+    /// # Safety
+    /// 1. The function must be from a module that has not been unloaded
+    #[cfg(feature = "cuda-12030")]
+    pub unsafe fn get_name(f: sys::CUfunction) -> Result<String, DriverError> {
+        type FuncGetName =
+            unsafe extern "C" fn(*mut *const c_char, sys::CUfunction) -> sys::CUresult;
+        let pfn = super::get_driver_proc_address("cuFuncGetName")?;
+        let func_get_name: FuncGetName = std::mem::transmute(pfn);
+        let mut name = std::ptr::null();
+        func_get_name(&mut name, f).result()?;
+        Ok(CStr::from_ptr(name).to_string_lossy().into_owned())
+    }
+}
+
 pub mod primary_ctx {
     //! Primary context management functions (`cuDevicePrimaryCtx*`).
     //!
     //! See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__PRIMARY__CTX.html#group__CUDA__PRIMARY__CTX)
 
     use super::{sys, DriverError};
+    use core::ffi::c_uint;
     use std::mem::MaybeUninit;
 
     /// Creates a primary context on the device and pushes it onto the primary context stack.
@@ -317,6 +695,43 @@ pub mod primary_ctx {
     pub unsafe fn release(dev: sys::CUdevice) -> Result<(), DriverError> {
         sys::cuDevicePrimaryCtxRelease_v2(dev).result()
     }
+
+    /// Destroys all allocations and resets all state of the primary context.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__PRIMARY__CTX.html#group__CUDA__PRIMARY__CTX)
+    ///
+    /// # Safety
+    ///
+    /// This is only safe with a device that was returned from [super::device::get],
+    /// and nothing created in the primary context may be used afterwards.
+    pub unsafe fn reset(dev: sys::CUdevice) -> Result<(), DriverError> {
+        sys::cuDevicePrimaryCtxReset_v2(dev).result()
+    }
+
+    /// Sets the flags the primary context is created with, a combination of [sys::CUctx_flags].
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__PRIMARY__CTX.html#group__CUDA__PRIMARY__CTX_1gd779a84f17acdad0d9143d9fe719cfdf)
+    ///
+    /// # Safety
+    ///
+    /// This is only safe with a device that was returned from [super::device::get].
+    pub unsafe fn set_flags(dev: sys::CUdevice, flags: c_uint) -> Result<(), DriverError> {
+        sys::cuDevicePrimaryCtxSetFlags_v2(dev, flags).result()
+    }
+
+    /// Returns the flags of the primary context, and whether it is active (i.e. has been retained).
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__PRIMARY__CTX.html#group__CUDA__PRIMARY__CTX_1g65f3e018721b6d90aa05cfb56250f469)
+    ///
+    /// # Safety
+    ///
+    /// This is only safe with a device that was returned from [super::device::get].
+    pub unsafe fn get_state(dev: sys::CUdevice) -> Result<(c_uint, bool), DriverError> {
+        let mut flags = MaybeUninit::uninit();
+        let mut active = MaybeUninit::uninit();
+        sys::cuDevicePrimaryCtxGetState(dev, flags.as_mut_ptr(), active.as_mut_ptr()).result()?;
+        Ok((flags.assume_init(), active.assume_init() != 0))
+    }
 }
 
 pub mod ctx {
@@ -355,6 +770,34 @@ pub mod ctx {
             }
         }
     }
+
+    /// Enables the current context to access memory allocated in `peer_ctx`.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__PEER__ACCESS.html#group__CUDA__PEER__ACCESS)
+    ///
+    /// # Safety
+    /// `peer_ctx` must be a valid context that was not already destroyed.
+    pub unsafe fn enable_peer_access(peer_ctx: sys::CUcontext) -> Result<(), DriverError> {
+        sys::cuCtxEnablePeerAccess(peer_ctx, 0).result()
+    }
+
+    /// Gets the value of `limit` for the current context.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__CTX.html#group__CUDA__CTX)
+    pub fn get_limit(limit: sys::CUlimit) -> Result<usize, DriverError> {
+        let mut value = MaybeUninit::uninit();
+        unsafe {
+            sys::cuCtxGetLimit(value.as_mut_ptr(), limit).result()?;
+            Ok(value.assume_init())
+        }
+    }
+
+    /// Sets `limit` to `value` for the current context.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__CTX.html#group__CUDA__CTX)
+    pub fn set_limit(limit: sys::CUlimit, value: usize) -> Result<(), DriverError> {
+        unsafe { sys::cuCtxSetLimit(limit, value) }.result()
+    }
 }
 
 pub mod stream {
@@ -363,7 +806,7 @@ pub mod stream {
     //! See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__STREAM.html#group__CUDA__STREAM).
 
     use super::{sys, DriverError};
-    use std::mem::MaybeUninit;
+    use std::{mem::MaybeUninit, vec::Vec};
 
     /// The kind of stream to initialize.
     ///
@@ -420,6 +863,41 @@ pub mod stream {
         sys::cuStreamSynchronize(stream).result()
     }
 
+    /// Returns `Ok(())` if all work queued on the stream has completed, and
+    /// `CUDA_ERROR_NOT_READY` if it hasn't.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__STREAM.html#group__CUDA__STREAM)
+    ///
+    /// # Safety
+    ///
+    /// This should only be called with stream created by [create] and not already
+    /// destroyed. This follows default stream semantics, see relevant cuda docs.
+    pub unsafe fn query(stream: sys::CUstream) -> Result<(), DriverError> {
+        sys::cuStreamQuery(stream).result()
+    }
+
+    /// Returns the id of the stream, which is unique among all streams created in
+    /// the process, including across contexts.
+    ///
+    /// `cuStreamGetId` was added in CUDA 12.0, after these bindings were generated, so it
+    /// is looked up at runtime and this returns `CUDA_ERROR_NOT_FOUND` on older drivers.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__STREAM.html#group__CUDA__STREAM)
+    ///
+    /// # Safety
+    ///
+    /// This should only be called with stream created by [create] and not already
+    /// destroyed.
+    #[cfg(feature = "cuda-12000")]
+    pub unsafe fn get_id(stream: sys::CUstream) -> Result<u64, DriverError> {
+        type StreamGetId = unsafe extern "C" fn(sys::CUstream, *mut u64) -> sys::CUresult;
+        let pfn = super::get_driver_proc_address("cuStreamGetId")?;
+        let stream_get_id: StreamGetId = std::mem::transmute(pfn);
+        let mut id = MaybeUninit::uninit();
+        stream_get_id(stream, id.as_mut_ptr()).result()?;
+        Ok(id.assume_init())
+    }
+
     /// Destroys a stream.
     ///
     /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__STREAM.html#group__CUDA__STREAM_1g244c8833de4596bcd31a06cdf21ee758)
@@ -445,59 +923,857 @@ pub mod stream {
     ) -> Result<(), DriverError> {
         sys::cuStreamWaitEvent(stream, event, flags as u32).result()
     }
-}
-
-/// Allocates memory with stream ordered semantics.
-///
-/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MALLOC__ASYNC.html#group__CUDA__MALLOC__ASYNC_1g13413273e84a641bce1929eae9e6501f)
-///
-/// # Safety
-/// 1. The stream should be an already created stream.
-/// 2. The memory return by this is unset, which may be invalid for `T`.
-/// 3. All uses of this memory must be on the same stream.
-pub unsafe fn malloc_async(
-    stream: sys::CUstream,
-    num_bytes: usize,
-) -> Result<sys::CUdeviceptr, DriverError> {
-    let mut dev_ptr = MaybeUninit::uninit();
-    sys::cuMemAllocAsync(dev_ptr.as_mut_ptr(), num_bytes, stream).result()?;
-    Ok(dev_ptr.assume_init())
-}
 
-/// Allocates memory
-///
-/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1gb82d2a09844a58dd9e744dc31e8aa467)
-///
-/// # Safety
-/// 1. The memory return by this is unset, which may be invalid for `T`.
-pub unsafe fn malloc_sync(num_bytes: usize) -> Result<sys::CUdeviceptr, DriverError> {
-    let mut dev_ptr = MaybeUninit::uninit();
-    sys::cuMemAlloc_v2(dev_ptr.as_mut_ptr(), num_bytes).result()?;
-    Ok(dev_ptr.assume_init())
-}
+    /// Copies the attributes, e.g. the access policy window, of `src` onto `dst`.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__STREAM.html#group__CUDA__STREAM)
+    ///
+    /// # Safety
+    /// 1. Both streams must not have been freed already
+    pub unsafe fn copy_attributes(
+        dst: sys::CUstream,
+        src: sys::CUstream,
+    ) -> Result<(), DriverError> {
+        sys::cuStreamCopyAttributes(dst, src).result()
+    }
 
-/// Frees memory with stream ordered semantics.
-///
-/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MALLOC__ASYNC.html#group__CUDA__MALLOC__ASYNC_1g41acf4131f672a2a75cd93d3241f10cf)
-///
-/// # Safety
-/// 1. The stream should be an already created stream.
-/// 2. The memory should have been allocated on this stream.
-/// 3. The memory should not have been freed already (double free)
-pub unsafe fn free_async(dptr: sys::CUdeviceptr, stream: sys::CUstream) -> Result<(), DriverError> {
-    sys::cuMemFreeAsync(dptr, stream).result()
-}
+    /// Gets the value of a stream attribute.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__STREAM.html#group__CUDA__STREAM)
+    ///
+    /// # Safety
+    /// 1. The stream must not have been freed already
+    pub unsafe fn get_attribute(
+        stream: sys::CUstream,
+        attr: sys::CUstreamAttrID,
+    ) -> Result<sys::CUstreamAttrValue, DriverError> {
+        let mut value = MaybeUninit::uninit();
+        sys::cuStreamGetAttribute(stream, attr, value.as_mut_ptr()).result()?;
+        Ok(value.assume_init())
+    }
 
-/// Allocates memory
-///
-/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1g89b3f154e17cc89b6eea277dbdf5c93a)
-///
+    /// Sets the value of a stream attribute.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__STREAM.html#group__CUDA__STREAM)
+    ///
+    /// # Safety
+    /// 1. The stream must not have been freed already
+    /// 2. `value` must be the union member that corresponds to `attr`
+    pub unsafe fn set_attribute(
+        stream: sys::CUstream,
+        attr: sys::CUstreamAttrID,
+        value: &sys::CUstreamAttrValue,
+    ) -> Result<(), DriverError> {
+        sys::cuStreamSetAttribute(stream, attr, value).result()
+    }
+
+    /// Returns whether the stream is currently capturing work into a graph.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__STREAM.html#group__CUDA__STREAM_1g37823c49206e3704ae23c7ad78560bca)
+    ///
+    /// # Safety
+    /// 1. The stream must not have been freed already
+    pub unsafe fn is_capturing(
+        stream: sys::CUstream,
+    ) -> Result<sys::CUstreamCaptureStatus, DriverError> {
+        let mut status = MaybeUninit::uninit();
+        sys::cuStreamIsCapturing(stream, status.as_mut_ptr()).result()?;
+        Ok(status.assume_init())
+    }
+
+    /// Begins capturing the work queued onto `stream` into a graph, instead of executing it.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__STREAM.html#group__CUDA__STREAM_1g767167da0bbf07157dc20b6c258a2143)
+    ///
+    /// # Safety
+    /// 1. The stream must not have been freed already
+    /// 2. The stream must not be the legacy null stream
+    pub unsafe fn begin_capture(
+        stream: sys::CUstream,
+        mode: sys::CUstreamCaptureMode,
+    ) -> Result<(), DriverError> {
+        sys::cuStreamBeginCapture_v2(stream, mode).result()
+    }
+
+    /// Begins capturing the work queued onto `stream` into the existing `graph`, where the
+    /// first captured work depends on `dependencies`, which must be nodes of `graph`.
+    /// [end_capture] then returns `graph`.
+    ///
+    /// `cuStreamBeginCaptureToGraph` was added in CUDA 12.3, after these bindings were generated,
+    /// so it is looked up at runtime and this returns `CUDA_ERROR_NOT_FOUND` on older drivers.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__STREAM.html#group__CUDA__STREAM)
+    ///
+    /// # Safety
+    /// 1. The stream and graph must not have been freed already
+    /// 2. The stream must not be the legacy null stream
+    /// 3. `graph` must not be modified or destroyed until the capture ends
+    #[cfg(feature = "cuda-12030")]
+    pub unsafe fn begin_capture_to_graph(
+        stream: sys::CUstream,
+        graph: sys::CUgraph,
+        dependencies: &[sys::CUgraphNode],
+        mode: sys::CUstreamCaptureMode,
+    ) -> Result<(), DriverError> {
+        type StreamBeginCaptureToGraph = unsafe extern "C" fn(
+            sys::CUstream,
+            sys::CUgraph,
+            *const sys::CUgraphNode,
+            *const core::ffi::c_void,
+            usize,
+            sys::CUstreamCaptureMode,
+        ) -> sys::CUresult;
+        let pfn = super::get_driver_proc_address("cuStreamBeginCaptureToGraph")?;
+        let begin_capture_to_graph: StreamBeginCaptureToGraph = std::mem::transmute(pfn);
+        begin_capture_to_graph(
+            stream,
+            graph,
+            dependencies.as_ptr(),
+            std::ptr::null(),
+            dependencies.len(),
+            mode,
+        )
+        .result()
+    }
+
+    /// Returns the capture status of `stream`, and if it is capturing, the id of the capture
+    /// and the nodes that the next captured node will depend on.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__STREAM.html#group__CUDA__STREAM)
+    ///
+    /// # Safety
+    /// 1. The stream must not have been freed already
+    pub unsafe fn get_capture_info(
+        stream: sys::CUstream,
+    ) -> Result<(sys::CUstreamCaptureStatus, u64, Vec<sys::CUgraphNode>), DriverError> {
+        let mut status = MaybeUninit::uninit();
+        let mut id = 0;
+        let mut deps = std::ptr::null();
+        let mut num_deps = 0;
+        sys::cuStreamGetCaptureInfo_v2(
+            stream,
+            status.as_mut_ptr(),
+            &mut id,
+            std::ptr::null_mut(),
+            &mut deps,
+            &mut num_deps,
+        )
+        .result()?;
+        let deps = if num_deps == 0 {
+            Vec::new()
+        } else {
+            std::slice::from_raw_parts(deps, num_deps).to_vec()
+        };
+        Ok((status.assume_init(), id, deps))
+    }
+
+    /// Adds to or replaces the nodes that the next node captured on `stream` will depend on.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__STREAM.html#group__CUDA__STREAM)
+    ///
+    /// # Safety
+    /// 1. The stream must be capturing and not freed already
+    /// 2. `nodes` must be nodes of the graph being captured
+    pub unsafe fn update_capture_dependencies(
+        stream: sys::CUstream,
+        nodes: &mut [sys::CUgraphNode],
+        flags: sys::CUstreamUpdateCaptureDependencies_flags,
+    ) -> Result<(), DriverError> {
+        sys::cuStreamUpdateCaptureDependencies(
+            stream,
+            nodes.as_mut_ptr(),
+            nodes.len(),
+            flags as u32,
+        )
+        .result()
+    }
+
+    /// Sets the calling thread's stream capture mode to `mode`, returning the previous mode.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__STREAM.html#group__CUDA__STREAM)
+    pub fn exchange_capture_mode(
+        mode: sys::CUstreamCaptureMode,
+    ) -> Result<sys::CUstreamCaptureMode, DriverError> {
+        let mut mode = mode;
+        unsafe { sys::cuThreadExchangeStreamCaptureMode(&mut mode) }.result()?;
+        Ok(mode)
+    }
+
+    /// Ends capturing on `stream`, returning the captured graph.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__STREAM.html#group__CUDA__STREAM_1g03dab8b2ba76b00718955177a929970c)
+    ///
+    /// # Safety
+    /// 1. The stream must have been passed to [begin_capture] and not freed already
+    pub unsafe fn end_capture(stream: sys::CUstream) -> Result<sys::CUgraph, DriverError> {
+        let mut graph = MaybeUninit::uninit();
+        sys::cuStreamEndCapture(stream, graph.as_mut_ptr()).result()?;
+        Ok(graph.assume_init())
+    }
+}
+
+pub mod mem_pool {
+    //! Memory pool management functions (`cuMemPool*`).
+    //!
+    //! See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MALLOC__ASYNC.html#group__CUDA__MALLOC__ASYNC)
+
+    use super::{sys, DriverError};
+    use core::ffi::c_int;
+    use std::mem::MaybeUninit;
+
+    /// Frees memory cached by `pool` back to the OS, until at most `min_bytes_to_keep`
+    /// reserved bytes remain. Memory backing live allocations is never freed.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MALLOC__ASYNC.html#group__CUDA__MALLOC__ASYNC)
+    ///
+    /// # Safety
+    /// 1. The pool must not have been destroyed already
+    pub unsafe fn trim_to(
+        pool: sys::CUmemoryPool,
+        min_bytes_to_keep: usize,
+    ) -> Result<(), DriverError> {
+        sys::cuMemPoolTrimTo(pool, min_bytes_to_keep).result()
+    }
+
+    /// Gets one of the boolean reuse policy attributes of `pool`, i.e.
+    /// `CU_MEMPOOL_ATTR_REUSE_FOLLOW_EVENT_DEPENDENCIES`, `CU_MEMPOOL_ATTR_REUSE_ALLOW_OPPORTUNISTIC`
+    /// or `CU_MEMPOOL_ATTR_REUSE_ALLOW_INTERNAL_DEPENDENCIES`.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MALLOC__ASYNC.html#group__CUDA__MALLOC__ASYNC)
+    ///
+    /// # Safety
+    /// 1. The pool must not have been destroyed already
+    /// 2. `attr` must be one of the attributes listed above
+    pub unsafe fn get_reuse_attribute(
+        pool: sys::CUmemoryPool,
+        attr: sys::CUmemPool_attribute,
+    ) -> Result<bool, DriverError> {
+        let mut value: c_int = 0;
+        sys::cuMemPoolGetAttribute(pool, attr, &mut value as *mut c_int as *mut _).result()?;
+        Ok(value != 0)
+    }
+
+    /// Sets one of the boolean reuse policy attributes of `pool`, see [get_reuse_attribute].
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MALLOC__ASYNC.html#group__CUDA__MALLOC__ASYNC)
+    ///
+    /// # Safety
+    /// 1. The pool must not have been destroyed already
+    /// 2. `attr` must be one of the attributes listed in [get_reuse_attribute]
+    pub unsafe fn set_reuse_attribute(
+        pool: sys::CUmemoryPool,
+        attr: sys::CUmemPool_attribute,
+        value: bool,
+    ) -> Result<(), DriverError> {
+        let mut value: c_int = value as c_int;
+        sys::cuMemPoolSetAttribute(pool, attr, &mut value as *mut c_int as *mut _).result()
+    }
+
+    /// Gets one of the `u64` byte count attributes of `pool`, i.e.
+    /// `CU_MEMPOOL_ATTR_{RESERVED,USED}_MEM_{CURRENT,HIGH}` or `CU_MEMPOOL_ATTR_RELEASE_THRESHOLD`.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MALLOC__ASYNC.html#group__CUDA__MALLOC__ASYNC)
+    ///
+    /// # Safety
+    /// 1. The pool must not have been destroyed already
+    /// 2. `attr` must be one of the attributes listed above
+    pub unsafe fn get_mem_attribute(
+        pool: sys::CUmemoryPool,
+        attr: sys::CUmemPool_attribute,
+    ) -> Result<u64, DriverError> {
+        let mut value: u64 = 0;
+        sys::cuMemPoolGetAttribute(pool, attr, &mut value as *mut u64 as *mut _).result()?;
+        Ok(value)
+    }
+
+    /// Creates a memory pool with `props`. It must be destroyed with [destroy].
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MALLOC__ASYNC.html#group__CUDA__MALLOC__ASYNC)
+    ///
+    /// # Safety
+    /// 1. `props.location` must be a valid device
+    pub unsafe fn create(props: &sys::CUmemPoolProps) -> Result<sys::CUmemoryPool, DriverError> {
+        let mut pool = MaybeUninit::uninit();
+        sys::cuMemPoolCreate(pool.as_mut_ptr(), props).result()?;
+        Ok(pool.assume_init())
+    }
+
+    /// Destroys `pool`. Memory still allocated from it is freed once it is freed with
+    /// [super::free_async] or [super::free_sync].
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MALLOC__ASYNC.html#group__CUDA__MALLOC__ASYNC)
+    ///
+    /// # Safety
+    /// 1. The pool must not have been destroyed already
+    pub unsafe fn destroy(pool: sys::CUmemoryPool) -> Result<(), DriverError> {
+        sys::cuMemPoolDestroy(pool).result()
+    }
+
+    /// Allocates `num_bytes` from `pool` with stream ordered semantics.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MALLOC__ASYNC.html#group__CUDA__MALLOC__ASYNC)
+    ///
+    /// # Safety
+    /// 1. The pool must not have been destroyed already
+    /// 2. The memory return by this is unset, which may be invalid for `T`.
+    pub unsafe fn alloc_async(
+        pool: sys::CUmemoryPool,
+        num_bytes: usize,
+        stream: sys::CUstream,
+    ) -> Result<sys::CUdeviceptr, DriverError> {
+        let mut dev_ptr = MaybeUninit::uninit();
+        sys::cuMemAllocFromPoolAsync(dev_ptr.as_mut_ptr(), num_bytes, pool, stream).result()?;
+        Ok(dev_ptr.assume_init())
+    }
+
+    /// Exports `pool` as a posix file descriptor, which can be sent to another process
+    /// (e.g. over a unix socket) and imported with [import_from_shareable_handle_fd].
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MALLOC__ASYNC.html#group__CUDA__MALLOC__ASYNC)
+    ///
+    /// # Safety
+    /// 1. The pool must not have been destroyed already
+    /// 2. The pool must have been created with `CU_MEM_HANDLE_TYPE_POSIX_FILE_DESCRIPTOR`
+    #[cfg(unix)]
+    pub unsafe fn export_to_shareable_handle_fd(
+        pool: sys::CUmemoryPool,
+    ) -> Result<std::os::fd::RawFd, DriverError> {
+        let mut fd: std::os::fd::RawFd = -1;
+        sys::cuMemPoolExportToShareableHandle(
+            &mut fd as *mut std::os::fd::RawFd as *mut _,
+            pool,
+            sys::CUmemAllocationHandleType::CU_MEM_HANDLE_TYPE_POSIX_FILE_DESCRIPTOR,
+            0,
+        )
+        .result()?;
+        Ok(fd)
+    }
+
+    /// Imports a pool from a posix file descriptor created by [export_to_shareable_handle_fd].
+    /// The imported pool must be destroyed with [destroy], and can't be allocated from.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MALLOC__ASYNC.html#group__CUDA__MALLOC__ASYNC)
+    ///
+    /// # Safety
+    /// 1. `fd` must be a file descriptor exported from a memory pool
+    #[cfg(unix)]
+    pub unsafe fn import_from_shareable_handle_fd(
+        fd: std::os::fd::RawFd,
+    ) -> Result<sys::CUmemoryPool, DriverError> {
+        let mut pool = MaybeUninit::uninit();
+        sys::cuMemPoolImportFromShareableHandle(
+            pool.as_mut_ptr(),
+            fd as isize as *mut _,
+            sys::CUmemAllocationHandleType::CU_MEM_HANDLE_TYPE_POSIX_FILE_DESCRIPTOR,
+            0,
+        )
+        .result()?;
+        Ok(pool.assume_init())
+    }
+
+    /// Exports the data needed to import `ptr`, which must be allocated from an
+    /// exportable pool, into another process with [import_pointer].
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MALLOC__ASYNC.html#group__CUDA__MALLOC__ASYNC)
+    ///
+    /// # Safety
+    /// 1. `ptr` must not have been freed already
+    pub unsafe fn export_pointer(
+        ptr: sys::CUdeviceptr,
+    ) -> Result<sys::CUmemPoolPtrExportData, DriverError> {
+        let mut data = MaybeUninit::uninit();
+        sys::cuMemPoolExportPointer(data.as_mut_ptr(), ptr).result()?;
+        Ok(data.assume_init())
+    }
+
+    /// Imports a pointer exported with [export_pointer] from `pool`, which must be the
+    /// pool it was allocated from, imported with [import_from_shareable_handle_fd].
+    ///
+    /// The pointer must be freed (with [super::free_async] or [super::free_sync]) before
+    /// the exporting process frees it.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MALLOC__ASYNC.html#group__CUDA__MALLOC__ASYNC)
+    ///
+    /// # Safety
+    /// 1. The pool must not have been destroyed already
+    /// 2. The exported pointer must not have been freed by the exporting process
+    pub unsafe fn import_pointer(
+        pool: sys::CUmemoryPool,
+        data: &sys::CUmemPoolPtrExportData,
+    ) -> Result<sys::CUdeviceptr, DriverError> {
+        let mut dev_ptr = MaybeUninit::uninit();
+        let mut data = *data;
+        sys::cuMemPoolImportPointer(dev_ptr.as_mut_ptr(), pool, &mut data).result()?;
+        Ok(dev_ptr.assume_init())
+    }
+}
+
+pub mod graph {
+    //! Graph management functions (`cuGraph*`).
+    //!
+    //! See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH)
+
+    use super::{sys, DriverError};
+    use std::{mem::MaybeUninit, vec::Vec};
+
+    /// Destroys a graph.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH_1g718cfd9681f078693d4be2426fd689c8)
+    ///
+    /// # Safety
+    /// 1. The graph must not have been destroyed already
+    pub unsafe fn destroy(graph: sys::CUgraph) -> Result<(), DriverError> {
+        sys::cuGraphDestroy(graph).result()
+    }
+
+    /// Creates an empty graph.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH)
+    pub fn create() -> Result<sys::CUgraph, DriverError> {
+        let mut graph = MaybeUninit::uninit();
+        unsafe {
+            sys::cuGraphCreate(graph.as_mut_ptr(), 0).result()?;
+            Ok(graph.assume_init())
+        }
+    }
+
+    /// Deep copies `graph`, including all of its nodes and their dependencies.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH)
+    ///
+    /// # Safety
+    /// 1. The graph must not have been destroyed already
+    pub unsafe fn clone(graph: sys::CUgraph) -> Result<sys::CUgraph, DriverError> {
+        let mut clone = MaybeUninit::uninit();
+        sys::cuGraphClone(clone.as_mut_ptr(), graph).result()?;
+        Ok(clone.assume_init())
+    }
+
+    /// Returns all of the nodes in `graph`.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH)
+    ///
+    /// # Safety
+    /// 1. The graph must not have been destroyed already
+    pub unsafe fn get_nodes(graph: sys::CUgraph) -> Result<Vec<sys::CUgraphNode>, DriverError> {
+        let mut num_nodes = 0;
+        sys::cuGraphGetNodes(graph, std::ptr::null_mut(), &mut num_nodes).result()?;
+        let mut nodes = std::vec![std::ptr::null_mut(); num_nodes];
+        sys::cuGraphGetNodes(graph, nodes.as_mut_ptr(), &mut num_nodes).result()?;
+        nodes.truncate(num_nodes);
+        Ok(nodes)
+    }
+
+    /// Returns all of the dependency edges in `graph` as `(from, to)` pairs,
+    /// meaning `to` depends on `from`.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH)
+    ///
+    /// # Safety
+    /// 1. The graph must not have been destroyed already
+    pub unsafe fn get_edges(
+        graph: sys::CUgraph,
+    ) -> Result<Vec<(sys::CUgraphNode, sys::CUgraphNode)>, DriverError> {
+        let mut num_edges = 0;
+        sys::cuGraphGetEdges(
+            graph,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &mut num_edges,
+        )
+        .result()?;
+        let mut from = std::vec![std::ptr::null_mut(); num_edges];
+        let mut to = std::vec![std::ptr::null_mut(); num_edges];
+        sys::cuGraphGetEdges(graph, from.as_mut_ptr(), to.as_mut_ptr(), &mut num_edges).result()?;
+        Ok(from.into_iter().zip(to).take(num_edges).collect())
+    }
+
+    /// Returns the type of a graph node.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH)
+    ///
+    /// # Safety
+    /// 1. The node's graph must not have been destroyed already
+    pub unsafe fn node_get_type(
+        node: sys::CUgraphNode,
+    ) -> Result<sys::CUgraphNodeType, DriverError> {
+        let mut ty = MaybeUninit::uninit();
+        sys::cuGraphNodeGetType(node, ty.as_mut_ptr()).result()?;
+        Ok(ty.assume_init())
+    }
+
+    /// Adds a node to `graph` that records `event` once all of `dependencies` have completed.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH)
+    ///
+    /// # Safety
+    /// 1. The graph must not have been destroyed already, and `dependencies` must be its nodes
+    /// 2. The event must not be destroyed while the graph or any executable of it exists
+    pub unsafe fn add_event_record_node(
+        graph: sys::CUgraph,
+        dependencies: &[sys::CUgraphNode],
+        event: sys::CUevent,
+    ) -> Result<sys::CUgraphNode, DriverError> {
+        let mut node = MaybeUninit::uninit();
+        sys::cuGraphAddEventRecordNode(
+            node.as_mut_ptr(),
+            graph,
+            dependencies.as_ptr(),
+            dependencies.len(),
+            event,
+        )
+        .result()?;
+        Ok(node.assume_init())
+    }
+
+    /// Adds a node to `graph` that waits on `event` once all of `dependencies` have completed.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH)
+    ///
+    /// # Safety
+    /// 1. The graph must not have been destroyed already, and `dependencies` must be its nodes
+    /// 2. The event must not be destroyed while the graph or any executable of it exists
+    pub unsafe fn add_event_wait_node(
+        graph: sys::CUgraph,
+        dependencies: &[sys::CUgraphNode],
+        event: sys::CUevent,
+    ) -> Result<sys::CUgraphNode, DriverError> {
+        let mut node = MaybeUninit::uninit();
+        sys::cuGraphAddEventWaitNode(
+            node.as_mut_ptr(),
+            graph,
+            dependencies.as_ptr(),
+            dependencies.len(),
+            event,
+        )
+        .result()?;
+        Ok(node.assume_init())
+    }
+
+    /// Returns the parameters of a kernel node.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH)
+    ///
+    /// # Safety
+    /// 1. The node must be a kernel node, and its graph must not have been destroyed already
+    pub unsafe fn kernel_node_get_params(
+        node: sys::CUgraphNode,
+    ) -> Result<sys::CUDA_KERNEL_NODE_PARAMS, DriverError> {
+        let mut params = MaybeUninit::uninit();
+        sys::cuGraphKernelNodeGetParams(node, params.as_mut_ptr()).result()?;
+        Ok(params.assume_init())
+    }
+
+    /// Gets the value of a kernel node attribute.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH)
+    ///
+    /// # Safety
+    /// 1. The node must be a kernel node, and its graph must not have been destroyed already
+    pub unsafe fn kernel_node_get_attribute(
+        node: sys::CUgraphNode,
+        attr: sys::CUkernelNodeAttrID,
+    ) -> Result<sys::CUkernelNodeAttrValue, DriverError> {
+        let mut value = MaybeUninit::uninit();
+        sys::cuGraphKernelNodeGetAttribute(node, attr, value.as_mut_ptr()).result()?;
+        Ok(value.assume_init())
+    }
+
+    /// Sets the value of a kernel node attribute.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH)
+    ///
+    /// # Safety
+    /// 1. The node must be a kernel node, and its graph must not have been destroyed already
+    /// 2. `value` must be the union member that corresponds to `attr`
+    pub unsafe fn kernel_node_set_attribute(
+        node: sys::CUgraphNode,
+        attr: sys::CUkernelNodeAttrID,
+        value: &sys::CUkernelNodeAttrValue,
+    ) -> Result<(), DriverError> {
+        sys::cuGraphKernelNodeSetAttribute(node, attr, value).result()
+    }
+
+    /// Copies the attributes of the kernel node `src` onto the kernel node `dst`.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH)
+    ///
+    /// # Safety
+    /// 1. Both nodes must be kernel nodes of graphs that have not been destroyed already
+    pub unsafe fn kernel_node_copy_attributes(
+        dst: sys::CUgraphNode,
+        src: sys::CUgraphNode,
+    ) -> Result<(), DriverError> {
+        sys::cuGraphKernelNodeCopyAttributes(dst, src).result()
+    }
+
+    /// Sets the parameters of the kernel node `node` in the executable graph `exec`,
+    /// without affecting the graph it was instantiated from.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH)
+    ///
+    /// # Safety
+    /// 1. `node` must be a kernel node of the graph `exec` was instantiated from
+    /// 2. `params.func` must be the node's original function, and `params.kernelParams`
+    ///    must match its signature
+    pub unsafe fn exec_kernel_node_set_params(
+        exec: sys::CUgraphExec,
+        node: sys::CUgraphNode,
+        params: &sys::CUDA_KERNEL_NODE_PARAMS,
+    ) -> Result<(), DriverError> {
+        sys::cuGraphExecKernelNodeSetParams(exec, node, params).result()
+    }
+
+    /// Creates an executable graph from `graph`.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH_1gb53b435e178cccfa37ac87285d2c3fa1)
+    ///
+    /// # Safety
+    /// 1. The graph must not have been destroyed already
+    pub unsafe fn instantiate(
+        graph: sys::CUgraph,
+        flags: u64,
+    ) -> Result<sys::CUgraphExec, DriverError> {
+        let mut exec = MaybeUninit::uninit();
+        sys::cuGraphInstantiateWithFlags(exec.as_mut_ptr(), graph, flags).result()?;
+        Ok(exec.assume_init())
+    }
+
+    /// Destroys an executable graph.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH_1ga32ad4944cc5d408158207c978bc43a7)
+    ///
+    /// # Safety
+    /// 1. The executable graph must not have been destroyed already
+    pub unsafe fn exec_destroy(exec: sys::CUgraphExec) -> Result<(), DriverError> {
+        sys::cuGraphExecDestroy(exec).result()
+    }
+
+    /// Uploads an executable graph to the device on `stream`, without launching it.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH)
+    ///
+    /// # Safety
+    /// 1. The executable graph and stream must not have been destroyed already
+    pub unsafe fn upload(exec: sys::CUgraphExec, stream: sys::CUstream) -> Result<(), DriverError> {
+        sys::cuGraphUpload(exec, stream).result()
+    }
+
+    /// Launches an executable graph on `stream`.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__GRAPH.html#group__CUDA__GRAPH_1g6b2dceb3901e71a390d2bd8b0491e471)
+    ///
+    /// # Safety
+    /// 1. The executable graph and stream must not have been destroyed already
+    pub unsafe fn launch(exec: sys::CUgraphExec, stream: sys::CUstream) -> Result<(), DriverError> {
+        sys::cuGraphLaunch(exec, stream).result()
+    }
+}
+
+/// Allocates memory with stream ordered semantics.
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MALLOC__ASYNC.html#group__CUDA__MALLOC__ASYNC_1g13413273e84a641bce1929eae9e6501f)
+///
+/// # Safety
+/// 1. The stream should be an already created stream.
+/// 2. The memory return by this is unset, which may be invalid for `T`.
+/// 3. All uses of this memory must be on the same stream.
+pub unsafe fn malloc_async(
+    stream: sys::CUstream,
+    num_bytes: usize,
+) -> Result<sys::CUdeviceptr, DriverError> {
+    let mut dev_ptr = MaybeUninit::uninit();
+    sys::cuMemAllocAsync(dev_ptr.as_mut_ptr(), num_bytes, stream).result()?;
+    Ok(dev_ptr.assume_init())
+}
+
+/// Allocates memory
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1gb82d2a09844a58dd9e744dc31e8aa467)
+///
+/// # Safety
+/// 1. The memory return by this is unset, which may be invalid for `T`.
+pub unsafe fn malloc_sync(num_bytes: usize) -> Result<sys::CUdeviceptr, DriverError> {
+    let mut dev_ptr = MaybeUninit::uninit();
+    sys::cuMemAlloc_v2(dev_ptr.as_mut_ptr(), num_bytes).result()?;
+    Ok(dev_ptr.assume_init())
+}
+
+/// Allocates `height` rows of at least `width_bytes` each, padding the rows so that each
+/// one starts at an address aligned for coalesced access. Returns the pointer and the
+/// pitch, i.e. the number of bytes between the start of each row, chosen by the driver.
+///
+/// `element_size_bytes` is the size of the largest access that will be made, and must be 4, 8 or 16.
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM)
+///
+/// # Safety
+/// 1. The memory return by this is unset, which may be invalid for `T`.
+/// 2. It must be freed with [free_sync].
+pub unsafe fn malloc_pitch(
+    width_bytes: usize,
+    height: usize,
+    element_size_bytes: c_uint,
+) -> Result<(sys::CUdeviceptr, usize), DriverError> {
+    let mut dev_ptr = MaybeUninit::uninit();
+    let mut pitch = 0;
+    sys::cuMemAllocPitch_v2(
+        dev_ptr.as_mut_ptr(),
+        &mut pitch,
+        width_bytes,
+        height,
+        element_size_bytes,
+    )
+    .result()?;
+    Ok((dev_ptr.assume_init(), pitch))
+}
+
+/// Frees memory with stream ordered semantics.
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MALLOC__ASYNC.html#group__CUDA__MALLOC__ASYNC_1g41acf4131f672a2a75cd93d3241f10cf)
+///
+/// # Safety
+/// 1. The stream should be an already created stream.
+/// 2. The memory should have been allocated on this stream.
+/// 3. The memory should not have been freed already (double free)
+pub unsafe fn free_async(dptr: sys::CUdeviceptr, stream: sys::CUstream) -> Result<(), DriverError> {
+    sys::cuMemFreeAsync(dptr, stream).result()
+}
+
+/// Allocates memory
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1g89b3f154e17cc89b6eea277dbdf5c93a)
+///
 /// # Safety
 /// 1. The memory should have been allocated with malloc_sync
 pub unsafe fn free_sync(dptr: sys::CUdeviceptr) -> Result<(), DriverError> {
     sys::cuMemFree_v2(dptr).result()
 }
 
+/// Allocates page-locked host memory. `flags` is a combination of `sys::CU_MEMHOSTALLOC_*` values.
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1g572ca4011bfcb25034888a14d4e035b9)
+///
+/// # Safety
+/// 1. The memory return by this is unset, which may be invalid for `T`.
+/// 2. The memory must be freed with [free_host].
+pub unsafe fn malloc_host(num_bytes: usize, flags: c_uint) -> Result<*mut c_void, DriverError> {
+    let mut host_ptr = MaybeUninit::uninit();
+    sys::cuMemHostAlloc(host_ptr.as_mut_ptr(), num_bytes, flags).result()?;
+    Ok(host_ptr.assume_init())
+}
+
+/// Frees page-locked host memory.
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1g62e0fdbe181dab6b1c90fa1a51c7b92c)
+///
+/// # Safety
+/// 1. The memory should have been allocated with [malloc_host]
+/// 2. The memory should not have been freed already (double free)
+/// 3. All async accesses to this memory must have been completed.
+pub unsafe fn free_host(host_ptr: *mut c_void) -> Result<(), DriverError> {
+    sys::cuMemFreeHost(host_ptr).result()
+}
+
+/// Returns the device pointer for host memory allocated with [malloc_host]
+/// and the `sys::CU_MEMHOSTALLOC_DEVICEMAP` flag.
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1g57a39e5cba26af4d06be67fc77cc62f0)
+///
+/// # Safety
+/// 1. The memory should have been allocated with [malloc_host] and not freed already.
+pub unsafe fn host_get_device_ptr(host_ptr: *mut c_void) -> Result<sys::CUdeviceptr, DriverError> {
+    let mut dev_ptr = MaybeUninit::uninit();
+    sys::cuMemHostGetDevicePointer_v2(dev_ptr.as_mut_ptr(), host_ptr, 0).result()?;
+    Ok(dev_ptr.assume_init())
+}
+
+/// The [sys::CUdevice] value that refers to the host in the managed memory functions,
+/// e.g. [mem_advise] and [mem_prefetch_async]. This is `CU_DEVICE_CPU` in `cuda.h`.
+pub const DEVICE_CPU: sys::CUdevice = -1;
+
+/// The [sys::CUdevice] value that [mem_range_get_attribute] uses for "no device".
+/// This is `CU_DEVICE_INVALID` in `cuda.h`.
+pub const DEVICE_INVALID: sys::CUdevice = -2;
+
+/// Allocates managed memory that is accessible from both the host and devices.
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1gb347ded34dc326af404aa02af5388a32)
+///
+/// # Safety
+/// 1. The memory return by this is unset, which may be invalid for `T`.
+/// 2. The memory must be freed with [free_sync].
+pub unsafe fn malloc_managed(
+    num_bytes: usize,
+    flags: sys::CUmemAttach_flags,
+) -> Result<sys::CUdeviceptr, DriverError> {
+    let mut dev_ptr = MaybeUninit::uninit();
+    sys::cuMemAllocManaged(dev_ptr.as_mut_ptr(), num_bytes, flags as c_uint).result()?;
+    Ok(dev_ptr.assume_init())
+}
+
+/// Advises the driver about how the managed memory range will be used by `device`,
+/// which can be [DEVICE_CPU].
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__UNIFIED.html#group__CUDA__UNIFIED_1g27608c857a9254789c13f3e3b72029e2)
+///
+/// # Safety
+/// 1. The range must be part of memory allocated with [malloc_managed] that has not been freed.
+pub unsafe fn mem_advise(
+    dptr: sys::CUdeviceptr,
+    num_bytes: usize,
+    advice: sys::CUmem_advise,
+    device: sys::CUdevice,
+) -> Result<(), DriverError> {
+    sys::cuMemAdvise(dptr, num_bytes, advice, device).result()
+}
+
+/// Migrates the managed memory range to `device`, which can be [DEVICE_CPU],
+/// with stream ordered semantics.
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__UNIFIED.html#group__CUDA__UNIFIED_1gfe94f8b7fb56291ebcea44261aa4cb84)
+///
+/// # Safety
+/// 1. The range must be part of memory allocated with [malloc_managed] that has not been freed.
+/// 2. The stream must not have been destroyed.
+pub unsafe fn mem_prefetch_async(
+    dptr: sys::CUdeviceptr,
+    num_bytes: usize,
+    device: sys::CUdevice,
+    stream: sys::CUstream,
+) -> Result<(), DriverError> {
+    sys::cuMemPrefetchAsync(dptr, num_bytes, device, stream).result()
+}
+
+/// Queries `attribute` of a managed memory range, writing the result into `data`.
+///
+/// Every attribute is made up of 32 bit integers:
+/// - `CU_MEM_RANGE_ATTRIBUTE_READ_MOSTLY` is 1 value, either 0 or 1
+/// - `CU_MEM_RANGE_ATTRIBUTE_PREFERRED_LOCATION` & `CU_MEM_RANGE_ATTRIBUTE_LAST_PREFETCH_LOCATION`
+///   are 1 device, which is [DEVICE_CPU] for the host, or [DEVICE_INVALID] if unset
+/// - `CU_MEM_RANGE_ATTRIBUTE_ACCESSED_BY` is a list of devices, with unused entries
+///   set to [DEVICE_INVALID]
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__UNIFIED.html#group__CUDA__UNIFIED_1g4dd0b6c1e8feb7e1f8c1d5b55bdd8da4)
+///
+/// # Safety
+/// 1. The range must be part of memory allocated with [malloc_managed] that has not been freed.
+pub unsafe fn mem_range_get_attribute(
+    dptr: sys::CUdeviceptr,
+    num_bytes: usize,
+    attribute: sys::CUmem_range_attribute,
+    data: &mut [c_int],
+) -> Result<(), DriverError> {
+    sys::cuMemRangeGetAttribute(
+        data.as_mut_ptr() as *mut c_void,
+        std::mem::size_of_val(data),
+        attribute,
+        dptr,
+        num_bytes,
+    )
+    .result()
+}
+
 /// Frees device memory.
 ///
 /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1g89b3f154e17cc89b6eea277dbdf5c93a)
@@ -618,36 +1894,195 @@ pub unsafe fn memcpy_dtoh_sync<T>(dst: &mut [T], src: sys::CUdeviceptr) -> Resul
     sys::cuMemcpyDtoH_v2(dst.as_mut_ptr() as *mut _, src, std::mem::size_of_val(dst)).result()
 }
 
-/// Copies memory from Device to Device with stream ordered semantics.
+/// Copies memory from Device to Device with stream ordered semantics.
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1g39ea09ba682b8eccc9c3e0c04319b5c8)
+///
+/// # Safety
+/// 1. `T` must be the type that BOTH device pointers were allocated with.
+/// 2. Neither device pointer should not have been freed already (double free)
+/// 3. The stream should be the stream the memory was allocated on.
+pub unsafe fn memcpy_dtod_async(
+    dst: sys::CUdeviceptr,
+    src: sys::CUdeviceptr,
+    num_bytes: usize,
+    stream: sys::CUstream,
+) -> Result<(), DriverError> {
+    sys::cuMemcpyDtoDAsync_v2(dst, src, num_bytes, stream).result()
+}
+
+/// Copies memory from Device to Device
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1g1725774abf8b51b91945f3336b778c8b)
+///
+/// # Safety
+/// 1. `T` must be the type that BOTH device pointers were allocated with.
+/// 2. Neither device pointer should not have been freed already (double free)
+pub unsafe fn memcpy_dtod_sync(
+    dst: sys::CUdeviceptr,
+    src: sys::CUdeviceptr,
+    num_bytes: usize,
+) -> Result<(), DriverError> {
+    sys::cuMemcpyDtoD_v2(dst, src, num_bytes).result()
+}
+
+/// A single copy of `num_bytes` from `src` to `dst` for [memcpy_batch_async].
+///
+/// Since these are copied with unified addressing, `src` and `dst` can be either
+/// device pointers or host pointers cast to [sys::CUdeviceptr].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CopyDesc {
+    pub dst: sys::CUdeviceptr,
+    pub src: sys::CUdeviceptr,
+    pub num_bytes: usize,
+}
+
+/// Queues all of `copies` on `stream`, in order. Since they are on the same stream,
+/// each copy starts after the previous one completes, and later work on `stream`
+/// starts after all of them complete.
+///
+/// This issues one [sys::cuMemcpyAsync()] per copy, since `cuMemcpyBatchAsync`
+/// is not available in the CUDA version these bindings were generated for.
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1g5f26aaf5582ade791e5688727a178d78)
+///
+/// # Safety
+/// 1. All pointers must be valid for `num_bytes` until the copies complete.
+/// 2. Host memory that isn't page-locked is copied synchronously.
+pub unsafe fn memcpy_batch_async(
+    copies: &[CopyDesc],
+    stream: sys::CUstream,
+) -> Result<(), DriverError> {
+    for copy in copies.iter() {
+        sys::cuMemcpyAsync(copy.dst, copy.src, copy.num_bytes, stream).result()?;
+    }
+    Ok(())
+}
+
+/// The parameters of a 3D copy for [memcpy_3d], so that the large [sys::CUDA_MEMCPY3D]
+/// doesn't have to be filled by hand. Host endpoints are borrowed for `'a`.
+///
+/// Every endpoint is either host memory, linear device memory, or a CUDA array. Linear
+/// endpoints are described by a pitch (the number of bytes between rows) and a height
+/// (the number of rows between 2D slices).
+///
+/// ```ignore
+/// let params = Memcpy3DParams::new(width * 4, height, depth)
+///     .src_host(&volume, width * 4, height)
+///     .dst_device(dptr, pitch, height);
+/// unsafe { memcpy_3d(&params) }?;
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Memcpy3DParams<'a> {
+    pub raw: sys::CUDA_MEMCPY3D,
+    marker: std::marker::PhantomData<&'a ()>,
+}
+
+impl<'a> Memcpy3DParams<'a> {
+    /// Copies a box `width_bytes` wide, `height` rows high and `depth` slices deep.
+    /// For arrays, `width_bytes` is the width in elements times the element size.
+    pub fn new(width_bytes: usize, height: usize, depth: usize) -> Self {
+        let mut raw: sys::CUDA_MEMCPY3D = unsafe { std::mem::zeroed() };
+        raw.WidthInBytes = width_bytes;
+        raw.Height = height;
+        raw.Depth = depth;
+        Self {
+            raw,
+            marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Copies from host memory with rows of `pitch_bytes` and slices of `height` rows.
+    pub fn src_host<T>(mut self, src: &'a [T], pitch_bytes: usize, height: usize) -> Self {
+        self.raw.srcMemoryType = sys::CUmemorytype::CU_MEMORYTYPE_HOST;
+        self.raw.srcHost = src.as_ptr() as *const c_void;
+        self.raw.srcPitch = pitch_bytes;
+        self.raw.srcHeight = height;
+        self
+    }
+
+    /// Copies from linear device memory with rows of `pitch_bytes` and slices of `height` rows.
+    pub fn src_device(mut self, src: sys::CUdeviceptr, pitch_bytes: usize, height: usize) -> Self {
+        self.raw.srcMemoryType = sys::CUmemorytype::CU_MEMORYTYPE_DEVICE;
+        self.raw.srcDevice = src;
+        self.raw.srcPitch = pitch_bytes;
+        self.raw.srcHeight = height;
+        self
+    }
+
+    /// Copies from a CUDA array.
+    pub fn src_array(mut self, src: sys::CUarray) -> Self {
+        self.raw.srcMemoryType = sys::CUmemorytype::CU_MEMORYTYPE_ARRAY;
+        self.raw.srcArray = src;
+        self
+    }
+
+    /// Starts copying at byte `x_bytes` of row `y` of slice `z` of the source.
+    pub fn src_offset(mut self, x_bytes: usize, y: usize, z: usize) -> Self {
+        self.raw.srcXInBytes = x_bytes;
+        self.raw.srcY = y;
+        self.raw.srcZ = z;
+        self
+    }
+
+    /// Copies to host memory with rows of `pitch_bytes` and slices of `height` rows.
+    pub fn dst_host<T>(mut self, dst: &'a mut [T], pitch_bytes: usize, height: usize) -> Self {
+        self.raw.dstMemoryType = sys::CUmemorytype::CU_MEMORYTYPE_HOST;
+        self.raw.dstHost = dst.as_mut_ptr() as *mut c_void;
+        self.raw.dstPitch = pitch_bytes;
+        self.raw.dstHeight = height;
+        self
+    }
+
+    /// Copies to linear device memory with rows of `pitch_bytes` and slices of `height` rows.
+    pub fn dst_device(mut self, dst: sys::CUdeviceptr, pitch_bytes: usize, height: usize) -> Self {
+        self.raw.dstMemoryType = sys::CUmemorytype::CU_MEMORYTYPE_DEVICE;
+        self.raw.dstDevice = dst;
+        self.raw.dstPitch = pitch_bytes;
+        self.raw.dstHeight = height;
+        self
+    }
+
+    /// Copies to a CUDA array.
+    pub fn dst_array(mut self, dst: sys::CUarray) -> Self {
+        self.raw.dstMemoryType = sys::CUmemorytype::CU_MEMORYTYPE_ARRAY;
+        self.raw.dstArray = dst;
+        self
+    }
+
+    /// Starts copying at byte `x_bytes` of row `y` of slice `z` of the destination.
+    pub fn dst_offset(mut self, x_bytes: usize, y: usize, z: usize) -> Self {
+        self.raw.dstXInBytes = x_bytes;
+        self.raw.dstY = y;
+        self.raw.dstZ = z;
+        self
+    }
+}
+
+/// Synchronously performs the 3D copy described by `params`.
 ///
-/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1g39ea09ba682b8eccc9c3e0c04319b5c8)
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM)
 ///
 /// # Safety
-/// 1. `T` must be the type that BOTH device pointers were allocated with.
-/// 2. Neither device pointer should not have been freed already (double free)
-/// 3. The stream should be the stream the memory was allocated on.
-pub unsafe fn memcpy_dtod_async(
-    dst: sys::CUdeviceptr,
-    src: sys::CUdeviceptr,
-    num_bytes: usize,
-    stream: sys::CUstream,
-) -> Result<(), DriverError> {
-    sys::cuMemcpyDtoDAsync_v2(dst, src, num_bytes, stream).result()
+/// 1. Both endpoints must be set and contain the whole box being copied
+/// 2. Device pointers and arrays must not have been freed
+pub unsafe fn memcpy_3d(params: &Memcpy3DParams) -> Result<(), DriverError> {
+    sys::cuMemcpy3D_v2(&params.raw).result()
 }
 
-/// Copies memory from Device to Device
+/// Queues the 3D copy described by `params` on `stream`.
 ///
-/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1g1725774abf8b51b91945f3336b778c8b)
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM)
 ///
 /// # Safety
-/// 1. `T` must be the type that BOTH device pointers were allocated with.
-/// 2. Neither device pointer should not have been freed already (double free)
-pub unsafe fn memcpy_dtod_sync(
-    dst: sys::CUdeviceptr,
-    src: sys::CUdeviceptr,
-    num_bytes: usize,
+/// 1. Both endpoints must be set and contain the whole box being copied
+/// 2. All the memory must stay valid until the copy completes
+/// 3. Host memory that isn't page-locked is copied synchronously
+pub unsafe fn memcpy_3d_async(
+    params: &Memcpy3DParams,
+    stream: sys::CUstream,
 ) -> Result<(), DriverError> {
-    sys::cuMemcpyDtoD_v2(dst, src, num_bytes).result()
+    sys::cuMemcpy3DAsync_v2(&params.raw, stream).result()
 }
 
 /// Returns (free, total) memory in bytes.
@@ -660,6 +2095,70 @@ pub fn mem_get_info() -> Result<(usize, usize), DriverError> {
     Ok((free, total))
 }
 
+/// Returns the type of memory `ptr` points to. Pageable host memory that was not
+/// registered with the driver is not known to it, and returns `CUDA_ERROR_INVALID_VALUE`.
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__UNIFIED.html#group__CUDA__UNIFIED)
+///
+/// # Safety
+/// A context must be bound to the calling thread.
+pub unsafe fn pointer_get_memory_type(
+    ptr: sys::CUdeviceptr,
+) -> Result<sys::CUmemorytype, DriverError> {
+    let mut ty = MaybeUninit::<sys::CUmemorytype>::uninit();
+    sys::cuPointerGetAttribute(
+        ty.as_mut_ptr() as *mut _,
+        sys::CUpointer_attribute::CU_POINTER_ATTRIBUTE_MEMORY_TYPE,
+        ptr,
+    )
+    .result()?;
+    Ok(ty.assume_init())
+}
+
+/// Returns the ordinal of the device that `ptr` was allocated on, or that its
+/// context is associated with.
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__UNIFIED.html#group__CUDA__UNIFIED)
+///
+/// # Safety
+/// A context must be bound to the calling thread.
+pub unsafe fn pointer_get_device_ordinal(ptr: sys::CUdeviceptr) -> Result<i32, DriverError> {
+    let mut ordinal = MaybeUninit::<i32>::uninit();
+    sys::cuPointerGetAttribute(
+        ordinal.as_mut_ptr() as *mut _,
+        sys::CUpointer_attribute::CU_POINTER_ATTRIBUTE_DEVICE_ORDINAL,
+        ptr,
+    )
+    .result()?;
+    Ok(ordinal.assume_init())
+}
+
+/// Returns the start address and size in bytes of the allocation that contains `ptr`.
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__UNIFIED.html#group__CUDA__UNIFIED)
+///
+/// # Safety
+/// A context must be bound to the calling thread.
+pub unsafe fn pointer_get_range(
+    ptr: sys::CUdeviceptr,
+) -> Result<(sys::CUdeviceptr, usize), DriverError> {
+    let mut start = MaybeUninit::<sys::CUdeviceptr>::uninit();
+    sys::cuPointerGetAttribute(
+        start.as_mut_ptr() as *mut _,
+        sys::CUpointer_attribute::CU_POINTER_ATTRIBUTE_RANGE_START_ADDR,
+        ptr,
+    )
+    .result()?;
+    let mut size = MaybeUninit::<usize>::uninit();
+    sys::cuPointerGetAttribute(
+        size.as_mut_ptr() as *mut _,
+        sys::CUpointer_attribute::CU_POINTER_ATTRIBUTE_RANGE_SIZE,
+        ptr,
+    )
+    .result()?;
+    Ok((start.assume_init(), size.assume_init()))
+}
+
 pub mod module {
     //! Module management functions (`cuModule*`).
     //!
@@ -715,6 +2214,24 @@ pub mod module {
         Ok(func.assume_init())
     }
 
+    /// Returns the address and size in bytes of a `__device__` global from the given module.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MODULE.html#group__CUDA__MODULE)
+    ///
+    /// # Safety
+    /// `module` must be a properly allocated and not freed module.
+    pub unsafe fn get_global(
+        module: sys::CUmodule,
+        name: CString,
+    ) -> Result<(sys::CUdeviceptr, usize), DriverError> {
+        let name_ptr = name.as_c_str().as_ptr();
+        let mut dptr = MaybeUninit::uninit();
+        let mut bytes = MaybeUninit::uninit();
+        sys::cuModuleGetGlobal_v2(dptr.as_mut_ptr(), bytes.as_mut_ptr(), module, name_ptr)
+            .result()?;
+        Ok((dptr.assume_init(), bytes.assume_init()))
+    }
+
     /// Unloads a module.
     ///
     /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MODULE.html#group__CUDA__MODULE_1g8ea3d716524369de3763104ced4ea57b)
@@ -751,6 +2268,45 @@ pub mod event {
         unsafe { sys::cuEventRecord(event, stream).result() }
     }
 
+    /// Records an event with the specified flags.
+    ///
+    /// `CU_EVENT_RECORD_EXTERNAL` is needed when the event is waited on by
+    /// work outside of the current stream capture (e.g. for graphics interop),
+    /// and requires CUDA 11.1+.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EVENT.html#group__CUDA__EVENT_1ge577e0c132d9b4c7b8bd2e3b0a4d8e9c)
+    ///
+    /// # Safety
+    /// 1. Both stream and event must not have been freed already
+    pub unsafe fn record_with_flags(
+        event: sys::CUevent,
+        stream: sys::CUstream,
+        flags: sys::CUevent_record_flags,
+    ) -> Result<(), DriverError> {
+        sys::cuEventRecordWithFlags(event, stream, flags as u32).result()
+    }
+
+    /// Queries an event's status. Returns `CUDA_ERROR_NOT_READY` if the
+    /// work captured by the event has not completed yet.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EVENT.html#group__CUDA__EVENT_1g6f0704d755066b0ee705749ae911deef)
+    ///
+    /// # Safety
+    /// 1. Event must not have been freed already
+    pub unsafe fn query(event: sys::CUevent) -> Result<(), DriverError> {
+        sys::cuEventQuery(event).result()
+    }
+
+    /// Waits for an event to complete.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EVENT.html#group__CUDA__EVENT_1g9e520d34e51af7f5375610bca4add99c)
+    ///
+    /// # Safety
+    /// 1. Event must not have been freed already
+    pub unsafe fn synchronize(event: sys::CUevent) -> Result<(), DriverError> {
+        sys::cuEventSynchronize(event).result()
+    }
+
     /// Computes the elapsed time (in milliseconds) between two events.
     ///
     /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EVENT.html#group__CUDA__EVENT_1gdfb1178807353bbcaa9e245da497cf97)
@@ -819,6 +2375,204 @@ pub unsafe fn launch_kernel(
     .result()
 }
 
+/// Launch a cuda kernel with extended launch `attrs`, such as a cluster dimension.
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXEC.html#group__CUDA__EXEC_1gb9c891eb6bb8f4089758e64c9c976db9)
+///
+/// # Safety
+/// This method is **very unsafe**. All the same rules as [launch_kernel] apply, and
+/// additionally `attrs` must be supported by the device the function is launched on.
+#[inline]
+pub unsafe fn launch_kernel_ex(
+    f: sys::CUfunction,
+    grid_dim: (c_uint, c_uint, c_uint),
+    block_dim: (c_uint, c_uint, c_uint),
+    shared_mem_bytes: c_uint,
+    stream: sys::CUstream,
+    attrs: &mut [sys::CUlaunchAttribute],
+    kernel_params: &mut [*mut c_void],
+) -> Result<(), DriverError> {
+    let config = sys::CUlaunchConfig {
+        gridDimX: grid_dim.0,
+        gridDimY: grid_dim.1,
+        gridDimZ: grid_dim.2,
+        blockDimX: block_dim.0,
+        blockDimY: block_dim.1,
+        blockDimZ: block_dim.2,
+        sharedMemBytes: shared_mem_bytes,
+        hStream: stream,
+        attrs: attrs.as_mut_ptr(),
+        numAttrs: attrs.len() as c_uint,
+    };
+    sys::cuLaunchKernelEx(&config, f, kernel_params.as_mut_ptr(), std::ptr::null_mut()).result()
+}
+
+pub mod array {
+    //! CUDA array management functions (`cuArray*`).
+    //!
+    //! See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM)
+
+    use super::{sys, DriverError};
+    use core::ffi::c_void;
+    use std::mem::MaybeUninit;
+
+    /// Creates a CUDA array. `Height` and `Depth` of `desc` are 0 for 1D and 2D arrays.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1gc2322c70b38c2984536c90ed118bb1d7)
+    ///
+    /// # Safety
+    /// 1. A context must be bound to the calling thread
+    /// 2. The contents of the array are unset.
+    pub unsafe fn create(desc: &sys::CUDA_ARRAY3D_DESCRIPTOR) -> Result<sys::CUarray, DriverError> {
+        let mut array = MaybeUninit::uninit();
+        sys::cuArray3DCreate_v2(array.as_mut_ptr(), desc).result()?;
+        Ok(array.assume_init())
+    }
+
+    /// Returns the descriptor `array` was created with.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM)
+    ///
+    /// # Safety
+    /// 1. The array must not have been destroyed already
+    pub unsafe fn get_descriptor(
+        array: sys::CUarray,
+    ) -> Result<sys::CUDA_ARRAY3D_DESCRIPTOR, DriverError> {
+        let mut desc = MaybeUninit::uninit();
+        sys::cuArray3DGetDescriptor_v2(desc.as_mut_ptr(), array).result()?;
+        Ok(desc.assume_init())
+    }
+
+    /// Destroys a CUDA array.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1g982878affbbc023de84874214d2ca9f2)
+    ///
+    /// # Safety
+    /// 1. The array must not have been destroyed already.
+    pub unsafe fn destroy(array: sys::CUarray) -> Result<(), DriverError> {
+        sys::cuArrayDestroy(array).result()
+    }
+
+    /// Creates a mipmapped CUDA array with `num_levels` levels, where level 0 is
+    /// described by `desc`.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM)
+    ///
+    /// # Safety
+    /// 1. A context must be bound to the calling thread
+    /// 2. The contents of the array are unset.
+    pub unsafe fn mipmapped_create(
+        desc: &sys::CUDA_ARRAY3D_DESCRIPTOR,
+        num_levels: u32,
+    ) -> Result<sys::CUmipmappedArray, DriverError> {
+        let mut mipmap = MaybeUninit::uninit();
+        sys::cuMipmappedArrayCreate(mipmap.as_mut_ptr(), desc, num_levels).result()?;
+        Ok(mipmap.assume_init())
+    }
+
+    /// Returns the array for `level` of `mipmap`. The array is owned by `mipmap`.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM)
+    ///
+    /// # Safety
+    /// 1. The mipmapped array must not have been destroyed already.
+    /// 2. The returned array must not be destroyed, and must not be used after `mipmap` is destroyed.
+    pub unsafe fn mipmapped_get_level(
+        mipmap: sys::CUmipmappedArray,
+        level: u32,
+    ) -> Result<sys::CUarray, DriverError> {
+        let mut array = MaybeUninit::uninit();
+        sys::cuMipmappedArrayGetLevel(array.as_mut_ptr(), mipmap, level).result()?;
+        Ok(array.assume_init())
+    }
+
+    /// Destroys a mipmapped CUDA array, including the arrays of all of its levels.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM)
+    ///
+    /// # Safety
+    /// 1. The mipmapped array must not have been destroyed already.
+    pub unsafe fn mipmapped_destroy(mipmap: sys::CUmipmappedArray) -> Result<(), DriverError> {
+        sys::cuMipmappedArrayDestroy(mipmap).result()
+    }
+
+    /// Synchronously copies `src`, which is rows of `width_bytes`, into the 1D or 2D `array`.
+    /// The first byte of `src` is copied to byte `x_offset_bytes` of row `y_offset` of `array`.
+    ///
+    /// Note that `x_offset_bytes` & `width_bytes` are in bytes, not elements, and `y_offset` is in rows.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1g27f885b30c34cc20a663a671dbf6fc27)
+    ///
+    /// # Safety
+    /// 1. The array must not have been destroyed.
+    /// 2. The region must be inside of `array`.
+    pub unsafe fn copy_to_array<T>(
+        array: sys::CUarray,
+        x_offset_bytes: usize,
+        y_offset: usize,
+        src: &[T],
+        width_bytes: usize,
+    ) -> Result<(), DriverError> {
+        let copy = sys::CUDA_MEMCPY2D {
+            srcXInBytes: 0,
+            srcY: 0,
+            srcMemoryType: sys::CUmemorytype::CU_MEMORYTYPE_HOST,
+            srcHost: src.as_ptr() as *const c_void,
+            srcDevice: 0,
+            srcArray: std::ptr::null_mut(),
+            srcPitch: width_bytes,
+            dstXInBytes: x_offset_bytes,
+            dstY: y_offset,
+            dstMemoryType: sys::CUmemorytype::CU_MEMORYTYPE_ARRAY,
+            dstHost: std::ptr::null_mut(),
+            dstDevice: 0,
+            dstArray: array,
+            dstPitch: 0,
+            WidthInBytes: width_bytes,
+            Height: std::mem::size_of_val(src) / width_bytes,
+        };
+        sys::cuMemcpy2D_v2(&copy).result()
+    }
+
+    /// Synchronously copies rows of `width_bytes` out of the 1D or 2D `array` into `dst`.
+    /// Byte `x_offset_bytes` of row `y_offset` of `array` is copied to the first byte of `dst`.
+    ///
+    /// Note that `x_offset_bytes` & `width_bytes` are in bytes, not elements, and `y_offset` is in rows.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM_1g27f885b30c34cc20a663a671dbf6fc27)
+    ///
+    /// # Safety
+    /// 1. The array must not have been destroyed.
+    /// 2. The region must be inside of `array`.
+    pub unsafe fn copy_from_array<T>(
+        dst: &mut [T],
+        array: sys::CUarray,
+        x_offset_bytes: usize,
+        y_offset: usize,
+        width_bytes: usize,
+    ) -> Result<(), DriverError> {
+        let copy = sys::CUDA_MEMCPY2D {
+            srcXInBytes: x_offset_bytes,
+            srcY: y_offset,
+            srcMemoryType: sys::CUmemorytype::CU_MEMORYTYPE_ARRAY,
+            srcHost: std::ptr::null(),
+            srcDevice: 0,
+            srcArray: array,
+            srcPitch: 0,
+            dstXInBytes: 0,
+            dstY: 0,
+            dstMemoryType: sys::CUmemorytype::CU_MEMORYTYPE_HOST,
+            dstHost: dst.as_mut_ptr() as *mut c_void,
+            dstDevice: 0,
+            dstArray: std::ptr::null_mut(),
+            dstPitch: width_bytes,
+            WidthInBytes: width_bytes,
+            Height: std::mem::size_of_val(dst) / width_bytes,
+        };
+        sys::cuMemcpy2D_v2(&copy).result()
+    }
+}
+
 pub mod external_memory {
     use std::mem::MaybeUninit;
 
@@ -918,3 +2672,321 @@ pub mod external_memory {
         Ok(device_ptr.assume_init())
     }
 }
+
+pub mod external_semaphore {
+    //! External semaphore interop functions (`cu*ExternalSemaphore*`).
+    //!
+    //! See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXTRES__INTEROP.html#group__CUDA__EXTRES__INTEROP)
+
+    use std::mem::MaybeUninit;
+
+    use super::{sys, DriverError};
+
+    /// Imports an external semaphore from a file descriptor, either
+    /// `CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD` or
+    /// `CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_TIMELINE_SEMAPHORE_FD`.
+    ///
+    /// The semaphore should be destroyed using [`destroy_external_semaphore`].
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXTRES__INTEROP.html#group__CUDA__EXTRES__INTEROP)
+    ///
+    /// # Safety
+    /// `fd` must be a semaphore of type `handle_type`. Ownership of `fd` is transferred to the
+    /// driver if the import succeeds.
+    #[cfg(unix)]
+    pub unsafe fn import_external_semaphore_fd(
+        fd: std::os::fd::RawFd,
+        handle_type: sys::CUexternalSemaphoreHandleType,
+    ) -> Result<sys::CUexternalSemaphore, DriverError> {
+        let mut external_semaphore = MaybeUninit::uninit();
+        let handle_description = sys::CUDA_EXTERNAL_SEMAPHORE_HANDLE_DESC {
+            type_: handle_type,
+            handle: sys::CUDA_EXTERNAL_SEMAPHORE_HANDLE_DESC_st__bindgen_ty_1 { fd },
+            ..Default::default()
+        };
+        sys::cuImportExternalSemaphore(external_semaphore.as_mut_ptr(), &handle_description)
+            .result()?;
+        Ok(external_semaphore.assume_init())
+    }
+
+    /// Imports an external semaphore from an NT handle, e.g.
+    /// `CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32`,
+    /// `CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_TIMELINE_SEMAPHORE_WIN32` or
+    /// `CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_D3D12_FENCE`.
+    ///
+    /// The semaphore should be destroyed using [`destroy_external_semaphore`].
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXTRES__INTEROP.html#group__CUDA__EXTRES__INTEROP)
+    ///
+    /// # Safety
+    /// `handle` must be a semaphore of type `handle_type`.
+    #[cfg(windows)]
+    pub unsafe fn import_external_semaphore_win32(
+        handle: std::os::windows::io::RawHandle,
+        handle_type: sys::CUexternalSemaphoreHandleType,
+    ) -> Result<sys::CUexternalSemaphore, DriverError> {
+        let mut external_semaphore = MaybeUninit::uninit();
+        let handle_description = sys::CUDA_EXTERNAL_SEMAPHORE_HANDLE_DESC {
+            type_: handle_type,
+            handle: sys::CUDA_EXTERNAL_SEMAPHORE_HANDLE_DESC_st__bindgen_ty_1 {
+                win32: sys::CUDA_EXTERNAL_SEMAPHORE_HANDLE_DESC_st__bindgen_ty_1__bindgen_ty_1 {
+                    handle,
+                    name: std::ptr::null(),
+                },
+            },
+            ..Default::default()
+        };
+        sys::cuImportExternalSemaphore(external_semaphore.as_mut_ptr(), &handle_description)
+            .result()?;
+        Ok(external_semaphore.assume_init())
+    }
+
+    /// Destroys an external semaphore object.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXTRES__INTEROP.html#group__CUDA__EXTRES__INTEROP)
+    ///
+    /// # Safety
+    /// 1. No outstanding signals or waits on the semaphore may be queued.
+    /// 2. The external semaphore must only be destroyed once.
+    pub unsafe fn destroy_external_semaphore(
+        external_semaphore: sys::CUexternalSemaphore,
+    ) -> Result<(), DriverError> {
+        sys::cuDestroyExternalSemaphore(external_semaphore).result()
+    }
+
+    /// Queues a signal of `external_semaphore` on `stream`.
+    ///
+    /// For fences and timeline semaphores `params.params.fence.value` is the value the
+    /// semaphore is set to. Binary semaphores ignore it. `params.params.keyedMutex.key`
+    /// is the key released for keyed mutexes.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXTRES__INTEROP.html#group__CUDA__EXTRES__INTEROP)
+    ///
+    /// # Safety
+    /// The semaphore and stream must not have been destroyed already.
+    pub unsafe fn signal_external_semaphore_async(
+        external_semaphore: sys::CUexternalSemaphore,
+        params: &sys::CUDA_EXTERNAL_SEMAPHORE_SIGNAL_PARAMS,
+        stream: sys::CUstream,
+    ) -> Result<(), DriverError> {
+        sys::cuSignalExternalSemaphoresAsync(&external_semaphore, params, 1, stream).result()
+    }
+
+    /// Queues a wait on `external_semaphore` on `stream`: work queued on `stream`
+    /// afterwards doesn't start until the semaphore is signaled.
+    ///
+    /// For fences and timeline semaphores `params.params.fence.value` is the value to wait
+    /// for the semaphore to reach. Binary semaphores ignore it. `params.params.keyedMutex`
+    /// holds the key to acquire and a timeout in milliseconds for keyed mutexes.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EXTRES__INTEROP.html#group__CUDA__EXTRES__INTEROP)
+    ///
+    /// # Safety
+    /// The semaphore and stream must not have been destroyed already.
+    pub unsafe fn wait_external_semaphore_async(
+        external_semaphore: sys::CUexternalSemaphore,
+        params: &sys::CUDA_EXTERNAL_SEMAPHORE_WAIT_PARAMS,
+        stream: sys::CUstream,
+    ) -> Result<(), DriverError> {
+        sys::cuWaitExternalSemaphoresAsync(&external_semaphore, params, 1, stream).result()
+    }
+}
+
+pub mod ipc {
+    //! Inter process communication functions (`cuIpc*`).
+    //!
+    //! See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM)
+
+    use super::{sys, DriverError};
+    use core::ffi::c_uint;
+    use std::mem::MaybeUninit;
+
+    /// Creates an event that can be shared with other processes with [get_event_handle].
+    /// Interprocess events can't be used for timing.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__EVENT.html#group__CUDA__EVENT)
+    pub fn create_event() -> Result<sys::CUevent, DriverError> {
+        let flags = sys::CUevent_flags::CU_EVENT_INTERPROCESS as c_uint
+            | sys::CUevent_flags::CU_EVENT_DISABLE_TIMING as c_uint;
+        let mut event = MaybeUninit::uninit();
+        unsafe {
+            sys::cuEventCreate(event.as_mut_ptr(), flags).result()?;
+            Ok(event.assume_init())
+        }
+    }
+
+    /// Gets a handle to `event`, which must have been created with [create_event],
+    /// for opening in another process with [open_event_handle].
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM)
+    ///
+    /// # Safety
+    /// 1. The event must not have been destroyed already
+    pub unsafe fn get_event_handle(
+        event: sys::CUevent,
+    ) -> Result<sys::CUipcEventHandle, DriverError> {
+        let mut handle = MaybeUninit::uninit();
+        sys::cuIpcGetEventHandle(handle.as_mut_ptr(), event).result()?;
+        Ok(handle.assume_init())
+    }
+
+    /// Opens an event handle from another process. The returned event is destroyed
+    /// with [super::event::destroy] like any other.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM)
+    ///
+    /// # Safety
+    /// 1. `handle` must be from [get_event_handle] in another process
+    pub unsafe fn open_event_handle(
+        handle: sys::CUipcEventHandle,
+    ) -> Result<sys::CUevent, DriverError> {
+        let mut event = MaybeUninit::uninit();
+        sys::cuIpcOpenEventHandle(event.as_mut_ptr(), handle).result()?;
+        Ok(event.assume_init())
+    }
+
+    /// Gets a handle to the allocation starting at `dptr`, for opening in another process
+    /// with [open_mem_handle]. The allocation must be from [super::malloc_sync], not a
+    /// memory pool.
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM)
+    ///
+    /// # Safety
+    /// 1. `dptr` must not have been freed already
+    pub unsafe fn get_mem_handle(
+        dptr: sys::CUdeviceptr,
+    ) -> Result<sys::CUipcMemHandle, DriverError> {
+        let mut handle = MaybeUninit::uninit();
+        sys::cuIpcGetMemHandle(handle.as_mut_ptr(), dptr).result()?;
+        Ok(handle.assume_init())
+    }
+
+    /// Maps an allocation from another process into this one. Free the returned pointer
+    /// with [close_mem_handle], not [super::free_sync].
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM)
+    ///
+    /// # Safety
+    /// 1. `handle` must be from [get_mem_handle] in another process
+    pub unsafe fn open_mem_handle(
+        handle: sys::CUipcMemHandle,
+        flags: sys::CUipcMem_flags,
+    ) -> Result<sys::CUdeviceptr, DriverError> {
+        let mut dptr = MaybeUninit::uninit();
+        sys::cuIpcOpenMemHandle_v2(dptr.as_mut_ptr(), handle, flags as c_uint).result()?;
+        Ok(dptr.assume_init())
+    }
+
+    /// Unmaps an allocation opened with [open_mem_handle].
+    ///
+    /// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__MEM.html#group__CUDA__MEM)
+    ///
+    /// # Safety
+    /// 1. `dptr` must be from [open_mem_handle] and not already closed
+    pub unsafe fn close_mem_handle(dptr: sys::CUdeviceptr) -> Result<(), DriverError> {
+        sys::cuIpcCloseMemHandle(dptr).result()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_version_compatibility() {
+        assert!(
+            DriverError(sys::CUresult::CUDA_ERROR_UNSUPPORTED_PTX_VERSION)
+                .is_unsupported_ptx_version()
+        );
+        assert!(!DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE).is_unsupported_ptx_version());
+
+        let mismatch = VersionMismatch {
+            driver_version: 11040,
+            nvrtc_version: 12030,
+        };
+        assert!(DriverError::from(mismatch).is_unsupported_ptx_version());
+        let message = std::format!("{mismatch}");
+        assert!(message.contains("11.4"));
+        assert!(message.contains("12.3"));
+    }
+
+    #[test]
+    fn test_get_proc_address() {
+        init().unwrap();
+        let flags = sys::CUdriverProcAddress_flags::CU_GET_PROC_ADDRESS_DEFAULT;
+        let pfn = get_proc_address(c"cuStreamSynchronize", flags).unwrap();
+        assert!(!pfn.is_null());
+        assert_eq!(
+            get_proc_address(c"cuNotARealFunction", flags).unwrap_err(),
+            DriverError(sys::CUresult::CUDA_ERROR_NOT_FOUND)
+        );
+    }
+
+    #[test]
+    fn test_memcpy_batch_async() {
+        let dev = crate::driver::CudaDevice::new(0).unwrap();
+        let dst = dev.alloc_zeros::<u32>(50 * 4).unwrap();
+        let src: Vec<[u32; 4]> = (0..50).map(|i| [i; 4]).collect();
+        let copies: Vec<CopyDesc> = src
+            .iter()
+            .enumerate()
+            .map(|(i, row)| CopyDesc {
+                dst: dst.cu_device_ptr + (i * std::mem::size_of_val(row)) as sys::CUdeviceptr,
+                src: row.as_ptr() as sys::CUdeviceptr,
+                num_bytes: std::mem::size_of_val(row),
+            })
+            .collect();
+        unsafe { memcpy_batch_async(&copies, *dev.cu_stream()) }.unwrap();
+        dev.synchronize().unwrap();
+
+        let mut host = vec![0; 50 * 4];
+        dev.dtoh_sync_copy_into(&dst, &mut host).unwrap();
+        for (i, row) in host.chunks(4).enumerate() {
+            assert_eq!(row, [i as u32; 4]);
+        }
+    }
+
+    #[test]
+    fn test_is_ecc_uncorrectable() {
+        assert!(DriverError(sys::CUresult::CUDA_ERROR_ECC_UNCORRECTABLE).is_ecc_uncorrectable());
+        assert!(!DriverError(sys::CUresult::CUDA_ERROR_OUT_OF_MEMORY).is_ecc_uncorrectable());
+    }
+
+    #[test]
+    fn test_is_deinitialized() {
+        assert!(DriverError(sys::CUresult::CUDA_ERROR_DEINITIALIZED).is_deinitialized());
+        assert!(!DriverError(sys::CUresult::CUDA_ERROR_NOT_INITIALIZED).is_deinitialized());
+    }
+
+    #[test]
+    fn test_memcpy_3d() {
+        let dev = crate::driver::CudaDevice::new(0).unwrap();
+        let (w, h, d) = (5usize, 4usize, 3usize);
+        let src: Vec<u16> = (0..w * h * d).map(|i| i as u16).collect();
+        let row = w * std::mem::size_of::<u16>();
+        let buf = dev.alloc_zeros::<u16>(w * h * d).unwrap();
+        let dptr = buf.cu_device_ptr;
+
+        let upload = Memcpy3DParams::new(row, h, d)
+            .src_host(&src, row, h)
+            .dst_device(dptr, row, h);
+        unsafe { memcpy_3d(&upload) }.unwrap();
+
+        // copy the inner 3x2x2 box back out
+        let mut dst = [0u16; 12];
+        let download = Memcpy3DParams::new(3 * 2, 2, 2)
+            .src_device(dptr, row, h)
+            .src_offset(2, 1, 1)
+            .dst_host(&mut dst, 3 * 2, 2);
+        unsafe { memcpy_3d(&download) }.unwrap();
+        let mut expected = Vec::new();
+        for z in 1..3 {
+            for y in 1..3 {
+                for x in 1..4 {
+                    expected.push(src[z * w * h + y * w + x]);
+                }
+            }
+        }
+        assert_eq!(dst.to_vec(), expected);
+    }
+}
@@ -1,9 +1,10 @@
 use crate::driver::{result, sys};
 
-use super::core::{CudaDevice, CudaSlice, CudaView, CudaViewMut};
+use super::core::{CudaDevice, CudaSlice, CudaStream, CudaView, CudaViewMut};
 use super::device_ptr::{DevicePtr, DevicePtrMut, DeviceSlice};
+use super::event::CudaEvent;
 
-use std::{marker::Unpin, pin::Pin, sync::Arc, vec::Vec};
+use std::{marker::Unpin, ops::RangeBounds, pin::Pin, sync::Arc, vec::Vec};
 
 /// Something that can be copied to device memory and
 /// turned into a parameter for [result::launch_kernel].
@@ -20,6 +21,25 @@ pub unsafe trait DeviceRepr {
     }
 }
 
+fn check_reuse_attribute(attr: sys::CUmemPool_attribute) -> Result<(), result::DriverError> {
+    use sys::CUmemPool_attribute::*;
+    match attr {
+        CU_MEMPOOL_ATTR_REUSE_FOLLOW_EVENT_DEPENDENCIES
+        | CU_MEMPOOL_ATTR_REUSE_ALLOW_OPPORTUNISTIC
+        | CU_MEMPOOL_ATTR_REUSE_ALLOW_INTERNAL_DEPENDENCIES => Ok(()),
+        _ => Err(result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE)),
+    }
+}
+
+/// The size in bytes of `len` elements of `T`.
+///
+/// Returns `CUDA_ERROR_INVALID_VALUE` instead of wrapping if this overflows a `usize`,
+/// so a huge `len` can never turn into a tiny allocation.
+pub(crate) fn size_of_len<T>(len: usize) -> Result<usize, result::DriverError> {
+    len.checked_mul(std::mem::size_of::<T>())
+        .ok_or(result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE))
+}
+
 unsafe impl DeviceRepr for bool {}
 unsafe impl DeviceRepr for i8 {}
 unsafe impl DeviceRepr for i16 {}
@@ -77,10 +97,71 @@ impl<T> CudaSlice<T> {
         if let Some(host_buf) = std::mem::take(&mut self.host_buf) {
             drop(host_buf);
         }
+        #[cfg(feature = "track-allocations")]
+        self.device
+            .track_free(self.capacity * std::mem::size_of::<T>());
         let ptr = self.cu_device_ptr;
         std::mem::forget(self);
         ptr
     }
+
+    /// Sets **every byte** of the elements in `range` to `value`, asynchronously on `stream`.
+    ///
+    /// Like [CudaDevice::memset_bytes()] this works on bytes, not elements: the byte range
+    /// written is `range.start * size_of::<T>()..range.end * size_of::<T>()`, and for `T`
+    /// wider than a byte each element is `value` repeated. An empty range does nothing.
+    ///
+    /// Returns `CUDA_ERROR_INVALID_VALUE` if `range` is out of bounds.
+    ///
+    /// # Safety
+    /// 1. `value` repeated over `size_of::<T>()` bytes must be a valid `T`.
+    /// 2. `stream` is not synchronized with the device's default stream, so any work
+    ///    on other streams that uses `range` must be ordered with `stream` by the caller,
+    ///    e.g. with [CudaStream::wait_for_default()].
+    pub unsafe fn memset_range_async(
+        &mut self,
+        range: impl RangeBounds<usize>,
+        value: u8,
+        stream: &CudaStream,
+    ) -> Result<(), result::DriverError> {
+        self.device.bind_to_thread()?;
+        let mut view = self
+            .try_slice_mut(range)
+            .ok_or(result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE))?;
+        if view.len() == 0 {
+            return Ok(());
+        }
+        result::memset_d8_async(
+            *view.device_ptr_mut(),
+            value,
+            view.num_bytes(),
+            stream.stream,
+        )
+    }
+}
+
+impl<T: DeviceRepr> CudaSlice<T> {
+    /// Copies this slice to a temporary host [Vec] and calls `f` on each element in order.
+    ///
+    /// This is a convenience for small buffers: the whole slice round trips through host
+    /// memory and `f` runs on the host, one element at a time. It is not parallel on the GPU.
+    pub fn for_each_host<F: FnMut(&T)>(&self, f: F) -> Result<(), result::DriverError> {
+        self.device.dtoh_sync_copy(self)?.iter().for_each(f);
+        Ok(())
+    }
+
+    /// Copies this slice to a temporary host [Vec], maps each element with `f` on the host,
+    /// and copies the results into a new [CudaSlice] on the same device.
+    ///
+    /// Like [CudaSlice::for_each_host()] this is a host round trip meant for small buffers,
+    /// not a GPU kernel. Use [super::LaunchAsync] for anything performance sensitive.
+    pub fn map_to_device<U: DeviceRepr, F: FnMut(&T) -> U>(
+        &self,
+        f: F,
+    ) -> Result<CudaSlice<U>, result::DriverError> {
+        let mapped: Vec<U> = self.device.dtoh_sync_copy(self)?.iter().map(f).collect();
+        self.device.htod_sync_copy(&mapped)
+    }
 }
 
 impl CudaDevice {
@@ -97,13 +178,56 @@ impl CudaDevice {
         cu_device_ptr: sys::CUdeviceptr,
         len: usize,
     ) -> CudaSlice<T> {
+        #[cfg(feature = "track-allocations")]
+        self.track_alloc(len * std::mem::size_of::<T>());
         CudaSlice {
             cu_device_ptr,
             len,
+            capacity: len,
             device: self.clone(),
             host_buf: None,
         }
     }
+
+    /// Like [CudaDevice::upgrade_device_ptr()], but first asks the driver whether
+    /// `cu_device_ptr` is the start of a large enough allocation of device memory on this
+    /// device, e.g. to catch mistakes with pointers received over FFI. Returns:
+    /// - `CUDA_ERROR_INVALID_VALUE` if it is null, not device memory (including host memory,
+    ///   pinned or not), not the start of its allocation, or if the allocation is smaller
+    ///   than `len * size_of::<T>()` bytes.
+    /// - `CUDA_ERROR_INVALID_DEVICE` if it is device memory on another device.
+    ///
+    /// # Safety
+    /// The driver can't check the rest of the requirements of
+    /// [CudaDevice::upgrade_device_ptr()], so they still apply:
+    /// - Nothing else may free the allocation, since the returned [CudaSlice] does
+    /// - The memory may not be valid for type `T`
+    pub unsafe fn upgrade_device_ptr_checked<T>(
+        self: &Arc<Self>,
+        cu_device_ptr: sys::CUdeviceptr,
+        len: usize,
+    ) -> Result<CudaSlice<T>, result::DriverError> {
+        if cu_device_ptr == 0 {
+            return Err(result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE));
+        }
+        self.bind_to_thread()?;
+        if result::pointer_get_memory_type(cu_device_ptr)?
+            != sys::CUmemorytype::CU_MEMORYTYPE_DEVICE
+        {
+            return Err(result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE));
+        }
+        if result::pointer_get_device_ordinal(cu_device_ptr)? != self.ordinal as i32 {
+            return Err(result::DriverError(
+                sys::CUresult::CUDA_ERROR_INVALID_DEVICE,
+            ));
+        }
+        let num_bytes = size_of_len::<T>(len)?;
+        let (start, size) = result::pointer_get_range(cu_device_ptr)?;
+        if start != cu_device_ptr || size < num_bytes {
+            return Err(result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE));
+        }
+        Ok(self.upgrade_device_ptr(cu_device_ptr, len))
+    }
 }
 
 impl CudaDevice {
@@ -120,6 +244,7 @@ impl CudaDevice {
         Ok(CudaSlice {
             cu_device_ptr,
             len: 0,
+            capacity: 0,
             device: self.clone(),
             host_buf: None,
         })
@@ -127,26 +252,127 @@ impl CudaDevice {
 
     /// Allocates device memory and increments the reference counter of [CudaDevice].
     ///
+    /// Returns `CUDA_ERROR_INVALID_VALUE` if the size in bytes overflows a `usize`.
+    ///
     /// # Safety
     /// This is unsafe because the device memory is unset after this call.
     pub unsafe fn alloc<T: DeviceRepr>(
         self: &Arc<Self>,
         len: usize,
     ) -> Result<CudaSlice<T>, result::DriverError> {
+        let num_bytes = size_of_len::<T>(len)?;
         self.bind_to_thread()?;
         let cu_device_ptr = if self.is_async {
-            result::malloc_async(self.stream, len * std::mem::size_of::<T>())?
+            result::malloc_async(self.stream, num_bytes)?
         } else {
-            result::malloc_sync(len * std::mem::size_of::<T>())?
+            result::malloc_sync(num_bytes)?
         };
+        #[cfg(feature = "track-allocations")]
+        self.track_alloc(num_bytes);
         Ok(CudaSlice {
             cu_device_ptr,
             len,
+            capacity: len,
             device: self.clone(),
             host_buf: None,
         })
     }
 
+    /// Allocates a [CudaSlice] with the same length as `src`, **without** copying it.
+    /// Useful as the destination of a later copy, e.g. [CudaDevice::htod_copy_into()].
+    /// See [CudaDevice::htod_sync_copy()] to also copy `src`.
+    ///
+    /// # Safety
+    /// This is unsafe because the device memory is unset after this call.
+    pub unsafe fn alloc_like<T: DeviceRepr>(
+        self: &Arc<Self>,
+        src: &[T],
+    ) -> Result<CudaSlice<T>, result::DriverError> {
+        self.alloc(src.len())
+    }
+
+    /// Frees memory cached by the device's current memory pool, which stream ordered
+    /// allocations (used when the device supports memory pools) come from, back to the OS,
+    /// until at most `min_bytes_to_keep` bytes remain reserved. Memory backing live
+    /// allocations is never freed.
+    ///
+    /// This doesn't apply to memory allocated inside graphs, see [CudaDevice::graph_mem_trim()].
+    pub fn trim_mem_pool(
+        self: &Arc<Self>,
+        min_bytes_to_keep: usize,
+    ) -> Result<(), result::DriverError> {
+        self.synchronize()?;
+        unsafe {
+            let pool = result::device::get_mem_pool(self.cu_device)?;
+            result::mem_pool::trim_to(pool, min_bytes_to_keep)
+        }
+    }
+
+    /// Runs `f`, and if it fails with `CUDA_ERROR_OUT_OF_MEMORY`, synchronizes the device's work
+    /// stream and trims the device's memory pool with [CudaDevice::trim_mem_pool()] (if it
+    /// uses one) before running `f` one more time. This recovers from running out of memory because freed
+    /// memory is still cached by the pool.
+    ///
+    /// `f` is retried at most once, and its second result is returned as is.
+    ///
+    /// ```ignore
+    /// let buf = dev.with_oom_retry(|| dev.alloc_zeros::<f32>(n))?;
+    /// ```
+    pub fn with_oom_retry<T, F: FnMut() -> Result<T, result::DriverError>>(
+        self: &Arc<Self>,
+        mut f: F,
+    ) -> Result<T, result::DriverError> {
+        match f() {
+            Err(result::DriverError(sys::CUresult::CUDA_ERROR_OUT_OF_MEMORY)) => {
+                if self.is_async {
+                    self.trim_mem_pool(0)?;
+                } else {
+                    self.synchronize()?;
+                }
+                f()
+            }
+            res => res,
+        }
+    }
+
+    /// Whether the reuse policy `attr` of the device's current memory pool is enabled. `attr` is one of:
+    /// - `CU_MEMPOOL_ATTR_REUSE_FOLLOW_EVENT_DEPENDENCIES`: reuse memory freed on another stream
+    ///   if an event dependency already orders the free before the new allocation
+    /// - `CU_MEMPOOL_ATTR_REUSE_ALLOW_OPPORTUNISTIC`: reuse memory freed on another stream once
+    ///   the free has already completed, without any dependency between the streams
+    /// - `CU_MEMPOOL_ATTR_REUSE_ALLOW_INTERNAL_DEPENDENCIES`: reuse memory freed on another stream
+    ///   by inserting a dependency on the free into the allocating stream
+    ///
+    /// Returns `CUDA_ERROR_INVALID_VALUE` for any other attribute.
+    pub fn mem_pool_reuse_policy(
+        self: &Arc<Self>,
+        attr: sys::CUmemPool_attribute,
+    ) -> Result<bool, result::DriverError> {
+        check_reuse_attribute(attr)?;
+        unsafe {
+            let pool = result::device::get_mem_pool(self.cu_device)?;
+            result::mem_pool::get_reuse_attribute(pool, attr)
+        }
+    }
+
+    /// Enables or disables the reuse policy `attr` of the device's current memory pool,
+    /// see [CudaDevice::mem_pool_reuse_policy()]. All of them are enabled by default.
+    ///
+    /// More reuse means less memory use, but `CU_MEMPOOL_ATTR_REUSE_ALLOW_INTERNAL_DEPENDENCIES`
+    /// can make streams wait on each other in ways that aren't visible in the code, and
+    /// `CU_MEMPOOL_ATTR_REUSE_ALLOW_OPPORTUNISTIC` makes reuse depend on timing.
+    pub fn set_mem_pool_reuse_policy(
+        self: &Arc<Self>,
+        attr: sys::CUmemPool_attribute,
+        enabled: bool,
+    ) -> Result<(), result::DriverError> {
+        check_reuse_attribute(attr)?;
+        unsafe {
+            let pool = result::device::get_mem_pool(self.cu_device)?;
+            result::mem_pool::set_reuse_attribute(pool, attr, enabled)
+        }
+    }
+
     /// Allocates device memory with no associated host memory, and memsets
     /// the device memory to all 0s.
     ///
@@ -181,6 +407,28 @@ impl CudaDevice {
         }
     }
 
+    /// Sets **every byte** of `dst` to `value` asynchronously.
+    ///
+    /// This works on bytes, not elements: for `T` wider than a byte, each element
+    /// is `value` repeated, e.g. `memset_bytes(&mut a, 1)` on a `u32` slice makes every
+    /// element `0x01010101`, not `1`. To set every element to a value,
+    /// copy it from the host (e.g. [CudaDevice::htod_sync_copy_into()]) or use a kernel.
+    ///
+    /// # Safety
+    /// `value` repeated over `size_of::<T>()` bytes must be a valid `T`.
+    pub unsafe fn memset_bytes<T: DeviceRepr, Dst: DevicePtrMut<T>>(
+        self: &Arc<Self>,
+        dst: &mut Dst,
+        value: u8,
+    ) -> Result<(), result::DriverError> {
+        self.bind_to_thread()?;
+        if self.is_async {
+            result::memset_d8_async(*dst.device_ptr_mut(), value, dst.num_bytes(), self.stream)
+        } else {
+            result::memset_d8_sync(*dst.device_ptr_mut(), value, dst.num_bytes())
+        }
+    }
+
     /// Device to device copy (safe version of [result::memcpy_dtod_async]).
     ///
     /// # Panics
@@ -281,6 +529,18 @@ impl CudaDevice {
         Ok(dst)
     }
 
+    /// Takes ownership of `src`, allocates new device memory and synchronously copies `src`
+    /// into it. `src` is freed before this returns.
+    ///
+    /// Unlike [CudaDevice::htod_copy()], the returned [CudaSlice] doesn't hold on to `src`
+    /// until it is dropped, at the cost of waiting for the copy.
+    pub fn htod_sync_copy_vec<T: DeviceRepr>(
+        self: &Arc<Self>,
+        src: Vec<T>,
+    ) -> Result<CudaSlice<T>, result::DriverError> {
+        self.htod_sync_copy(&src)
+    }
+
     /// Synchronously copies data from `src` into the new allocation.
     ///
     /// If you want an asynchronous copy, see [CudaDevice::htod_copy()].
@@ -307,6 +567,63 @@ impl CudaDevice {
         self.synchronize()
     }
 
+    /// Queues an asynchronous copy of `src` into `dst` on `stream`, then records a new
+    /// timing-disabled [CudaEvent] on `stream` right after the copy and returns it.
+    /// Use [CudaEvent::is_complete()] or [CudaEvent::synchronize()] to find out when the copy lands.
+    ///
+    /// # Panics
+    ///
+    /// If the lengths of slices are not equal, this method panics.
+    ///
+    /// # Safety
+    /// 1. `src` must not be mutated or freed until the returned event has completed.
+    /// 2. For the copy to be truly asynchronous `src` should be page-locked,
+    ///    e.g. from [super::PinnedHostSlice::as_slice()]. With the `debug-checks`
+    ///    feature a warning is emitted if it isn't.
+    pub unsafe fn htod_copy_into_with_event<T: DeviceRepr, Dst: DevicePtrMut<T>>(
+        self: &Arc<Self>,
+        src: &[T],
+        dst: &mut Dst,
+        stream: &CudaStream,
+    ) -> Result<CudaEvent, result::DriverError> {
+        assert_eq!(src.len(), dst.len());
+        let event = self.create_event(sys::CUevent_flags::CU_EVENT_DISABLE_TIMING)?;
+        #[cfg(feature = "debug-checks")]
+        super::debug_checks::check_page_locked(src, "CudaDevice::htod_copy_into_with_event");
+        result::memcpy_htod_async(*dst.device_ptr_mut(), src, stream.stream)?;
+        event.record_on_stream(stream)?;
+        Ok(event)
+    }
+
+    /// Allocates a copy of `src` on every device in `devices`.
+    ///
+    /// All of the copies are queued on each device's work stream before
+    /// any of them are waited on, so transfers to different devices may overlap.
+    /// This returns after every device has finished its copy.
+    ///
+    /// # Safety
+    /// 1. Since this function doesn't own `src` it waits for all copies to finish before returning.
+    /// 2. Each allocation is made with its own device bound to the calling thread.
+    pub fn htod_broadcast<T: DeviceRepr>(
+        devices: &[Arc<Self>],
+        src: &[T],
+    ) -> Result<Vec<CudaSlice<T>>, result::DriverError> {
+        let mut dsts = Vec::with_capacity(devices.len());
+        for dev in devices.iter() {
+            let dst = unsafe { dev.alloc::<T>(src.len()) }?;
+            if dev.is_async {
+                unsafe { result::memcpy_htod_async(dst.cu_device_ptr, src, dev.stream) }?;
+            } else {
+                unsafe { result::memcpy_htod_sync(dst.cu_device_ptr, src) }?;
+            }
+            dsts.push(dst);
+        }
+        for dev in devices.iter() {
+            dev.synchronize()?;
+        }
+        Ok(dsts)
+    }
+
     /// Synchronously copies device memory into host memory.
     /// Unlike [`CudaDevice::dtoh_sync_copy_into`] this returns a [`Vec<T>`].
     ///
@@ -351,6 +668,82 @@ impl CudaDevice {
         self.synchronize()
     }
 
+    /// Synchronously copies the row-major matrix `src`, which has `src.len()` rows
+    /// of `C` columns, into a new allocation of `src.len() * C` elements.
+    ///
+    /// # Safety
+    /// 1. Since this function doesn't own `src` it is executed synchronously.
+    /// 2. Self is [`Arc<Self>`], and this method increments the rc for self
+    pub fn htod_sync_copy_2d<T: DeviceRepr, const C: usize>(
+        self: &Arc<Self>,
+        src: &[[T; C]],
+    ) -> Result<CudaSlice<T>, result::DriverError> {
+        let flat = unsafe { std::slice::from_raw_parts(src.as_ptr() as *const T, src.len() * C) };
+        self.htod_sync_copy(flat)
+    }
+
+    /// Synchronously copies device memory into a row-major matrix of `C` columns.
+    ///
+    /// # Panics
+    ///
+    /// If the length of `src` is not a multiple of `C`, this method panics.
+    ///
+    /// # Safety
+    /// 1. Since this function doesn't own `dst` (after returning) it is executed synchronously.
+    /// 2. Self is [`Arc<Self>`], and this method increments the rc for self
+    #[allow(clippy::uninit_vec)]
+    pub fn dtoh_sync_copy_2d<T: DeviceRepr, const C: usize, Src: DevicePtr<T>>(
+        self: &Arc<Self>,
+        src: &Src,
+    ) -> Result<Vec<[T; C]>, result::DriverError> {
+        assert!(C > 0 && src.len() % C == 0);
+        let rows = src.len() / C;
+        let mut dst: Vec<[T; C]> = Vec::with_capacity(rows);
+        unsafe { dst.set_len(rows) };
+        let flat = unsafe { std::slice::from_raw_parts_mut(dst.as_mut_ptr() as *mut T, rows * C) };
+        self.dtoh_sync_copy_into(src, flat)?;
+        Ok(dst)
+    }
+
+    /// Synchronously copies the matrix `src` into a new allocation of `src.len()` elements,
+    /// in row-major order.
+    ///
+    /// Only contiguous arrays in standard (row-major) layout are supported. Others, e.g.
+    /// transposed or strided views, return `CUDA_ERROR_INVALID_VALUE`, and can be copied
+    /// into one first with `src.as_standard_layout()`.
+    #[cfg(feature = "ndarray")]
+    pub fn htod_sync_copy_array2<T: DeviceRepr, S: ndarray::Data<Elem = T>>(
+        self: &Arc<Self>,
+        src: &ndarray::ArrayBase<S, ndarray::Ix2>,
+    ) -> Result<CudaSlice<T>, result::DriverError> {
+        let flat = src
+            .as_slice()
+            .ok_or(result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE))?;
+        self.htod_sync_copy(flat)
+    }
+
+    /// Synchronously copies device memory into a new `rows x cols` matrix in standard
+    /// (row-major) layout.
+    ///
+    /// Returns `CUDA_ERROR_INVALID_VALUE` if `rows * cols` is not the length of `src`.
+    #[cfg(feature = "ndarray")]
+    #[allow(clippy::uninit_vec)]
+    pub fn dtoh_sync_copy_array2<T: DeviceRepr, Src: DevicePtr<T>>(
+        self: &Arc<Self>,
+        src: &Src,
+        rows: usize,
+        cols: usize,
+    ) -> Result<ndarray::Array2<T>, result::DriverError> {
+        let invalid = result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE);
+        if rows.checked_mul(cols) != Some(src.len()) {
+            return Err(invalid);
+        }
+        let mut flat = Vec::with_capacity(src.len());
+        unsafe { flat.set_len(src.len()) };
+        self.dtoh_sync_copy_into(src, &mut flat)?;
+        ndarray::Array2::from_shape_vec((rows, cols), flat).map_err(|_| invalid)
+    }
+
     /// Synchronously de-allocates `src` and converts it into it's host value.
     /// You can just [drop] the slice if you don't need the host data.
     ///
@@ -371,10 +764,38 @@ impl CudaDevice {
     }
 
     /// Synchronizes the stream.
+    ///
+    /// This only waits on the device's default work stream (the null stream), and the blocking
+    /// streams it implicitly synchronizes with. It does **not** wait for streams from
+    /// [CudaDevice::fork_default_stream()], which are non-blocking; use [CudaStream::synchronize()]
+    /// or [CudaDevice::wait_for()] for those.
     pub fn synchronize(self: &Arc<Self>) -> Result<(), result::DriverError> {
         self.bind_to_thread()?;
         unsafe { result::stream::synchronize(self.stream) }
     }
+
+    /// Like [CudaDevice::synchronize()], but returns `CUDA_ERROR_TIMEOUT` if the work
+    /// hasn't completed after `timeout`, instead of potentially blocking forever.
+    ///
+    /// This busy waits, polling the stream and yielding the thread in between,
+    /// so it keeps a CPU core busy until it returns.
+    pub fn synchronize_with_timeout(
+        self: &Arc<Self>,
+        timeout: std::time::Duration,
+    ) -> Result<(), result::DriverError> {
+        self.bind_to_thread()?;
+        let start = std::time::Instant::now();
+        loop {
+            match unsafe { result::stream::query(self.stream) } {
+                Err(result::DriverError(sys::CUresult::CUDA_ERROR_NOT_READY)) => (),
+                res => return res,
+            }
+            if start.elapsed() >= timeout {
+                return Err(result::DriverError(sys::CUresult::CUDA_ERROR_TIMEOUT));
+            }
+            std::thread::yield_now();
+        }
+    }
 }
 
 /// Marker trait to indicate that the type is valid
@@ -427,6 +848,256 @@ impl_tuples!(A, B, C, D, E, F, G, H, I, J, K, L);
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_2d_round_trip() {
+        let dev = CudaDevice::new(0).unwrap();
+        let src: [[f32; 4]; 3] = [
+            [0.0, 1.0, 2.0, 3.0],
+            [4.0, 5.0, 6.0, 7.0],
+            [8.0, 9.0, 10.0, 11.0],
+        ];
+        let a = dev.htod_sync_copy_2d(&src).unwrap();
+        assert_eq!(a.len(), 12);
+        assert_eq!(
+            dev.dtoh_sync_copy(&a).unwrap(),
+            (0..12).map(|i| i as f32).collect::<Vec<_>>()
+        );
+        assert_eq!(dev.dtoh_sync_copy_2d::<f32, 4, _>(&a).unwrap(), src);
+    }
+
+    #[test]
+    #[cfg(feature = "ndarray")]
+    fn test_array2_round_trip() {
+        let dev = CudaDevice::new(0).unwrap();
+        let src = ndarray::Array2::from_shape_fn((3, 4), |(r, c)| (4 * r + c) as f32);
+        let a = dev.htod_sync_copy_array2(&src).unwrap();
+        assert_eq!(a.len(), 12);
+        assert_eq!(
+            dev.dtoh_sync_copy(&a).unwrap(),
+            (0..12).map(|i| i as f32).collect::<Vec<_>>()
+        );
+        assert_eq!(dev.dtoh_sync_copy_array2(&a, 3, 4).unwrap(), src);
+
+        let invalid = result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE);
+        assert_eq!(dev.dtoh_sync_copy_array2(&a, 4, 4).unwrap_err(), invalid);
+        assert_eq!(dev.htod_sync_copy_array2(&src.t()).unwrap_err(), invalid);
+
+        let transposed = dev
+            .htod_sync_copy_array2(&src.t().as_standard_layout())
+            .unwrap();
+        assert_eq!(
+            dev.dtoh_sync_copy_array2(&transposed, 4, 3).unwrap(),
+            src.t()
+        );
+    }
+
+    #[test]
+    fn test_htod_copy_into_with_event() {
+        let dev = CudaDevice::new(0).unwrap();
+        let src: Vec<f32> = (0..1024).map(|i| i as f32).collect();
+        let mut dst = dev.alloc_zeros::<f32>(1024).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+        let event = unsafe { dev.htod_copy_into_with_event(&src, &mut dst, &stream) }.unwrap();
+        while !event.is_complete().unwrap() {
+            std::thread::yield_now();
+        }
+        assert_eq!(dev.dtoh_sync_copy(&dst).unwrap(), src);
+    }
+
+    #[test]
+    fn test_alloc_like() {
+        let dev = CudaDevice::new(0).unwrap();
+        let src: Vec<f32> = (0..100).map(|i| i as f32).collect();
+        let mut a = unsafe { dev.alloc_like(&src) }.unwrap();
+        assert_eq!(a.len(), src.len());
+        dev.htod_sync_copy_into(&src, &mut a).unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), src);
+
+        let empty = unsafe { dev.alloc_like::<f32>(&[]) }.unwrap();
+        assert_eq!(empty.len(), 0);
+    }
+
+    #[test]
+    fn test_memset_bytes() {
+        let dev = CudaDevice::new(0).unwrap();
+        let mut a = dev.htod_sync_copy(&[1234u16; 10]).unwrap();
+        unsafe { dev.memset_bytes(&mut a, 0) }.unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [0; 10]);
+
+        // every byte is set, so each u16 is 0x0101 rather than 1
+        unsafe { dev.memset_bytes(&mut a, 1) }.unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [0x0101; 10]);
+    }
+
+    #[test]
+    fn test_memset_range_async() {
+        let dev = CudaDevice::new(0).unwrap();
+        let mut a = dev.htod_sync_copy(&[7u32; 16]).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+        unsafe { a.memset_range_async(8.., 0, &stream) }.unwrap();
+        unsafe { a.memset_range_async(4..4, 0, &stream) }.unwrap();
+        stream.synchronize().unwrap();
+        let host = dev.dtoh_sync_copy(&a).unwrap();
+        assert_eq!(host[..8], [7; 8]);
+        assert_eq!(host[8..], [0; 8]);
+
+        assert_eq!(
+            unsafe { a.memset_range_async(8..17, 0, &stream) },
+            Err(result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE))
+        );
+    }
+
+    #[test]
+    fn test_for_each_host() {
+        let dev = CudaDevice::new(0).unwrap();
+        let a = dev.htod_sync_copy(&[1.0f32, 2.0, 3.0, 4.0]).unwrap();
+        let mut seen = Vec::new();
+        a.for_each_host(|x| seen.push(*x)).unwrap();
+        assert_eq!(seen, [1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_map_to_device() {
+        let dev = CudaDevice::new(0).unwrap();
+        let a = dev.htod_sync_copy(&[1.0f32, 2.0, 3.0, 4.0]).unwrap();
+        let b: CudaSlice<f64> = a.map_to_device(|x| (*x as f64) * 0.5).unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&b).unwrap(), [0.5, 1.0, 1.5, 2.0]);
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [1.0, 2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_trim_mem_pool() {
+        let dev = CudaDevice::new(0).unwrap();
+        if !dev.is_async {
+            return;
+        }
+        drop(dev.alloc_zeros::<u8>(1 << 20).unwrap());
+        dev.trim_mem_pool(0).unwrap();
+    }
+
+    #[test]
+    fn test_htod_sync_copy_vec() {
+        let dev = CudaDevice::new(0).unwrap();
+        let a = dev.htod_sync_copy_vec(std::vec![1.0f32; 100]).unwrap();
+        assert!(a.host_buf.is_none());
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [1.0; 100]);
+    }
+
+    #[test]
+    fn test_synchronize_with_timeout() {
+        let dev = CudaDevice::new(0).unwrap();
+        let _a = dev.alloc_zeros::<f32>(1 << 20).unwrap();
+        dev.synchronize_with_timeout(std::time::Duration::from_secs(10))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_mem_pool_reuse_policy() {
+        use sys::CUmemPool_attribute::*;
+        let dev = CudaDevice::new(0).unwrap();
+        if !dev.is_async {
+            return;
+        }
+        for attr in [
+            CU_MEMPOOL_ATTR_REUSE_FOLLOW_EVENT_DEPENDENCIES,
+            CU_MEMPOOL_ATTR_REUSE_ALLOW_OPPORTUNISTIC,
+            CU_MEMPOOL_ATTR_REUSE_ALLOW_INTERNAL_DEPENDENCIES,
+        ] {
+            let original = dev.mem_pool_reuse_policy(attr).unwrap();
+            dev.set_mem_pool_reuse_policy(attr, !original).unwrap();
+            assert_eq!(dev.mem_pool_reuse_policy(attr).unwrap(), !original);
+            dev.set_mem_pool_reuse_policy(attr, original).unwrap();
+            assert_eq!(dev.mem_pool_reuse_policy(attr).unwrap(), original);
+        }
+        assert!(dev
+            .mem_pool_reuse_policy(CU_MEMPOOL_ATTR_RELEASE_THRESHOLD)
+            .is_err());
+    }
+
+    #[test]
+    fn test_mem_pool_reuses_freed_memory() {
+        let dev = CudaDevice::new(0).unwrap();
+        if !dev.is_async {
+            return;
+        }
+        let attr = sys::CUmemPool_attribute::CU_MEMPOOL_ATTR_REUSE_ALLOW_OPPORTUNISTIC;
+        dev.set_mem_pool_reuse_policy(attr, true).unwrap();
+        let reserved = || unsafe {
+            let pool = result::device::get_mem_pool(dev.cu_device).unwrap();
+            let attr = sys::CUmemPool_attribute::CU_MEMPOOL_ATTR_RESERVED_MEM_CURRENT;
+            result::mem_pool::get_mem_attribute(pool, attr).unwrap()
+        };
+        let a = unsafe { dev.alloc::<u8>(1 << 20) }.unwrap();
+        let reserved_with_a = reserved();
+        drop(a);
+        dev.synchronize().unwrap();
+        let _b = unsafe { dev.alloc::<u8>(1 << 20) }.unwrap();
+        assert!(reserved() <= reserved_with_a);
+    }
+
+    #[test]
+    fn test_with_oom_retry() {
+        let dev = CudaDevice::new(0).unwrap();
+        let oom = result::DriverError(sys::CUresult::CUDA_ERROR_OUT_OF_MEMORY);
+
+        let mut num_calls = 0;
+        let a = dev
+            .with_oom_retry(|| {
+                num_calls += 1;
+                if num_calls == 1 {
+                    Err(oom)
+                } else {
+                    dev.alloc_zeros::<f32>(100)
+                }
+            })
+            .unwrap();
+        assert_eq!(num_calls, 2);
+        assert_eq!(a.len(), 100);
+
+        let mut num_calls = 0;
+        let res: Result<(), _> = dev.with_oom_retry(|| {
+            num_calls += 1;
+            Err(oom)
+        });
+        assert_eq!(res, Err(oom));
+        assert_eq!(num_calls, 2);
+
+        let mut num_calls = 0;
+        let res: Result<(), _> = dev.with_oom_retry(|| {
+            num_calls += 1;
+            Err(result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE))
+        });
+        assert!(res.is_err());
+        assert_eq!(num_calls, 1);
+    }
+
+    #[test]
+    fn test_alloc_size_overflow() {
+        let dev = CudaDevice::new(0).unwrap();
+        let len = usize::MAX / std::mem::size_of::<f32>() + 1;
+        assert_eq!(
+            unsafe { dev.alloc::<f32>(len) }.unwrap_err(),
+            result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE)
+        );
+        assert_eq!(
+            dev.alloc_zeros::<f32>(len).unwrap_err(),
+            result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE)
+        );
+        assert_eq!(
+            unsafe { dev.alloc_pinned::<f32>(len) }.unwrap_err(),
+            result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE)
+        );
+        assert!(unsafe { dev.alloc_batch::<f32>(&[1, len]) }.is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_dtoh_2d_bad_shape() {
+        let dev = CudaDevice::new(0).unwrap();
+        let a = dev.alloc_zeros::<f32>(10).unwrap();
+        let _ = dev.dtoh_sync_copy_2d::<f32, 4, _>(&a);
+    }
+
     #[test]
     fn test_post_build_arc_count() {
         let device = CudaDevice::new(0).unwrap();
@@ -554,6 +1225,38 @@ mod tests {
         assert_eq!(dev.dtoh_sync_copy(&c).unwrap(), &[1.0, 2.0, 3.0, 4.0, 5.0]);
     }
 
+    #[test]
+    fn test_upgrade_device_ptr_checked() {
+        let dev = CudaDevice::new(0).unwrap();
+
+        let invalid = result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE);
+        let ptr = dev.htod_copy(std::vec![1.0f32, 2.0, 3.0]).unwrap().leak();
+        for (ptr, len) in [(ptr + 4, 2), (ptr, 1 << 30), (ptr, usize::MAX)] {
+            assert_eq!(
+                unsafe { dev.upgrade_device_ptr_checked::<f32>(ptr, len) }.unwrap_err(),
+                invalid
+            );
+        }
+        let a = unsafe { dev.upgrade_device_ptr_checked::<f32>(ptr, 3) }.unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), &[1.0, 2.0, 3.0]);
+
+        let pageable = std::vec![0.0f32; 3];
+        let ptr = pageable.as_ptr() as sys::CUdeviceptr;
+        assert!(unsafe { dev.upgrade_device_ptr_checked::<f32>(ptr, 3) }.is_err());
+
+        let pinned = dev.alloc_pinned_zeros::<f32>(3).unwrap();
+        let ptr = pinned.as_slice().unwrap().as_ptr() as sys::CUdeviceptr;
+        assert_eq!(
+            unsafe { dev.upgrade_device_ptr_checked::<f32>(ptr, 3) }.unwrap_err(),
+            invalid
+        );
+
+        assert_eq!(
+            unsafe { dev.upgrade_device_ptr_checked::<f32>(0, 3) }.unwrap_err(),
+            invalid
+        );
+    }
+
     /// See https://github.com/coreylowman/cudarc/issues/160
     #[test]
     fn test_slice_is_freed_with_correct_context() {
@@ -579,4 +1282,22 @@ mod tests {
         let slice = dev0.htod_copy(vec![1.0; 10]).unwrap();
         let _out = dev0.dtoh_sync_copy(&slice).unwrap();
     }
+
+    #[test]
+    fn test_htod_broadcast() {
+        let n = CudaDevice::count().unwrap();
+        if n < 2 {
+            return;
+        }
+        let devs: Vec<_> = (0..n as usize)
+            .map(|i| CudaDevice::new(i).unwrap())
+            .collect();
+        let src = [1.0f32, 2.0, 3.0, 4.0];
+        let dsts = CudaDevice::htod_broadcast(&devs, &src).unwrap();
+        assert_eq!(dsts.len(), devs.len());
+        for (dev, dst) in devs.iter().zip(dsts.iter()) {
+            assert_eq!(dst.device().ordinal(), dev.ordinal());
+            assert_eq!(dev.dtoh_sync_copy(dst).unwrap(), src);
+        }
+    }
 }
@@ -0,0 +1,325 @@
+use crate::driver::{sys, DriverError};
+
+use super::alloc::{size_of_len, DeviceRepr, ValidAsZeroBits};
+use super::core::{CudaDevice, CudaSlice};
+use super::device_ptr::{DevicePtr, DevicePtrMut, DeviceSlice};
+
+use std::{cell::Cell, marker::PhantomData, sync::Arc, vec::Vec};
+
+/// The alignment in bytes of every allocation made by [DeviceArena::alloc()].
+pub const ARENA_ALIGNMENT: usize = 256;
+
+/// A bump allocator that hands out [ArenaSlice]s from a single [CudaSlice]
+/// allocated up front, which avoids a driver allocation per call.
+///
+/// Create with [CudaDevice::alloc_arena()]. All the space is reclaimed at once
+/// with [DeviceArena::reset()], which requires that no [ArenaSlice] is still alive.
+///
+/// ```ignore
+/// let mut arena = dev.alloc_arena(1 << 20)?;
+/// for _ in 0..num_iters {
+///     let a = arena.alloc_zeros::<f32>(1024)?;
+///     let b = arena.alloc_zeros::<u32>(512)?;
+///     // ... use a & b ...
+///     drop((a, b));
+///     arena.reset();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct DeviceArena {
+    pub(crate) buf: CudaSlice<u8>,
+    pub(crate) offset: Cell<usize>,
+}
+
+/// A typed region of a [DeviceArena], created with [DeviceArena::alloc()] or
+/// [DeviceArena::alloc_zeros()].
+#[derive(Debug)]
+pub struct ArenaSlice<'a, T> {
+    pub(crate) cu_device_ptr: sys::CUdeviceptr,
+    pub(crate) len: usize,
+    pub(crate) marker: PhantomData<(&'a DeviceArena, T)>,
+}
+
+impl CudaDevice {
+    /// Allocates a [DeviceArena] with `num_bytes` of device memory.
+    pub fn alloc_arena(self: &Arc<Self>, num_bytes: usize) -> Result<DeviceArena, DriverError> {
+        Ok(DeviceArena {
+            buf: unsafe { self.alloc(num_bytes) }?,
+            offset: Cell::new(0),
+        })
+    }
+}
+
+impl DeviceArena {
+    /// Get a clone of the underlying [CudaDevice].
+    pub fn device(&self) -> Arc<CudaDevice> {
+        self.buf.device()
+    }
+
+    /// The total number of bytes in the arena.
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity
+    }
+
+    /// The number of bytes currently in use, including alignment padding.
+    pub fn used(&self) -> usize {
+        self.offset.get()
+    }
+
+    /// Allocates `len` elements of `T`, starting at the next [ARENA_ALIGNMENT] byte boundary.
+    ///
+    /// Returns `CUDA_ERROR_OUT_OF_MEMORY` if the arena doesn't have enough space left.
+    ///
+    /// # Safety
+    /// This is unsafe because the memory is unset after this call. After
+    /// [DeviceArena::reset()] it may hold whatever a previous [ArenaSlice] wrote there,
+    /// which isn't necessarily a valid `T`.
+    pub unsafe fn alloc<T: DeviceRepr>(
+        &self,
+        len: usize,
+    ) -> Result<ArenaSlice<'_, T>, DriverError> {
+        assert!(std::mem::align_of::<T>() <= ARENA_ALIGNMENT);
+        let start = self.offset.get().next_multiple_of(ARENA_ALIGNMENT);
+        let end = len
+            .checked_mul(std::mem::size_of::<T>())
+            .and_then(|num_bytes| start.checked_add(num_bytes))
+            .filter(|&end| end <= self.capacity())
+            .ok_or(DriverError(sys::CUresult::CUDA_ERROR_OUT_OF_MEMORY))?;
+        self.offset.set(end);
+        Ok(ArenaSlice {
+            cu_device_ptr: self.buf.cu_device_ptr + start as sys::CUdeviceptr,
+            len,
+            marker: PhantomData,
+        })
+    }
+
+    /// Allocates `len` elements of `T` like [DeviceArena::alloc()], and sets all of
+    /// them to 0 on the device's work stream.
+    pub fn alloc_zeros<T: ValidAsZeroBits + DeviceRepr>(
+        &self,
+        len: usize,
+    ) -> Result<ArenaSlice<'_, T>, DriverError> {
+        let mut slice = unsafe { self.alloc(len) }?;
+        self.buf.device.memset_zeros(&mut slice)?;
+        Ok(slice)
+    }
+
+    /// Reclaims all the space in the arena, without freeing the underlying memory.
+    ///
+    /// Since this takes `&mut self`, all [ArenaSlice]s must have been dropped.
+    /// Work that is still queued on the device's stream can use the reclaimed space
+    /// safely, as long as later work is queued on the same stream.
+    pub fn reset(&mut self) {
+        self.offset.set(0);
+    }
+}
+
+/// One of the slices created together by [CudaDevice::alloc_batch()], which all
+/// share a single device allocation.
+///
+/// The shared allocation is only freed once every [BatchSlice] from the batch has
+/// been dropped, so holding on to any one of them keeps the whole batch's memory alive.
+#[derive(Debug)]
+pub struct BatchSlice<T> {
+    pub(crate) cu_device_ptr: sys::CUdeviceptr,
+    pub(crate) len: usize,
+    pub(crate) backing: Arc<CudaSlice<u8>>,
+    pub(crate) marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for BatchSlice<T> {}
+unsafe impl<T: Sync> Sync for BatchSlice<T> {}
+
+impl CudaDevice {
+    /// Allocates one slice for every length in `lens` with a single device allocation,
+    /// which is much faster than allocating many small slices individually.
+    /// Each slice starts at an [ARENA_ALIGNMENT] byte boundary.
+    ///
+    /// See [BatchSlice] for when the memory is freed.
+    ///
+    /// # Safety
+    /// This is unsafe because the memory is unset after this call.
+    pub unsafe fn alloc_batch<T: DeviceRepr>(
+        self: &Arc<Self>,
+        lens: &[usize],
+    ) -> Result<Vec<BatchSlice<T>>, DriverError> {
+        let mut offsets = Vec::with_capacity(lens.len());
+        let mut num_bytes: usize = 0;
+        for &len in lens.iter() {
+            let start = num_bytes.next_multiple_of(ARENA_ALIGNMENT);
+            offsets.push(start);
+            num_bytes = size_of_len::<T>(len)?
+                .checked_add(start)
+                .ok_or(DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE))?;
+        }
+        let backing = Arc::new(self.alloc::<u8>(num_bytes)?);
+        Ok(lens
+            .iter()
+            .zip(offsets)
+            .map(|(&len, start)| BatchSlice {
+                cu_device_ptr: backing.cu_device_ptr + start as sys::CUdeviceptr,
+                len,
+                backing: backing.clone(),
+                marker: PhantomData,
+            })
+            .collect())
+    }
+}
+
+impl<T> BatchSlice<T> {
+    /// Get a clone of the underlying [CudaDevice].
+    pub fn device(&self) -> Arc<CudaDevice> {
+        self.backing.device()
+    }
+}
+
+impl<T> DeviceSlice<T> for BatchSlice<T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<T> DevicePtr<T> for BatchSlice<T> {
+    fn device_ptr(&self) -> &sys::CUdeviceptr {
+        &self.cu_device_ptr
+    }
+}
+
+impl<T> DevicePtrMut<T> for BatchSlice<T> {
+    fn device_ptr_mut(&mut self) -> &mut sys::CUdeviceptr {
+        &mut self.cu_device_ptr
+    }
+}
+
+unsafe impl<T: DeviceRepr> DeviceRepr for &BatchSlice<T> {
+    #[inline(always)]
+    fn as_kernel_param(&self) -> *mut std::ffi::c_void {
+        (&self.cu_device_ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
+    }
+}
+
+unsafe impl<T: DeviceRepr> DeviceRepr for &mut BatchSlice<T> {
+    #[inline(always)]
+    fn as_kernel_param(&self) -> *mut std::ffi::c_void {
+        (&self.cu_device_ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
+    }
+}
+
+impl<'a, T> DeviceSlice<T> for ArenaSlice<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'a, T> DevicePtr<T> for ArenaSlice<'a, T> {
+    fn device_ptr(&self) -> &sys::CUdeviceptr {
+        &self.cu_device_ptr
+    }
+}
+
+impl<'a, T> DevicePtrMut<T> for ArenaSlice<'a, T> {
+    fn device_ptr_mut(&mut self) -> &mut sys::CUdeviceptr {
+        &mut self.cu_device_ptr
+    }
+}
+
+unsafe impl<'a, T: DeviceRepr> DeviceRepr for &ArenaSlice<'a, T> {
+    #[inline(always)]
+    fn as_kernel_param(&self) -> *mut std::ffi::c_void {
+        (&self.cu_device_ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
+    }
+}
+
+unsafe impl<'a, T: DeviceRepr> DeviceRepr for &mut ArenaSlice<'a, T> {
+    #[inline(always)]
+    fn as_kernel_param(&self) -> *mut std::ffi::c_void {
+        (&self.cu_device_ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arena_regions_dont_overlap() {
+        let dev = CudaDevice::new(0).unwrap();
+        let arena = dev.alloc_arena(4096).unwrap();
+
+        let mut a = unsafe { arena.alloc::<f32>(10) }.unwrap();
+        let mut b = unsafe { arena.alloc::<u8>(3) }.unwrap();
+        let mut c = unsafe { arena.alloc::<f64>(100) }.unwrap();
+
+        let regions = [
+            (a.cu_device_ptr, a.num_bytes()),
+            (b.cu_device_ptr, b.num_bytes()),
+            (c.cu_device_ptr, c.num_bytes()),
+        ];
+        for (i, &(ptr, num_bytes)) in regions.iter().enumerate() {
+            assert_eq!(ptr as usize % ARENA_ALIGNMENT, 0);
+            for &(other, _) in regions[i + 1..].iter() {
+                assert!(ptr + num_bytes as sys::CUdeviceptr <= other);
+            }
+        }
+        assert_eq!(arena.used(), 2 * ARENA_ALIGNMENT + 800);
+
+        dev.htod_sync_copy_into(&[1.0f32; 10], &mut a).unwrap();
+        dev.htod_sync_copy_into(&[2u8; 3], &mut b).unwrap();
+        dev.htod_sync_copy_into(&[3.0f64; 100], &mut c).unwrap();
+
+        let mut a_host = [0.0f32; 10];
+        let mut b_host = [0u8; 3];
+        let mut c_host = [0.0f64; 100];
+        dev.dtoh_sync_copy_into(&a, &mut a_host).unwrap();
+        dev.dtoh_sync_copy_into(&b, &mut b_host).unwrap();
+        dev.dtoh_sync_copy_into(&c, &mut c_host).unwrap();
+        assert_eq!(a_host, [1.0; 10]);
+        assert_eq!(b_host, [2; 3]);
+        assert_eq!(c_host, [3.0; 100]);
+    }
+
+    #[test]
+    fn test_arena_exhaustion_and_reset() {
+        let dev = CudaDevice::new(0).unwrap();
+        let mut arena = dev.alloc_arena(1024).unwrap();
+
+        let mut first = unsafe { arena.alloc::<u8>(1024) }.unwrap();
+        dev.htod_sync_copy_into(&[2u8; 1024], &mut first).unwrap();
+        let first = first.cu_device_ptr;
+        assert_eq!(
+            unsafe { arena.alloc::<f32>(1) }.unwrap_err(),
+            DriverError(sys::CUresult::CUDA_ERROR_OUT_OF_MEMORY)
+        );
+
+        arena.reset();
+        assert_eq!(arena.used(), 0);
+        let again = arena.alloc_zeros::<bool>(1024).unwrap();
+        assert_eq!(first, again.cu_device_ptr);
+        let mut host = [true; 1024];
+        dev.dtoh_sync_copy_into(&again, &mut host).unwrap();
+        assert_eq!(host, [false; 1024]);
+    }
+
+    #[test]
+    fn test_alloc_batch() {
+        let dev = CudaDevice::new(0).unwrap();
+        let lens: Vec<usize> = (0..100).map(|i| 1 + i % 7).collect();
+        let mut bufs = unsafe { dev.alloc_batch::<u32>(&lens) }.unwrap();
+        assert_eq!(bufs.len(), 100);
+        for (i, buf) in bufs.iter_mut().enumerate() {
+            dev.htod_sync_copy_into(&vec![i as u32; lens[i]], buf)
+                .unwrap();
+        }
+
+        for (i, buf) in bufs.iter().enumerate() {
+            let mut host = vec![0; lens[i]];
+            dev.dtoh_sync_copy_into(buf, &mut host).unwrap();
+            assert_eq!(host, vec![i as u32; lens[i]]);
+        }
+
+        let last = bufs.pop().unwrap();
+        assert_eq!(Arc::strong_count(&last.backing), 100);
+        drop(bufs);
+        assert_eq!(Arc::strong_count(&last.backing), 1);
+    }
+}
@@ -0,0 +1,403 @@
+use crate::driver::{result, sys, DriverError};
+
+use super::alloc::DeviceRepr;
+use super::core::{unwrap_on_drop, CudaDevice};
+
+use core::marker::PhantomData;
+use std::sync::Arc;
+
+/// A wrapper around [sys::CUarray], an opaque block of device memory laid out
+/// for texture & surface access.
+///
+/// Create with [CudaDevice::alloc_array()].
+#[derive(Debug)]
+pub struct CudaArray {
+    pub(crate) cu_array: sys::CUarray,
+    pub(crate) device: Arc<CudaDevice>,
+}
+
+unsafe impl Send for CudaArray {}
+unsafe impl Sync for CudaArray {}
+
+impl Drop for CudaArray {
+    fn drop(&mut self) {
+        unwrap_on_drop(self.device.bind_to_thread());
+        unwrap_on_drop(unsafe { result::array::destroy(self.cu_array) });
+    }
+}
+
+/// The shape & format of a [CudaArray], returned by [CudaArray::info()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArrayInfo {
+    /// Width in elements.
+    pub width: usize,
+    /// Height in elements, 0 for 1D arrays.
+    pub height: usize,
+    /// Depth in elements, 0 for 1D and 2D arrays.
+    pub depth: usize,
+    /// The format of each channel.
+    pub format: sys::CUarray_format,
+    /// The number of channels per element, one of 1, 2 or 4.
+    pub num_channels: u32,
+    /// The `CUDA_ARRAY3D_*` flags the array was created with.
+    pub flags: u32,
+}
+
+impl CudaDevice {
+    /// Allocates a [CudaArray] described by `desc`. Set `desc.Height` to 0 for a 1D array,
+    /// and `desc.Depth` to 0 for a 1D or 2D array.
+    ///
+    /// The contents of the array are unset.
+    pub fn alloc_array(
+        self: &Arc<Self>,
+        desc: sys::CUDA_ARRAY3D_DESCRIPTOR,
+    ) -> Result<CudaArray, DriverError> {
+        self.bind_to_thread()?;
+        let cu_array = unsafe { result::array::create(&desc) }?;
+        Ok(CudaArray {
+            cu_array,
+            device: self.clone(),
+        })
+    }
+}
+
+impl CudaArray {
+    /// Get a clone of the underlying [CudaDevice].
+    pub fn device(&self) -> Arc<CudaDevice> {
+        self.device.clone()
+    }
+
+    /// Get the underlying [sys::CUarray].
+    ///
+    /// # Safety
+    /// While this function is marked as safe, actually using the
+    /// returned object is unsafe.
+    ///
+    /// **You must not destroy the array**, as it is still
+    /// owned by the [CudaArray].
+    pub fn cu_array(&self) -> &sys::CUarray {
+        &self.cu_array
+    }
+
+    /// The extent, format and flags of this array, as reported by the driver.
+    pub fn info(&self) -> Result<ArrayInfo, DriverError> {
+        array_info(&self.device, self.cu_array)
+    }
+
+    /// Synchronously copies `src`, which is rows of `width_bytes`, into a 1D or 2D array,
+    /// starting at byte `x_offset_bytes` of row `y_offset`.
+    ///
+    /// # Panics
+    ///
+    /// If `src` is not a whole number of rows, this method panics.
+    pub fn copy_from_host<T: DeviceRepr>(
+        &mut self,
+        src: &[T],
+        width_bytes: usize,
+        offset: (usize, usize),
+    ) -> Result<(), DriverError> {
+        copy_to_array(&self.device, self.cu_array, src, width_bytes, offset)
+    }
+
+    /// Synchronously copies rows of `width_bytes` out of a 1D or 2D array into `dst`,
+    /// starting at byte `x_offset_bytes` of row `y_offset`.
+    ///
+    /// # Panics
+    ///
+    /// If `dst` is not a whole number of rows, this method panics.
+    ///
+    /// # Safety
+    /// The array is untyped and its contents are unset until written, so the copied
+    /// bytes must be a valid `T`. E.g. they must have been written from a `T` with
+    /// [CudaArray::copy_from_host()], or `T` must be valid for any bit pattern.
+    pub unsafe fn copy_to_host<T: DeviceRepr>(
+        &self,
+        dst: &mut [T],
+        width_bytes: usize,
+        offset: (usize, usize),
+    ) -> Result<(), DriverError> {
+        copy_from_array(&self.device, self.cu_array, dst, width_bytes, offset)
+    }
+}
+
+fn array_info(device: &CudaDevice, cu_array: sys::CUarray) -> Result<ArrayInfo, DriverError> {
+    device.bind_to_thread()?;
+    let desc = unsafe { result::array::get_descriptor(cu_array) }?;
+    Ok(ArrayInfo {
+        width: desc.Width,
+        height: desc.Height,
+        depth: desc.Depth,
+        format: desc.Format,
+        num_channels: desc.NumChannels,
+        flags: desc.Flags,
+    })
+}
+
+fn copy_to_array<T: DeviceRepr>(
+    device: &CudaDevice,
+    cu_array: sys::CUarray,
+    src: &[T],
+    width_bytes: usize,
+    (x_offset_bytes, y_offset): (usize, usize),
+) -> Result<(), DriverError> {
+    assert!(width_bytes > 0);
+    assert_eq!(std::mem::size_of_val(src) % width_bytes, 0);
+    device.bind_to_thread()?;
+    unsafe { result::array::copy_to_array(cu_array, x_offset_bytes, y_offset, src, width_bytes) }
+}
+
+fn copy_from_array<T: DeviceRepr>(
+    device: &CudaDevice,
+    cu_array: sys::CUarray,
+    dst: &mut [T],
+    width_bytes: usize,
+    (x_offset_bytes, y_offset): (usize, usize),
+) -> Result<(), DriverError> {
+    assert!(width_bytes > 0);
+    assert_eq!(std::mem::size_of_val(dst) % width_bytes, 0);
+    device.bind_to_thread()?;
+    unsafe { result::array::copy_from_array(dst, cu_array, x_offset_bytes, y_offset, width_bytes) }
+}
+
+/// A wrapper around [sys::CUmipmappedArray], a chain of [CudaArray]s where each level
+/// is half the size of the previous one in every dimension (rounded down, at least 1).
+///
+/// Create with [CudaDevice::alloc_mipmapped_array()], and access the levels with
+/// [CudaMipmappedArray::level()].
+#[derive(Debug)]
+pub struct CudaMipmappedArray {
+    pub(crate) cu_mipmap: sys::CUmipmappedArray,
+    pub(crate) num_levels: u32,
+    pub(crate) device: Arc<CudaDevice>,
+}
+
+unsafe impl Send for CudaMipmappedArray {}
+unsafe impl Sync for CudaMipmappedArray {}
+
+impl Drop for CudaMipmappedArray {
+    fn drop(&mut self) {
+        unwrap_on_drop(self.device.bind_to_thread());
+        unwrap_on_drop(unsafe { result::array::mipmapped_destroy(self.cu_mipmap) });
+    }
+}
+
+/// One level of a [CudaMipmappedArray], with the same methods as a [CudaArray].
+///
+/// The level's storage belongs to the [CudaMipmappedArray], so it is not freed when this
+/// is dropped, and it can't outlive the [CudaMipmappedArray].
+#[derive(Debug)]
+pub struct MipmapLevel<'a> {
+    cu_array: sys::CUarray,
+    device: &'a Arc<CudaDevice>,
+    marker: PhantomData<&'a mut CudaMipmappedArray>,
+}
+
+impl MipmapLevel<'_> {
+    /// Get a clone of the underlying [CudaDevice].
+    pub fn device(&self) -> Arc<CudaDevice> {
+        self.device.clone()
+    }
+
+    /// Get the underlying [sys::CUarray].
+    ///
+    /// # Safety
+    /// While this function is marked as safe, actually using the
+    /// returned object is unsafe.
+    ///
+    /// **You must not destroy the array**, as it is still
+    /// owned by the [CudaMipmappedArray].
+    pub fn cu_array(&self) -> &sys::CUarray {
+        &self.cu_array
+    }
+
+    /// See [CudaArray::info()].
+    pub fn info(&self) -> Result<ArrayInfo, DriverError> {
+        array_info(self.device, self.cu_array)
+    }
+
+    /// See [CudaArray::copy_from_host()].
+    pub fn copy_from_host<T: DeviceRepr>(
+        &mut self,
+        src: &[T],
+        width_bytes: usize,
+        offset: (usize, usize),
+    ) -> Result<(), DriverError> {
+        copy_to_array(self.device, self.cu_array, src, width_bytes, offset)
+    }
+
+    /// See [CudaArray::copy_to_host()].
+    ///
+    /// # Safety
+    /// The copied bytes must be a valid `T`, see [CudaArray::copy_to_host()].
+    pub unsafe fn copy_to_host<T: DeviceRepr>(
+        &self,
+        dst: &mut [T],
+        width_bytes: usize,
+        offset: (usize, usize),
+    ) -> Result<(), DriverError> {
+        copy_from_array(self.device, self.cu_array, dst, width_bytes, offset)
+    }
+}
+
+impl CudaDevice {
+    /// Allocates a [CudaMipmappedArray] with `num_levels` levels, where level 0 is
+    /// described by `desc` like in [CudaDevice::alloc_array()].
+    ///
+    /// The contents of every level are unset.
+    pub fn alloc_mipmapped_array(
+        self: &Arc<Self>,
+        desc: sys::CUDA_ARRAY3D_DESCRIPTOR,
+        num_levels: u32,
+    ) -> Result<CudaMipmappedArray, DriverError> {
+        self.bind_to_thread()?;
+        let cu_mipmap = unsafe { result::array::mipmapped_create(&desc, num_levels) }?;
+        Ok(CudaMipmappedArray {
+            cu_mipmap,
+            num_levels,
+            device: self.clone(),
+        })
+    }
+}
+
+impl CudaMipmappedArray {
+    /// Get a clone of the underlying [CudaDevice].
+    pub fn device(&self) -> Arc<CudaDevice> {
+        self.device.clone()
+    }
+
+    /// Get the underlying [sys::CUmipmappedArray].
+    ///
+    /// # Safety
+    /// While this function is marked as safe, actually using the
+    /// returned object is unsafe.
+    ///
+    /// **You must not destroy the array**, as it is still
+    /// owned by the [CudaMipmappedArray].
+    pub fn cu_mipmapped_array(&self) -> &sys::CUmipmappedArray {
+        &self.cu_mipmap
+    }
+
+    /// The number of levels this was allocated with.
+    pub fn num_levels(&self) -> u32 {
+        self.num_levels
+    }
+
+    /// Level `level` of this array, where level 0 is the full size array. Each call
+    /// returns a view of the same storage, so writes through one level are visible
+    /// to later calls and to textures sampling this array.
+    ///
+    /// This borrows `self` mutably so that only one level is accessed at a time.
+    ///
+    /// Returns `CUDA_ERROR_INVALID_VALUE` if `level` is not less than
+    /// [CudaMipmappedArray::num_levels()].
+    pub fn level(&mut self, level: u32) -> Result<MipmapLevel<'_>, DriverError> {
+        if level >= self.num_levels {
+            return Err(DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE));
+        }
+        self.device.bind_to_thread()?;
+        let cu_array = unsafe { result::array::mipmapped_get_level(self.cu_mipmap, level) }?;
+        Ok(MipmapLevel {
+            cu_array,
+            device: &self.device,
+            marker: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_array_round_trip() {
+        let dev = CudaDevice::new(0).unwrap();
+        let mut array = dev
+            .alloc_array(sys::CUDA_ARRAY3D_DESCRIPTOR {
+                Width: 4,
+                Height: 3,
+                Depth: 0,
+                Format: sys::CUarray_format::CU_AD_FORMAT_FLOAT,
+                NumChannels: 1,
+                Flags: 0,
+            })
+            .unwrap();
+
+        let src: Vec<f32> = (0..12).map(|i| i as f32).collect();
+        array.copy_from_host(&src, 16, (0, 0)).unwrap();
+        let mut dst = [0.0f32; 12];
+        unsafe { array.copy_to_host(&mut dst, 16, (0, 0)) }.unwrap();
+        assert_eq!(dst.to_vec(), src);
+
+        // the middle two elements of the last two rows
+        array.copy_from_host(&[-1.0f32; 4], 8, (4, 1)).unwrap();
+        let mut sub = [0.0f32; 4];
+        unsafe { array.copy_to_host(&mut sub, 8, (4, 1)) }.unwrap();
+        assert_eq!(sub, [-1.0; 4]);
+        unsafe { array.copy_to_host(&mut dst, 16, (0, 0)) }.unwrap();
+        assert_eq!(
+            dst,
+            [0.0, 1.0, 2.0, 3.0, 4.0, -1.0, -1.0, 7.0, 8.0, -1.0, -1.0, 11.0]
+        );
+    }
+
+    #[test]
+    fn test_array_info() {
+        let dev = CudaDevice::new(0).unwrap();
+        let array = dev
+            .alloc_array(sys::CUDA_ARRAY3D_DESCRIPTOR {
+                Width: 64,
+                Height: 32,
+                Depth: 0,
+                Format: sys::CUarray_format::CU_AD_FORMAT_UNSIGNED_INT16,
+                NumChannels: 2,
+                Flags: 0,
+            })
+            .unwrap();
+        assert_eq!(
+            array.info().unwrap(),
+            ArrayInfo {
+                width: 64,
+                height: 32,
+                depth: 0,
+                format: sys::CUarray_format::CU_AD_FORMAT_UNSIGNED_INT16,
+                num_channels: 2,
+                flags: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_mipmapped_array_levels() {
+        let dev = CudaDevice::new(0).unwrap();
+        let mut mipmap = dev
+            .alloc_mipmapped_array(
+                sys::CUDA_ARRAY3D_DESCRIPTOR {
+                    Width: 8,
+                    Height: 4,
+                    Depth: 0,
+                    Format: sys::CUarray_format::CU_AD_FORMAT_FLOAT,
+                    NumChannels: 1,
+                    Flags: 0,
+                },
+                2,
+            )
+            .unwrap();
+        assert_eq!(mipmap.num_levels(), 2);
+
+        let info = mipmap.level(0).unwrap().info().unwrap();
+        assert_eq!((info.width, info.height), (8, 4));
+        let mut level1 = mipmap.level(1).unwrap();
+        let info = level1.info().unwrap();
+        assert_eq!((info.width, info.height), (4, 2));
+
+        level1.copy_from_host(&[1.0f32; 8], 16, (0, 0)).unwrap();
+        let mut dst = [0.0f32; 8];
+        unsafe { mipmap.level(1).unwrap().copy_to_host(&mut dst, 16, (0, 0)) }.unwrap();
+        assert_eq!(dst, [1.0; 8]);
+
+        assert_eq!(
+            mipmap.level(2).unwrap_err(),
+            DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE)
+        );
+    }
+}
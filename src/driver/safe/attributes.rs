@@ -0,0 +1,97 @@
+use crate::driver::{sys, DriverError};
+
+use super::core::CudaDevice;
+
+#[cfg(feature = "no-std")]
+use spin::RwLock;
+#[cfg(not(feature = "no-std"))]
+use std::sync::RwLock;
+
+use std::{collections::BTreeMap, sync::Arc};
+
+/// Memoizes [CudaDevice::attribute()], so that only the first query of each
+/// attribute goes to the driver. This is safe since a device's attributes never change.
+///
+/// ```ignore
+/// let attrs = CachedAttributes::new(dev.clone());
+/// for _ in 0..n {
+///     let num_sms = attrs.get(sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_MULTIPROCESSOR_COUNT)?;
+/// }
+/// ```
+#[derive(Debug)]
+pub struct CachedAttributes {
+    pub(crate) device: Arc<CudaDevice>,
+    pub(crate) cache: RwLock<BTreeMap<sys::CUdevice_attribute, i32>>,
+}
+
+impl CachedAttributes {
+    /// An empty cache for `device`.
+    pub fn new(device: Arc<CudaDevice>) -> Self {
+        Self {
+            device,
+            cache: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Get a clone of the underlying [CudaDevice].
+    pub fn device(&self) -> Arc<CudaDevice> {
+        self.device.clone()
+    }
+
+    /// The value of `attrib`, which is only queried from the driver the first time.
+    pub fn get(&self, attrib: sys::CUdevice_attribute) -> Result<i32, DriverError> {
+        {
+            let cache = self.cache.read();
+            #[cfg(not(feature = "no-std"))]
+            let cache = cache.unwrap();
+            if let Some(&value) = cache.get(&attrib) {
+                return Ok(value);
+            }
+        }
+        let value = self.device.attribute(attrib)?;
+        #[allow(unused_mut)]
+        {
+            let mut cache = self.cache.write();
+            #[cfg(not(feature = "no-std"))]
+            let mut cache = cache.unwrap();
+            cache.insert(attrib, value);
+        }
+        Ok(value)
+    }
+
+    /// The number of attributes that have been queried from the driver.
+    pub fn len(&self) -> usize {
+        let cache = self.cache.read();
+        #[cfg(not(feature = "no-std"))]
+        let cache = cache.unwrap();
+        cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_attributes() {
+        let dev = CudaDevice::new(0).unwrap();
+        let attrs = CachedAttributes::new(dev.clone());
+        assert!(attrs.is_empty());
+
+        let attrib = sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_MULTIPROCESSOR_COUNT;
+        let expected = dev.attribute(attrib).unwrap();
+        for _ in 0..10 {
+            assert_eq!(attrs.get(attrib).unwrap(), expected);
+        }
+        assert_eq!(attrs.len(), 1);
+
+        let attrib = sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_WARP_SIZE;
+        assert_eq!(attrs.get(attrib).unwrap(), dev.attribute(attrib).unwrap());
+        assert_eq!(attrs.get(attrib).unwrap(), dev.attribute(attrib).unwrap());
+        assert_eq!(attrs.len(), 2);
+    }
+}
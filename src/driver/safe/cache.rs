@@ -0,0 +1,215 @@
+use crate::driver::{sys, DriverError};
+
+use super::alloc::DeviceRepr;
+use super::core::{CudaDevice, CudaSlice};
+use super::device_ptr::{DevicePtr, DevicePtrMut, DeviceSlice};
+
+#[cfg(feature = "no-std")]
+use spin::RwLock;
+#[cfg(not(feature = "no-std"))]
+use std::sync::RwLock;
+
+use std::{collections::BTreeMap, sync::Arc, vec::Vec};
+
+/// Recycles [CudaSlice]s so that repeatedly allocating & freeing similarly sized
+/// buffers doesn't go to the driver each time. This is useful when the device doesn't
+/// support stream ordered allocations (see `CU_DEVICE_ATTRIBUTE_MEMORY_POOLS_SUPPORTED`).
+///
+/// Allocations are rounded up to the next power of two elements, and buffers are
+/// returned to the cache when the [CachedSlice] from [BufferCache::get()] is dropped.
+/// Cached buffers are only freed when the cache is dropped or [BufferCache::clear()] is called.
+///
+/// ```ignore
+/// let cache = BufferCache::<f32>::new(dev.clone());
+/// for _ in 0..num_iters {
+///     let mut a = unsafe { cache.get(1000) }?;
+///     dev.htod_sync_copy_into(&host, &mut a)?;
+///     // ... use a ...
+/// }
+/// ```
+#[derive(Debug)]
+pub struct BufferCache<T> {
+    pub(crate) device: Arc<CudaDevice>,
+    pub(crate) state: RwLock<CacheState<T>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct CacheState<T> {
+    pub(crate) free: BTreeMap<usize, Vec<CudaSlice<T>>>,
+    pub(crate) num_allocs: usize,
+}
+
+/// A buffer of at least the requested length, created with [BufferCache::get()].
+/// It is returned to the cache on drop.
+#[derive(Debug)]
+pub struct CachedSlice<'a, T> {
+    pub(crate) buf: Option<CudaSlice<T>>,
+    pub(crate) cu_device_ptr: sys::CUdeviceptr,
+    pub(crate) len: usize,
+    pub(crate) cache: &'a BufferCache<T>,
+}
+
+impl<T> BufferCache<T> {
+    /// An empty cache for `device`.
+    pub fn new(device: Arc<CudaDevice>) -> Self {
+        Self {
+            device,
+            state: RwLock::new(CacheState {
+                free: BTreeMap::new(),
+                num_allocs: 0,
+            }),
+        }
+    }
+
+    /// Get a clone of the underlying [CudaDevice].
+    pub fn device(&self) -> Arc<CudaDevice> {
+        self.device.clone()
+    }
+
+    /// The number of buffers this cache has allocated from the driver.
+    pub fn num_allocs(&self) -> usize {
+        let state = self.state.read();
+        #[cfg(not(feature = "no-std"))]
+        let state = state.unwrap();
+        state.num_allocs
+    }
+
+    /// The number of buffers currently waiting in the cache to be reused.
+    pub fn num_cached(&self) -> usize {
+        let state = self.state.read();
+        #[cfg(not(feature = "no-std"))]
+        let state = state.unwrap();
+        state.free.values().map(Vec::len).sum()
+    }
+
+    /// Frees all of the buffers currently in the cache.
+    #[allow(unused_mut)]
+    pub fn clear(&self) {
+        let mut state = self.state.write();
+        #[cfg(not(feature = "no-std"))]
+        let mut state = state.unwrap();
+        state.free.clear();
+    }
+
+    #[allow(unused_mut)]
+    fn put(&self, buf: CudaSlice<T>) {
+        let mut state = self.state.write();
+        #[cfg(not(feature = "no-std"))]
+        let mut state = state.unwrap();
+        state.free.entry(buf.capacity).or_default().push(buf);
+    }
+}
+
+impl<T: DeviceRepr> BufferCache<T> {
+    /// Returns a [CachedSlice] of `len` elements, reusing a cached buffer of the same
+    /// power of two size if there is one, and otherwise allocating a new one.
+    ///
+    /// # Safety
+    /// This is unsafe because the memory is unset, or holds whatever a previous user wrote.
+    #[allow(unused_mut)]
+    pub unsafe fn get(&self, len: usize) -> Result<CachedSlice<'_, T>, DriverError> {
+        let bucket = len
+            .checked_next_power_of_two()
+            .ok_or(DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE))?;
+        let reused = {
+            let mut state = self.state.write();
+            #[cfg(not(feature = "no-std"))]
+            let mut state = state.unwrap();
+            state.free.get_mut(&bucket).and_then(Vec::pop)
+        };
+        let buf = match reused {
+            Some(buf) => buf,
+            None => {
+                let buf = self.device.alloc::<T>(bucket)?;
+                let mut state = self.state.write();
+                #[cfg(not(feature = "no-std"))]
+                let mut state = state.unwrap();
+                state.num_allocs += 1;
+                buf
+            }
+        };
+        Ok(CachedSlice {
+            cu_device_ptr: buf.cu_device_ptr,
+            buf: Some(buf),
+            len,
+            cache: self,
+        })
+    }
+}
+
+impl<'a, T> CachedSlice<'a, T> {
+    /// The number of elements in the underlying buffer, which is at least [DeviceSlice::len()].
+    pub fn capacity(&self) -> usize {
+        self.buf.as_ref().unwrap().capacity
+    }
+}
+
+impl<'a, T> Drop for CachedSlice<'a, T> {
+    fn drop(&mut self) {
+        if let Some(buf) = self.buf.take() {
+            self.cache.put(buf);
+        }
+    }
+}
+
+impl<'a, T> DeviceSlice<T> for CachedSlice<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'a, T> DevicePtr<T> for CachedSlice<'a, T> {
+    fn device_ptr(&self) -> &sys::CUdeviceptr {
+        &self.cu_device_ptr
+    }
+}
+
+impl<'a, T> DevicePtrMut<T> for CachedSlice<'a, T> {
+    fn device_ptr_mut(&mut self) -> &mut sys::CUdeviceptr {
+        &mut self.cu_device_ptr
+    }
+}
+
+unsafe impl<'a, T: DeviceRepr> DeviceRepr for &CachedSlice<'a, T> {
+    #[inline(always)]
+    fn as_kernel_param(&self) -> *mut std::ffi::c_void {
+        (&self.cu_device_ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
+    }
+}
+
+unsafe impl<'a, T: DeviceRepr> DeviceRepr for &mut CachedSlice<'a, T> {
+    #[inline(always)]
+    fn as_kernel_param(&self) -> *mut std::ffi::c_void {
+        (&self.cu_device_ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_cache_reuses_buffers() {
+        let dev = CudaDevice::new(0).unwrap();
+        let cache = BufferCache::<f32>::new(dev.clone());
+
+        for i in 0..10 {
+            let mut a = unsafe { cache.get(1000) }.unwrap();
+            let b = unsafe { cache.get(600 + i) }.unwrap();
+            assert_eq!(a.len(), 1000);
+            assert_eq!(a.capacity(), 1024);
+            assert_eq!(b.capacity(), 1024);
+            dev.htod_sync_copy_into(&[i as f32; 1000], &mut a).unwrap();
+            let mut host = [0.0; 1000];
+            dev.dtoh_sync_copy_into(&a, &mut host).unwrap();
+            assert_eq!(host, [i as f32; 1000]);
+            assert_eq!(cache.num_allocs(), 2);
+        }
+        assert_eq!(cache.num_cached(), 2);
+
+        let _c = unsafe { cache.get(10) }.unwrap();
+        assert_eq!(cache.num_allocs(), 3);
+        cache.clear();
+        assert_eq!(cache.num_cached(), 0);
+    }
+}
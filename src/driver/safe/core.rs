@@ -40,6 +40,9 @@ pub struct CudaDevice {
     pub(crate) modules: RwLock<BTreeMap<String, CudaModule>>,
     pub(crate) ordinal: usize,
     pub(crate) is_async: bool,
+    /// Bytes owned by live [CudaSlice]s allocated through this device.
+    #[cfg(feature = "track-allocations")]
+    pub(crate) allocated_bytes: core::sync::atomic::AtomicUsize,
 }
 
 unsafe impl Send for CudaDevice {}
@@ -47,6 +50,9 @@ unsafe impl Sync for CudaDevice {}
 
 impl CudaDevice {
     /// Creates a new [CudaDevice] on device index `ordinal`.
+    ///
+    /// This eagerly initializes the device's primary context, so the cost of
+    /// context creation is paid here instead of by the first allocation or launch.
     pub fn new(ordinal: usize) -> Result<Arc<Self>, result::DriverError> {
         result::init().unwrap();
 
@@ -76,20 +82,94 @@ impl CudaDevice {
             modules: RwLock::new(BTreeMap::new()),
             ordinal,
             is_async,
+            #[cfg(feature = "track-allocations")]
+            allocated_bytes: core::sync::atomic::AtomicUsize::new(0),
         };
         Ok(Arc::new(device))
     }
 
+    /// Creates a new [CudaDevice] on device index `ordinal`, after setting the flags
+    /// of the primary context to `flags`, a combination of [sys::CUctx_flags]
+    /// (e.g. `CU_CTX_SCHED_BLOCKING_SYNC`).
+    ///
+    /// The flags apply to the primary context, so they are shared with every other
+    /// [CudaDevice] on the same ordinal.
+    pub fn new_with_ctx_flags(
+        ordinal: usize,
+        flags: std::ffi::c_uint,
+    ) -> Result<Arc<Self>, result::DriverError> {
+        result::init()?;
+        let cu_device = result::device::get(ordinal as i32)?;
+        unsafe { result::primary_ctx::set_flags(cu_device, flags) }?;
+        Self::new(ordinal)
+    }
+
+    /// Drops this device and resets its primary context, destroying everything that was
+    /// allocated in it, e.g. to recover from a sticky error.
+    ///
+    /// Every [CudaSlice], [CudaStream], [CudaFunction] etc. holds an [Arc] of the device,
+    /// so this only resets if `self` is the last reference to it, and no other [CudaDevice]
+    /// for the same ordinal is alive. Otherwise nothing is reset and this returns
+    /// `CUDA_ERROR_CONTEXT_ALREADY_IN_USE`. There is no extra bookkeeping for this beyond
+    /// the reference count of the [Arc].
+    ///
+    /// # Safety
+    /// Another thread must not call [CudaDevice::new()] (or otherwise retain the primary
+    /// context) for the same ordinal while this runs. The check that the context is unused
+    /// and the reset are not atomic, so that thread's context could be reset out from under it.
+    pub unsafe fn reset(self: Arc<Self>) -> Result<(), result::DriverError> {
+        let in_use = result::DriverError(sys::CUresult::CUDA_ERROR_CONTEXT_ALREADY_IN_USE);
+        let cu_device = self.cu_device;
+        drop(Arc::try_unwrap(self).map_err(|_| in_use)?);
+        let (_, active) = result::primary_ctx::get_state(cu_device)?;
+        if active {
+            return Err(in_use);
+        }
+        result::primary_ctx::reset(cu_device)
+    }
+
     pub fn count() -> Result<i32, result::DriverError> {
         result::init().unwrap();
         result::device::get_count()
     }
 
+    /// Creates a [CudaDevice] for whichever device currently has the most free memory,
+    /// a common heuristic for picking a GPU. Ties go to the lowest ordinal.
+    ///
+    /// This creates (or retains) the primary context of every device to query it, and
+    /// the returned device is bound to the calling thread.
+    ///
+    /// Returns `CUDA_ERROR_NO_DEVICE` if there are no devices.
+    pub fn least_loaded() -> Result<Arc<Self>, result::DriverError> {
+        let mut best: Option<(Arc<Self>, usize)> = None;
+        for ordinal in 0..Self::count()? as usize {
+            let dev = Self::new(ordinal)?;
+            let (free, _) = dev.mem_get_info()?;
+            if !matches!(best, Some((_, best_free)) if best_free >= free) {
+                best = Some((dev, free));
+            }
+        }
+        let (dev, _) = best.ok_or(result::DriverError(sys::CUresult::CUDA_ERROR_NO_DEVICE))?;
+        dev.bind_to_thread()?;
+        Ok(dev)
+    }
+
+    /// The free and total memory of this device in bytes, as reported by the driver.
+    pub fn mem_get_info(&self) -> Result<(usize, usize), result::DriverError> {
+        self.bind_to_thread()?;
+        result::mem_get_info()
+    }
+
     /// Get the `ordinal` index of this [CudaDevice].
     pub fn ordinal(&self) -> usize {
         self.ordinal
     }
 
+    /// The name of this device, e.g. `"NVIDIA GeForce RTX 3090"`.
+    pub fn name(&self) -> Result<String, result::DriverError> {
+        unsafe { result::device::get_name(self.cu_device) }
+    }
+
     /// Get the underlying [sys::CUdevice] of this [CudaDevice].
     ///
     /// # Safety
@@ -131,6 +211,179 @@ impl CudaDevice {
     pub fn attribute(&self, attrib: sys::CUdevice_attribute) -> Result<i32, result::DriverError> {
         unsafe { result::device::get_attribute(self.cu_device, attrib) }
     }
+
+    /// Whether this device can directly access memory allocated on `peer`.
+    pub fn can_access_peer(&self, peer: &CudaDevice) -> Result<bool, result::DriverError> {
+        unsafe { result::device::can_access_peer(self.cu_device, peer.cu_device) }
+    }
+
+    /// Lets work in this device's context access memory allocated on `peer`, e.g. kernels
+    /// reading a [CudaSlice] from `peer`. Access is one directional, and it is not an error
+    /// if it was already enabled.
+    ///
+    /// Returns `CUDA_ERROR_PEER_ACCESS_UNSUPPORTED` if [CudaDevice::can_access_peer()] is false.
+    pub fn enable_peer_access(&self, peer: &CudaDevice) -> Result<(), result::DriverError> {
+        self.bind_to_thread()?;
+        match unsafe { result::ctx::enable_peer_access(peer.cu_primary_ctx) } {
+            Err(result::DriverError(sys::CUresult::CUDA_ERROR_PEER_ACCESS_ALREADY_ENABLED)) => {
+                Ok(())
+            }
+            res => res,
+        }
+    }
+
+    /// Calls [CudaDevice::enable_peer_access()] for every ordered pair of `devices` where
+    /// [CudaDevice::can_access_peer()] is true, skipping pairs that can't access each other.
+    ///
+    /// This is best effort: it stops at the first error, leaving access enabled for the
+    /// pairs before it.
+    pub fn enable_all_peer_access(devices: &[Arc<CudaDevice>]) -> Result<(), result::DriverError> {
+        for dev in devices.iter() {
+            for peer in devices.iter() {
+                if dev.cu_device != peer.cu_device && dev.can_access_peer(peer)? {
+                    dev.enable_peer_access(peer)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Get an attribute of the link from this device to `peer`:
+    /// - `CU_DEVICE_P2P_ATTRIBUTE_PERFORMANCE_RANK` is the relative performance of the link,
+    ///   where lower is better
+    /// - `CU_DEVICE_P2P_ATTRIBUTE_ACCESS_SUPPORTED` is 1 if peer access is supported
+    /// - `CU_DEVICE_P2P_ATTRIBUTE_NATIVE_ATOMIC_SUPPORTED` is 1 if atomics over the link are supported
+    /// - `CU_DEVICE_P2P_ATTRIBUTE_ACCESS_ACCESS_SUPPORTED` is 1 if cuda arrays can be accessed over the link
+    pub fn p2p_attribute(
+        &self,
+        peer: &CudaDevice,
+        attrib: sys::CUdevice_P2PAttribute,
+    ) -> Result<i32, result::DriverError> {
+        unsafe { result::device::get_p2p_attribute(attrib, self.cu_device, peer.cu_device) }
+    }
+
+    /// The maximum width in elements of a 1D texture bound to linear memory, where each
+    /// element has `num_channels` (1, 2 or 4) channels of `format`, e.g.
+    /// `CU_AD_FORMAT_FLOAT` with 4 channels for a `float4` texture.
+    pub fn max_texture_1d_linear_width(
+        &self,
+        format: sys::CUarray_format,
+        num_channels: u32,
+    ) -> Result<usize, result::DriverError> {
+        unsafe {
+            result::device::get_texture_1d_linear_max_width(self.cu_device, format, num_channels)
+        }
+    }
+
+    /// Fills `attr_list`, an `NvSciSyncAttrList` created with the NvSci libraries, with the
+    /// attributes this device needs to signal (`CUDA_NVSCISYNC_ATTR_SIGNAL`) and/or wait on
+    /// (`CUDA_NVSCISYNC_ATTR_WAIT`) NvSciSync objects. See
+    /// [result::device::get_nvsci_sync_attributes()].
+    ///
+    /// This is only supported on Tegra platforms, and requires the `tegra` feature.
+    /// Without it this returns `CUDA_ERROR_NOT_SUPPORTED` without calling the driver.
+    ///
+    /// # Safety
+    /// With the `tegra` feature, `attr_list` must be a valid `NvSciSyncAttrList`.
+    pub unsafe fn nvsci_sync_attributes(
+        &self,
+        attr_list: *mut std::ffi::c_void,
+        flags: std::ffi::c_int,
+    ) -> Result<(), result::DriverError> {
+        result::device::get_nvsci_sync_attributes(attr_list, self.cu_device, flags)
+    }
+
+    /// Whether this is an integrated GPU, which shares physical memory with the host.
+    ///
+    /// On integrated GPUs mapped host memory (see [CudaDevice::alloc_pinned_mapped()])
+    /// is as fast as device memory, so zero-copy access is usually better than explicit copies.
+    /// This is false for discrete GPUs.
+    pub fn is_integrated(&self) -> Result<bool, result::DriverError> {
+        let integrated = self.attribute(sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_INTEGRATED)?;
+        Ok(integrated != 0)
+    }
+
+    /// Whether the device and host share one memory architecture, i.e. the GPU is integrated
+    /// or it can access pageable host memory coherently through the host's page tables
+    /// (`CU_DEVICE_ATTRIBUTE_PAGEABLE_MEMORY_ACCESS_USES_HOST_PAGE_TABLES`).
+    ///
+    /// This is false for discrete GPUs attached over PCIe.
+    pub fn is_unified_memory_arch(&self) -> Result<bool, result::DriverError> {
+        let host_page_tables = self.attribute(
+            sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_PAGEABLE_MEMORY_ACCESS_USES_HOST_PAGE_TABLES,
+        )?;
+        Ok(self.is_integrated()? || host_page_tables != 0)
+    }
+
+    /// A heuristic for the number of blocks of `block_size` threads that fit on the device
+    /// at once, i.e. the number of multiprocessors times how many such blocks each can hold
+    /// (limited by `CU_DEVICE_ATTRIBUTE_MAX_THREADS_PER_MULTIPROCESSOR` and
+    /// `CU_DEVICE_ATTRIBUTE_MAX_BLOCKS_PER_MULTIPROCESSOR`).
+    ///
+    /// This is a good grid size for kernels with grid-stride loops. It ignores a kernel's
+    /// register & shared memory usage, which
+    /// [CudaFunction::occupancy_max_active_blocks_per_multiprocessor()] accounts for.
+    ///
+    /// Returns `CUDA_ERROR_INVALID_VALUE` if `block_size` is 0 or larger than
+    /// `CU_DEVICE_ATTRIBUTE_MAX_THREADS_PER_BLOCK`.
+    pub fn resident_blocks_estimate(&self, block_size: u32) -> Result<u32, result::DriverError> {
+        let max_block_size =
+            self.attribute(sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_MAX_THREADS_PER_BLOCK)?;
+        if block_size == 0 || block_size > max_block_size as u32 {
+            return Err(result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE));
+        }
+        let num_sms =
+            self.attribute(sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_MULTIPROCESSOR_COUNT)?;
+        let threads_per_sm = self.attribute(
+            sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_MAX_THREADS_PER_MULTIPROCESSOR,
+        )?;
+        let max_blocks_per_sm = self.attribute(
+            sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_MAX_BLOCKS_PER_MULTIPROCESSOR,
+        )?;
+        let blocks_per_sm = (threads_per_sm as u32 / block_size).min(max_blocks_per_sm as u32);
+        Ok(num_sms as u32 * blocks_per_sm)
+    }
+
+    /// Whether ECC (error correcting code) memory is enabled on this device.
+    ///
+    /// If an uncorrectable ECC error happens, calls return a sticky error that
+    /// [result::DriverError::is_ecc_uncorrectable()] recognizes, and the device must be reset.
+    pub fn ecc_enabled(&self) -> Result<bool, result::DriverError> {
+        let enabled = self.attribute(sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_ECC_ENABLED)?;
+        Ok(enabled != 0)
+    }
+
+    /// Whether [CudaDevice::flush_gpu_direct_rdma_writes()] is supported by this device.
+    pub fn supports_flush_gpu_direct_rdma_writes(&self) -> Result<bool, result::DriverError> {
+        let options = self.attribute(
+            sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_GPU_DIRECT_RDMA_FLUSH_WRITES_OPTIONS,
+        )?;
+        let host =
+            sys::CUflushGPUDirectRDMAWritesOptions::CU_FLUSH_GPU_DIRECT_RDMA_WRITES_OPTION_HOST;
+        Ok(options & host as i32 != 0)
+    }
+
+    /// Blocks until all GPUDirect RDMA writes into this device's context that are
+    /// visible to `scope` have completed. This is needed before consuming data that a
+    /// third party device (e.g. a NIC) wrote directly into device memory.
+    ///
+    /// Returns `CUDA_ERROR_NOT_SUPPORTED` if the device doesn't support flushing,
+    /// see [CudaDevice::supports_flush_gpu_direct_rdma_writes()].
+    pub fn flush_gpu_direct_rdma_writes(
+        &self,
+        scope: sys::CUflushGPUDirectRDMAWritesScope,
+    ) -> Result<(), result::DriverError> {
+        if !self.supports_flush_gpu_direct_rdma_writes()? {
+            return Err(result::DriverError(sys::CUresult::CUDA_ERROR_NOT_SUPPORTED));
+        }
+        self.bind_to_thread()?;
+        unsafe {
+            result::device::flush_gpu_direct_rdma_writes(
+                sys::CUflushGPUDirectRDMAWritesTarget::CU_FLUSH_GPU_DIRECT_RDMA_WRITES_TARGET_CURRENT_CTX,
+                scope,
+            )
+        }
+    }
 }
 
 impl Drop for CudaDevice {
@@ -193,6 +446,7 @@ impl Drop for CudaDevice {
 pub struct CudaSlice<T> {
     pub(crate) cu_device_ptr: sys::CUdeviceptr,
     pub(crate) len: usize,
+    pub(crate) capacity: usize,
     pub(crate) device: Arc<CudaDevice>,
     pub(crate) host_buf: Option<Pin<Vec<T>>>,
 }
@@ -200,16 +454,28 @@ pub struct CudaSlice<T> {
 unsafe impl<T: Send> Send for CudaSlice<T> {}
 unsafe impl<T: Sync> Sync for CudaSlice<T> {}
 
+/// Unwraps the result of releasing a resource in a [Drop] impl, except that
+/// errors from the driver shutting down are ignored (see [result::DriverError::is_deinitialized()]).
+pub(crate) fn unwrap_on_drop(res: Result<(), result::DriverError>) {
+    match res {
+        Err(e) if e.is_deinitialized() => {}
+        res => res.unwrap(),
+    }
+}
+
 impl<T> Drop for CudaSlice<T> {
     fn drop(&mut self) {
-        self.device.bind_to_thread().unwrap();
-        unsafe {
+        unwrap_on_drop(self.device.bind_to_thread());
+        unwrap_on_drop(unsafe {
             if self.device.is_async {
-                result::free_async(self.cu_device_ptr, self.device.stream).unwrap();
+                result::free_async(self.cu_device_ptr, self.device.stream)
             } else {
-                result::free_sync(self.cu_device_ptr).unwrap();
+                result::free_sync(self.cu_device_ptr)
             }
-        }
+        });
+        #[cfg(feature = "track-allocations")]
+        self.device
+            .track_free(self.capacity * std::mem::size_of::<T>());
     }
 }
 
@@ -218,6 +484,28 @@ impl<T> CudaSlice<T> {
     pub fn device(&self) -> Arc<CudaDevice> {
         self.device.clone()
     }
+
+    /// The number of elements this slice was allocated with, which is at least
+    /// [DeviceSlice::len()].
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Changes the length of this slice to `new_len` without reallocating, e.g. after
+    /// a kernel has written a variable length result into a prefix of it. Copies and
+    /// views afterwards only cover the first `new_len` elements.
+    ///
+    /// Returns `CUDA_ERROR_INVALID_VALUE` if `new_len` exceeds [CudaSlice::capacity()].
+    ///
+    /// # Safety
+    /// The first `new_len` elements must hold valid values of `T`.
+    pub unsafe fn set_len(&mut self, new_len: usize) -> Result<(), result::DriverError> {
+        if new_len > self.capacity {
+            return Err(result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE));
+        }
+        self.len = new_len;
+        Ok(())
+    }
 }
 
 impl<T: DeviceRepr> CudaSlice<T> {
@@ -387,6 +675,26 @@ impl CudaFunction {
 
         Ok(cluster_size as u32)
     }
+
+    /// Sets the shared memory bank size used by all subsequent launches of this function.
+    ///
+    /// This has no effect on devices with a fixed shared memory bank size.
+    pub fn set_shared_mem_config(
+        &self,
+        config: sys::CUsharedconfig,
+    ) -> Result<(), result::DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe { result::function::set_shared_mem_config(self.cu_function, config) }
+    }
+
+    /// The name of this function, which is mangled unless it was declared `extern "C"`.
+    ///
+    /// Returns `CUDA_ERROR_NOT_FOUND` if the driver is older than CUDA 12.3.
+    #[cfg(feature = "cuda-12030")]
+    pub fn name(&self) -> Result<String, result::DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe { result::function::get_name(self.cu_function) }
+    }
 }
 
 unsafe impl Send for CudaFunction {}
@@ -413,10 +721,23 @@ unsafe impl Sync for CudaFunction {}
 /// - 3 will launch after 1 on the default work stream, but potentially concurrently to 2.
 /// - 4 will place a streamWaitEvent(`&stream`) on default work stream
 /// - 5 will happen on the default stream **after the default stream waits for 2**
+///
+/// All async APIs take `&CudaStream`, which is already a [Copy] handle that can't
+/// outlive the stream, so it can be threaded through functions without caring
+/// about ownership:
+/// ```compile_fail
+/// # use cudarc::driver::*;
+/// let dev = CudaDevice::new(0).unwrap();
+/// let stream_ref = {
+///     let stream = dev.fork_default_stream().unwrap();
+///     &stream
+/// };
+/// stream_ref.synchronize().unwrap();
+/// ```
 #[derive(Debug)]
 pub struct CudaStream {
     pub stream: sys::CUstream,
-    device: Arc<CudaDevice>,
+    pub(crate) device: Arc<CudaDevice>,
 }
 
 impl CudaDevice {
@@ -437,6 +758,25 @@ impl CudaDevice {
         Ok(stream)
     }
 
+    /// A non-owning [CudaStream] for the legacy null stream (stream 0), which can be passed to
+    /// anything that takes a [CudaStream] without creating a new stream. Dropping it does nothing.
+    ///
+    /// The null stream synchronizes implicitly: work queued on it waits for all prior work on
+    /// every blocking stream of the context, and work queued on blocking streams afterwards
+    /// waits for it. Streams from [CudaDevice::fork_default_stream()] are non-blocking, so they
+    /// are **not** implicitly synchronized with the null stream.
+    ///
+    /// The device's default work stream is also the null stream, so work queued on this runs
+    /// in order with e.g. [CudaDevice::htod_copy()] and [CudaDevice::synchronize()] waits for it.
+    ///
+    /// Note the null stream cannot be captured into a graph.
+    pub fn null_stream(self: &Arc<Self>) -> CudaStream {
+        CudaStream {
+            stream: result::stream::null(),
+            device: self.clone(),
+        }
+    }
+
     /// Forces [CudaStream] to drop, causing the default work stream to block on `streams` completion.
     /// **This is asynchronous with respect to the host.**
     #[allow(unused_variables)]
@@ -467,14 +807,124 @@ impl CudaStream {
             )
         }
     }
+
+    /// Blocks until all work queued on this stream has completed.
+    pub fn synchronize(&self) -> Result<(), result::DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe { result::stream::synchronize(self.stream) }
+    }
+
+    /// Queries this stream, and only blocks until its work has completed if it is still busy.
+    /// Returns whether there was outstanding work, which is cheaper than always calling
+    /// [CudaStream::synchronize()] in loops that usually find the stream idle.
+    pub fn sync_if_busy(&self) -> Result<bool, result::DriverError> {
+        self.device.bind_to_thread()?;
+        match unsafe { result::stream::query(self.stream) } {
+            Ok(()) => Ok(false),
+            Err(result::DriverError(sys::CUresult::CUDA_ERROR_NOT_READY)) => {
+                unsafe { result::stream::synchronize(self.stream) }?;
+                Ok(true)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// An id for this stream that is unique within the process, e.g. for correlating
+    /// work across logs. Returns `CUDA_ERROR_NOT_FOUND` on drivers older than CUDA 12.0.
+    #[cfg(feature = "cuda-12000")]
+    pub fn id(&self) -> Result<u64, result::DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe { result::stream::get_id(self.stream) }
+    }
+
+    /// Synchronizes every stream in `streams` in order, stopping at the first error.
+    /// See [CudaStream::synchronize_all_collect()] to get the status of every stream.
+    pub fn synchronize_all(streams: &[&CudaStream]) -> Result<(), result::DriverError> {
+        for stream in streams.iter() {
+            stream.synchronize()?;
+        }
+        Ok(())
+    }
+
+    /// Synchronizes every stream in `streams`, returning the result of each one,
+    /// so the index of any stream that failed is known.
+    pub fn synchronize_all_collect(
+        streams: &[&CudaStream],
+    ) -> Vec<Result<(), result::DriverError>> {
+        streams.iter().map(|stream| stream.synchronize()).collect()
+    }
+
+    /// Copies the attributes of `template` onto this stream, which are:
+    /// - `CU_LAUNCH_ATTRIBUTE_ACCESS_POLICY_WINDOW`, the L2 access policy window
+    /// - `CU_LAUNCH_ATTRIBUTE_SYNCHRONIZATION_POLICY`
+    /// - `CU_LAUNCH_ATTRIBUTE_PRIORITY`, on drivers that support it
+    ///
+    /// This does not copy the stream's flags (e.g. non-blocking), which are fixed at creation.
+    pub fn copy_attributes_from(&self, template: &CudaStream) -> Result<(), result::DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe { result::stream::copy_attributes(self.stream, template.stream) }
+    }
+
+    /// Gets the value of `attr` for this stream. Only the union member corresponding
+    /// to `attr` is meaningful.
+    pub fn attribute(
+        &self,
+        attr: sys::CUstreamAttrID,
+    ) -> Result<sys::CUstreamAttrValue, result::DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe { result::stream::get_attribute(self.stream, attr) }
+    }
+
+    /// Sets the value of `attr` for this stream.
+    ///
+    /// # Safety
+    /// `value` must have the union member corresponding to `attr` set.
+    pub unsafe fn set_attribute(
+        &self,
+        attr: sys::CUstreamAttrID,
+        value: &sys::CUstreamAttrValue,
+    ) -> Result<(), result::DriverError> {
+        self.device.bind_to_thread()?;
+        result::stream::set_attribute(self.stream, attr, value)
+    }
+
+    /// How a host thread waits on this stream, e.g. in [CudaStream::synchronize()]:
+    /// - `CU_SYNC_POLICY_AUTO` picks one of the below based on the number of active contexts
+    ///   and processors
+    /// - `CU_SYNC_POLICY_SPIN` busy waits, for the lowest latency at the cost of a CPU core
+    /// - `CU_SYNC_POLICY_YIELD` spins but yields the thread to other threads
+    /// - `CU_SYNC_POLICY_BLOCKING_SYNC` blocks the thread on a synchronization primitive
+    ///
+    /// This overrides the context wide `CU_CTX_SCHED_*` flags for waits on this stream only.
+    pub fn sync_policy(&self) -> Result<sys::CUsynchronizationPolicy, result::DriverError> {
+        let value =
+            self.attribute(sys::CUstreamAttrID::CU_LAUNCH_ATTRIBUTE_SYNCHRONIZATION_POLICY)?;
+        Ok(unsafe { value.syncPolicy })
+    }
+
+    /// Sets how a host thread waits on this stream, see [CudaStream::sync_policy()].
+    pub fn set_sync_policy(
+        &self,
+        policy: sys::CUsynchronizationPolicy,
+    ) -> Result<(), result::DriverError> {
+        let mut value: sys::CUstreamAttrValue = unsafe { std::mem::zeroed() };
+        value.syncPolicy = policy;
+        unsafe {
+            self.set_attribute(
+                sys::CUstreamAttrID::CU_LAUNCH_ATTRIBUTE_SYNCHRONIZATION_POLICY,
+                &value,
+            )
+        }
+    }
 }
 
 impl Drop for CudaStream {
     fn drop(&mut self) {
-        self.device.wait_for(self).unwrap();
-        unsafe {
-            result::stream::destroy(self.stream).unwrap();
+        if self.stream.is_null() {
+            return;
         }
+        unwrap_on_drop(self.device.wait_for(self));
+        unwrap_on_drop(unsafe { result::stream::destroy(self.stream) });
     }
 }
 
@@ -484,7 +934,7 @@ pub struct CudaView<'a, T> {
     pub(crate) root: &'a sys::CUdeviceptr,
     pub(crate) ptr: sys::CUdeviceptr,
     pub(crate) len: usize,
-    marker: PhantomData<T>,
+    pub(crate) marker: PhantomData<T>,
 }
 
 impl<T> CudaSlice<T> {
@@ -492,14 +942,15 @@ impl<T> CudaSlice<T> {
     ///
     /// Returns `None` if `range.start >= self.len`
     pub fn slice(&self, range: impl RangeBounds<usize>) -> CudaView<'_, T> {
-        self.try_slice(range).unwrap()
+        self.try_slice(range).expect(SLICE_PANIC)
     }
 
     /// Fallible version of [CudaSlice::slice]
     pub fn try_slice(&self, range: impl RangeBounds<usize>) -> Option<CudaView<'_, T>> {
-        range.bounds(..self.len()).map(|(start, end)| CudaView {
+        let (start, end) = range.bounds(..self.len())?;
+        Some(CudaView {
             root: &self.cu_device_ptr,
-            ptr: self.cu_device_ptr + (start * std::mem::size_of::<T>()) as u64,
+            ptr: element_ptr::<T>(self.cu_device_ptr, start)?,
             len: end - start,
             marker: PhantomData,
         })
@@ -513,7 +964,7 @@ impl<T> CudaSlice<T> {
     /// This is unsafe because not the memory for the view may not be a valid interpretation
     /// for the type `S`.
     pub unsafe fn transmute<S>(&self, len: usize) -> Option<CudaView<'_, S>> {
-        (len * std::mem::size_of::<S>() <= self.num_bytes()).then_some(CudaView {
+        (len.checked_mul(std::mem::size_of::<S>())? <= self.num_bytes()).then_some(CudaView {
             root: &self.cu_device_ptr,
             ptr: self.cu_device_ptr,
             len,
@@ -527,14 +978,15 @@ impl<'a, T> CudaView<'a, T> {
     ///
     /// Returns `None` if `range.start >= self.len`
     pub fn slice(&self, range: impl RangeBounds<usize>) -> CudaView<'a, T> {
-        self.try_slice(range).unwrap()
+        self.try_slice(range).expect(SLICE_PANIC)
     }
 
     /// Fallible version of [CudaView::slice]
     pub fn try_slice(&self, range: impl RangeBounds<usize>) -> Option<CudaView<'a, T>> {
-        range.bounds(..self.len()).map(|(start, end)| CudaView {
+        let (start, end) = range.bounds(..self.len())?;
+        Some(CudaView {
             root: self.root,
-            ptr: self.ptr + (start * std::mem::size_of::<T>()) as u64,
+            ptr: element_ptr::<T>(self.ptr, start)?,
             len: end - start,
             marker: PhantomData,
         })
@@ -547,7 +999,7 @@ pub struct CudaViewMut<'a, T> {
     pub(crate) root: &'a mut sys::CUdeviceptr,
     pub(crate) ptr: sys::CUdeviceptr,
     pub(crate) len: usize,
-    marker: PhantomData<T>,
+    pub(crate) marker: PhantomData<T>,
 }
 
 impl<T> CudaSlice<T> {
@@ -555,13 +1007,14 @@ impl<T> CudaSlice<T> {
     ///
     /// Returns `None` if `offset >= self.len`
     pub fn slice_mut(&mut self, range: impl RangeBounds<usize>) -> CudaViewMut<'_, T> {
-        self.try_slice_mut(range).unwrap()
+        self.try_slice_mut(range).expect(SLICE_PANIC)
     }
 
     /// Fallible version of [CudaSlice::slice_mut]
     pub fn try_slice_mut(&mut self, range: impl RangeBounds<usize>) -> Option<CudaViewMut<'_, T>> {
-        range.bounds(..self.len()).map(|(start, end)| CudaViewMut {
-            ptr: self.cu_device_ptr + (start * std::mem::size_of::<T>()) as u64,
+        let (start, end) = range.bounds(..self.len())?;
+        Some(CudaViewMut {
+            ptr: element_ptr::<T>(self.cu_device_ptr, start)?,
             root: &mut self.cu_device_ptr,
             len: end - start,
             marker: PhantomData,
@@ -576,7 +1029,7 @@ impl<T> CudaSlice<T> {
     /// This is unsafe because not the memory for the view may not be a valid interpretation
     /// for the type `S`.
     pub unsafe fn transmute_mut<S>(&mut self, len: usize) -> Option<CudaViewMut<'_, S>> {
-        (len * std::mem::size_of::<S>() <= self.num_bytes()).then_some(CudaViewMut {
+        (len.checked_mul(std::mem::size_of::<S>())? <= self.num_bytes()).then_some(CudaViewMut {
             ptr: self.cu_device_ptr,
             root: &mut self.cu_device_ptr,
             len,
@@ -590,14 +1043,15 @@ impl<'a, T> CudaViewMut<'a, T> {
     ///
     /// Returns `None` if `range.start >= self.len`
     pub fn slice<'b: 'a>(&'b self, range: impl RangeBounds<usize>) -> CudaView<'a, T> {
-        self.try_slice(range).unwrap()
+        self.try_slice(range).expect(SLICE_PANIC)
     }
 
     /// Fallible version of [CudaViewMut::slice]
     pub fn try_slice<'b: 'a>(&'b self, range: impl RangeBounds<usize>) -> Option<CudaView<'a, T>> {
-        range.bounds(..self.len()).map(|(start, end)| CudaView {
+        let (start, end) = range.bounds(..self.len())?;
+        Some(CudaView {
             root: self.root,
-            ptr: self.ptr + (start * std::mem::size_of::<T>()) as u64,
+            ptr: element_ptr::<T>(self.ptr, start)?,
             len: end - start,
             marker: PhantomData,
         })
@@ -607,7 +1061,7 @@ impl<'a, T> CudaViewMut<'a, T> {
     ///
     /// Returns `None` if `offset >= self.len`
     pub fn slice_mut<'b: 'a>(&'b mut self, range: impl RangeBounds<usize>) -> CudaViewMut<'a, T> {
-        self.try_slice_mut(range).unwrap()
+        self.try_slice_mut(range).expect(SLICE_PANIC)
     }
 
     /// Fallible version of [CudaViewMut::slice_mut]
@@ -615,8 +1069,9 @@ impl<'a, T> CudaViewMut<'a, T> {
         &'b mut self,
         range: impl RangeBounds<usize>,
     ) -> Option<CudaViewMut<'a, T>> {
-        range.bounds(..self.len()).map(|(start, end)| CudaViewMut {
-            ptr: self.ptr + (start * std::mem::size_of::<T>()) as u64,
+        let (start, end) = range.bounds(..self.len())?;
+        Some(CudaViewMut {
+            ptr: element_ptr::<T>(self.ptr, start)?,
             root: self.root,
             len: end - start,
             marker: PhantomData,
@@ -624,14 +1079,23 @@ impl<'a, T> CudaViewMut<'a, T> {
     }
 }
 
+const SLICE_PANIC: &str = "range is out of bounds, or its byte offset overflows";
+
+/// The address of element `index` of type `T` starting at `ptr`, or `None` if computing
+/// it overflows, so that a bogus length can't produce a wild pointer.
+fn element_ptr<T>(ptr: sys::CUdeviceptr, index: usize) -> Option<sys::CUdeviceptr> {
+    let offset = index.checked_mul(std::mem::size_of::<T>())?;
+    ptr.checked_add(offset.try_into().ok()?)
+}
+
 trait RangeHelper: RangeBounds<usize> {
-    fn inclusive_start(&self, valid_start: usize) -> usize;
-    fn exclusive_end(&self, valid_end: usize) -> usize;
+    fn inclusive_start(&self, valid_start: usize) -> Option<usize>;
+    fn exclusive_end(&self, valid_end: usize) -> Option<usize>;
     fn bounds(&self, valid: impl RangeHelper) -> Option<(usize, usize)> {
-        let vs = valid.inclusive_start(0);
-        let ve = valid.exclusive_end(usize::MAX);
-        let s = self.inclusive_start(vs);
-        let e = self.exclusive_end(ve);
+        let vs = valid.inclusive_start(0)?;
+        let ve = valid.exclusive_end(usize::MAX)?;
+        let s = self.inclusive_start(vs)?;
+        let e = self.exclusive_end(ve)?;
 
         let inside = s >= vs && e <= ve;
         let valid = s < e || (s == e && !matches!(self.end_bound(), Bound::Included(_)));
@@ -640,18 +1104,18 @@ trait RangeHelper: RangeBounds<usize> {
     }
 }
 impl<R: RangeBounds<usize>> RangeHelper for R {
-    fn inclusive_start(&self, valid_start: usize) -> usize {
+    fn inclusive_start(&self, valid_start: usize) -> Option<usize> {
         match self.start_bound() {
-            Bound::Included(n) => *n,
-            Bound::Excluded(n) => *n + 1,
-            Bound::Unbounded => valid_start,
+            Bound::Included(n) => Some(*n),
+            Bound::Excluded(n) => n.checked_add(1),
+            Bound::Unbounded => Some(valid_start),
         }
     }
-    fn exclusive_end(&self, valid_end: usize) -> usize {
+    fn exclusive_end(&self, valid_end: usize) -> Option<usize> {
         match self.end_bound() {
-            Bound::Included(n) => *n + 1,
-            Bound::Excluded(n) => *n,
-            Bound::Unbounded => valid_end,
+            Bound::Included(n) => n.checked_add(1),
+            Bound::Excluded(n) => Some(*n),
+            Bound::Unbounded => Some(valid_end),
         }
     }
 }
@@ -671,6 +1135,172 @@ mod tests {
         assert_eq!((2..2usize).bounds(0..usize::MAX), Some((2, 2)));
         assert_eq!((1..0usize).bounds(0..usize::MAX), None);
         assert_eq!((1..=0usize).bounds(0..usize::MAX), None);
+        assert_eq!((..=usize::MAX).bounds(0..usize::MAX), None);
+        assert_eq!(
+            (Bound::Excluded(usize::MAX), Bound::Unbounded).bounds(0..usize::MAX),
+            None
+        );
+    }
+
+    #[test]
+    fn test_slice_offset_overflow() {
+        assert_eq!(element_ptr::<[u64; 1024]>(0, usize::MAX / 1024), None);
+        assert_eq!(element_ptr::<u8>(u64::MAX, 1), None);
+        assert_eq!(element_ptr::<[u64; 1024]>(16, 2), Some(16 + 2 * 8192));
+
+        let dev = CudaDevice::new(0).unwrap();
+        let mut a = dev.alloc_zeros::<u8>(16).unwrap();
+        assert!(a.try_slice(..=usize::MAX).is_none());
+        assert!(a.try_slice_mut(usize::MAX..).is_none());
+        assert!(unsafe { a.transmute::<[u64; 1024]>(usize::MAX / 1024) }.is_none());
+        assert!(unsafe { a.transmute_mut::<[u64; 1024]>(usize::MAX / 1024) }.is_none());
+
+        let big = unsafe { dev.upgrade_device_ptr::<[u64; 1024]>(a.leak(), 1) };
+        assert!(big.try_slice(1..).is_some());
+        assert!(std::panic::catch_unwind(|| {
+            big.slice(2..);
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_flush_gpu_direct_rdma_writes() {
+        let dev = CudaDevice::new(0).unwrap();
+        let scope = sys::CUflushGPUDirectRDMAWritesScope::CU_FLUSH_GPU_DIRECT_RDMA_WRITES_TO_OWNER;
+        if dev.supports_flush_gpu_direct_rdma_writes().unwrap() {
+            dev.flush_gpu_direct_rdma_writes(scope).unwrap();
+        } else {
+            assert_eq!(
+                dev.flush_gpu_direct_rdma_writes(scope),
+                Err(result::DriverError(sys::CUresult::CUDA_ERROR_NOT_SUPPORTED))
+            );
+        }
+    }
+
+    #[test]
+    fn test_least_loaded() {
+        let n = CudaDevice::count().unwrap();
+        if n < 2 {
+            return;
+        }
+        let dev = CudaDevice::least_loaded().unwrap();
+        assert!(dev.ordinal() < n as usize);
+        let (free, _) = dev.mem_get_info().unwrap();
+
+        // devices before the chosen one must not have had more free memory,
+        // allowing some slack for other processes allocating in between
+        let slack = 64 << 20;
+        for ordinal in 0..dev.ordinal() {
+            let (other_free, _) = CudaDevice::new(ordinal).unwrap().mem_get_info().unwrap();
+            assert!(other_free <= free + slack);
+        }
+    }
+
+    #[test]
+    fn test_enable_all_peer_access() {
+        let n = CudaDevice::count().unwrap();
+        if n < 2 {
+            return;
+        }
+        let devs: Vec<_> = (0..n as usize)
+            .map(|i| CudaDevice::new(i).unwrap())
+            .collect();
+        CudaDevice::enable_all_peer_access(&devs).unwrap();
+        CudaDevice::enable_all_peer_access(&devs).unwrap();
+        for dev in devs.iter() {
+            for peer in devs.iter() {
+                if dev.ordinal() == peer.ordinal() || !dev.can_access_peer(peer).unwrap() {
+                    continue;
+                }
+                dev.bind_to_thread().unwrap();
+                assert_eq!(
+                    unsafe { result::ctx::enable_peer_access(peer.cu_primary_ctx) },
+                    Err(result::DriverError(
+                        sys::CUresult::CUDA_ERROR_PEER_ACCESS_ALREADY_ENABLED
+                    ))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_device_reset() {
+        let dev = CudaDevice::new(0).unwrap();
+        let a = dev.alloc_zeros::<f32>(100).unwrap();
+        let in_use = result::DriverError(sys::CUresult::CUDA_ERROR_CONTEXT_ALREADY_IN_USE);
+        // `a` holds a reference to the device, so nothing is reset
+        assert_eq!(unsafe { dev.clone().reset() }, Err(in_use));
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [0.0; 100]);
+
+        // actually resetting could race with other tests creating devices on
+        // other threads, which is exactly what the safety contract forbids
+    }
+
+    #[test]
+    fn test_resident_blocks_estimate() {
+        let dev = CudaDevice::new(0).unwrap();
+        let num_sms = dev
+            .attribute(sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_MULTIPROCESSOR_COUNT)
+            .unwrap() as u32;
+        let blocks = dev.resident_blocks_estimate(256).unwrap();
+        assert!(blocks > 0);
+        assert_eq!(blocks % num_sms, 0);
+        assert!(dev.resident_blocks_estimate(1024).unwrap() <= blocks);
+
+        let invalid = result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE);
+        assert_eq!(dev.resident_blocks_estimate(0), Err(invalid));
+        assert_eq!(dev.resident_blocks_estimate(1 << 20), Err(invalid));
+    }
+
+    #[test]
+    fn test_p2p_attribute() {
+        if CudaDevice::count().unwrap() < 2 {
+            return;
+        }
+        let dev0 = CudaDevice::new(0).unwrap();
+        let dev1 = CudaDevice::new(1).unwrap();
+        let rank = dev0
+            .p2p_attribute(
+                &dev1,
+                sys::CUdevice_P2PAttribute::CU_DEVICE_P2P_ATTRIBUTE_PERFORMANCE_RANK,
+            )
+            .unwrap();
+        assert!(rank >= 0);
+        let supported = dev0
+            .p2p_attribute(
+                &dev1,
+                sys::CUdevice_P2PAttribute::CU_DEVICE_P2P_ATTRIBUTE_ACCESS_SUPPORTED,
+            )
+            .unwrap();
+        assert_eq!(supported != 0, dev0.can_access_peer(&dev1).unwrap());
+    }
+
+    #[test]
+    fn test_new_with_ctx_flags() {
+        /// Restores the primary context flags even if the test panics, since they
+        /// are shared with every other test using this device.
+        struct RestoreFlags(sys::CUdevice, u32);
+        impl Drop for RestoreFlags {
+            fn drop(&mut self) {
+                unsafe { result::primary_ctx::set_flags(self.0, self.1) }.unwrap();
+            }
+        }
+
+        result::init().unwrap();
+        let cu_device = result::device::get(0).unwrap();
+        let (prev_flags, _) = unsafe { result::primary_ctx::get_state(cu_device) }.unwrap();
+        let _restore = RestoreFlags(cu_device, prev_flags);
+
+        let flags = sys::CUctx_flags::CU_CTX_SCHED_BLOCKING_SYNC as u32;
+        let dev = CudaDevice::new_with_ctx_flags(0, flags).unwrap();
+        let (ctx_flags, active) = unsafe { result::primary_ctx::get_state(dev.cu_device) }.unwrap();
+        assert!(active);
+        assert_eq!(
+            ctx_flags & sys::CUctx_flags::CU_CTX_SCHED_MASK as u32,
+            flags
+        );
+        let a = dev.alloc_zeros::<f32>(10).unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [0.0; 10]);
     }
 
     #[test]
@@ -682,4 +1312,251 @@ mod tests {
         assert!(unsafe { slice.transmute_mut::<f32>(25) }.is_some());
         assert!(unsafe { slice.transmute_mut::<f32>(26) }.is_none());
     }
+
+    #[test]
+    fn test_set_shared_mem_config() {
+        let ptx = crate::nvrtc::compile_ptx(
+            "extern \"C\" __global__ void kernel(double *out) { out[threadIdx.x] = 0.0; }",
+        )
+        .unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "tests", &["kernel"]).unwrap();
+        let f = dev.get_func("tests", "kernel").unwrap();
+        f.set_shared_mem_config(sys::CUsharedconfig::CU_SHARED_MEM_CONFIG_EIGHT_BYTE_BANK_SIZE)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_copy_stream_attributes() {
+        let dev = CudaDevice::new(0).unwrap();
+        let template = dev.fork_default_stream().unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+        let buf = dev.alloc_zeros::<u8>(1 << 20).unwrap();
+
+        let attr = sys::CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_ACCESS_POLICY_WINDOW;
+        let window = sys::CUaccessPolicyWindow {
+            base_ptr: buf.cu_device_ptr as *mut std::ffi::c_void,
+            num_bytes: buf.num_bytes(),
+            hitRatio: 0.5,
+            hitProp: sys::CUaccessProperty::CU_ACCESS_PROPERTY_PERSISTING,
+            missProp: sys::CUaccessProperty::CU_ACCESS_PROPERTY_STREAMING,
+        };
+        let mut value: sys::CUstreamAttrValue = unsafe { std::mem::zeroed() };
+        value.accessPolicyWindow = window;
+        unsafe { template.set_attribute(attr, &value) }.unwrap();
+
+        stream.copy_attributes_from(&template).unwrap();
+        let copied = stream.attribute(attr).unwrap();
+        assert_eq!(unsafe { copied.accessPolicyWindow }, window);
+    }
+
+    #[test]
+    fn test_stream_sync_policy() {
+        let dev = CudaDevice::new(0).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+        for policy in [
+            sys::CUsynchronizationPolicy::CU_SYNC_POLICY_SPIN,
+            sys::CUsynchronizationPolicy::CU_SYNC_POLICY_YIELD,
+            sys::CUsynchronizationPolicy::CU_SYNC_POLICY_BLOCKING_SYNC,
+            sys::CUsynchronizationPolicy::CU_SYNC_POLICY_AUTO,
+        ] {
+            stream.set_sync_policy(policy).unwrap();
+            assert_eq!(stream.sync_policy().unwrap(), policy);
+            stream.synchronize().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_null_stream() {
+        let dev = CudaDevice::new(0).unwrap();
+        let stream = dev.null_stream();
+        assert!(stream.stream.is_null());
+
+        let src = [1.0f32, 2.0, 3.0];
+        let a = unsafe { dev.alloc::<f32>(3) }.unwrap();
+        unsafe { result::memcpy_htod_async(a.cu_device_ptr, &src, stream.stream) }.unwrap();
+        dev.synchronize().unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), src);
+
+        let event = dev
+            .create_event(sys::CUevent_flags::CU_EVENT_DEFAULT)
+            .unwrap();
+        event.record_on_stream(&stream).unwrap();
+        event.synchronize().unwrap();
+        drop(stream);
+    }
+
+    #[test]
+    fn test_ecc_enabled() {
+        let dev = CudaDevice::new(0).unwrap();
+        let attr = dev
+            .attribute(sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_ECC_ENABLED)
+            .unwrap();
+        assert_eq!(dev.ecc_enabled().unwrap(), attr != 0);
+    }
+
+    #[test]
+    fn test_max_texture_1d_linear_width() {
+        let dev = CudaDevice::new(0).unwrap();
+        let width = dev
+            .max_texture_1d_linear_width(sys::CUarray_format::CU_AD_FORMAT_FLOAT, 1)
+            .unwrap();
+        assert!(width >= 1 << 20);
+    }
+
+    #[test]
+    #[cfg(not(feature = "tegra"))]
+    fn test_nvsci_sync_attributes_unsupported() {
+        // on Tegra, build with the `tegra` feature and pass a list from
+        // NvSciSyncAttrListCreate to fill it in
+        let dev = CudaDevice::new(0).unwrap();
+        let flags = (sys::CUDA_NVSCISYNC_ATTR_SIGNAL | sys::CUDA_NVSCISYNC_ATTR_WAIT) as i32;
+        assert_eq!(
+            unsafe { dev.nvsci_sync_attributes(std::ptr::null_mut(), flags) },
+            Err(result::DriverError(sys::CUresult::CUDA_ERROR_NOT_SUPPORTED))
+        );
+    }
+
+    #[test]
+    fn test_is_integrated() {
+        let dev = CudaDevice::new(0).unwrap();
+        let attr = dev
+            .attribute(sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_INTEGRATED)
+            .unwrap();
+        assert_eq!(dev.is_integrated().unwrap(), attr != 0);
+    }
+
+    #[test]
+    fn test_synchronize_all() {
+        let dev = CudaDevice::new(0).unwrap();
+        let mut bufs: Vec<CudaSlice<u8>> =
+            (0..4).map(|_| dev.alloc_zeros(1 << 20).unwrap()).collect();
+        let streams: Vec<CudaStream> = (0..4).map(|_| dev.fork_default_stream().unwrap()).collect();
+        for (i, (buf, stream)) in bufs.iter_mut().zip(streams.iter()).enumerate() {
+            unsafe { result::memset_d8_async(buf.cu_device_ptr, i as u8, buf.len, stream.stream) }
+                .unwrap();
+        }
+        let streams: Vec<&CudaStream> = streams.iter().collect();
+        CudaStream::synchronize_all(&streams).unwrap();
+        assert!(CudaStream::synchronize_all_collect(&streams)
+            .iter()
+            .all(|r| r.is_ok()));
+        for (i, buf) in bufs.iter().enumerate() {
+            assert!(dev
+                .dtoh_sync_copy(buf)
+                .unwrap()
+                .iter()
+                .all(|&x| x == i as u8));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "cuda-12030")]
+    fn test_function_name() {
+        let ptx = crate::nvrtc::compile_ptx(
+            "extern \"C\" __global__ void named_kernel(float *out) { out[threadIdx.x] = 0.0f; }",
+        )
+        .unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "tests", &["named_kernel"]).unwrap();
+        let f = dev.get_func("tests", "named_kernel").unwrap();
+        match f.name() {
+            Ok(name) => assert!(name.contains("named_kernel")),
+            Err(e) => assert_eq!(e, result::DriverError(sys::CUresult::CUDA_ERROR_NOT_FOUND)),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "cuda-12000")]
+    fn test_stream_ids_differ() {
+        let dev = CudaDevice::new(0).unwrap();
+        let a = dev.fork_default_stream().unwrap();
+        let b = dev.fork_default_stream().unwrap();
+        let id_a = match a.id() {
+            Ok(id) => id,
+            Err(e) => {
+                assert_eq!(e, result::DriverError(sys::CUresult::CUDA_ERROR_NOT_FOUND));
+                return;
+            }
+        };
+        assert_eq!(a.id().unwrap(), id_a);
+        assert_ne!(b.id().unwrap(), id_a);
+    }
+
+    #[test]
+    fn test_slice_set_len() {
+        let dev = CudaDevice::new(0).unwrap();
+        let mut a = dev
+            .htod_copy(std::vec![1.0f32, 2.0, 3.0, 4.0, 5.0])
+            .unwrap();
+        assert_eq!(a.capacity(), 5);
+
+        unsafe { a.set_len(3) }.unwrap();
+        assert_eq!(a.len(), 3);
+        assert_eq!(a.capacity(), 5);
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [1.0, 2.0, 3.0]);
+
+        assert_eq!(
+            unsafe { a.set_len(6) },
+            Err(result::DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE))
+        );
+        assert_eq!(a.len(), 3);
+
+        unsafe { a.set_len(5) }.unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [1.0, 2.0, 3.0, 4.0, 5.0]);
+    }
+
+    #[test]
+    fn test_borrowed_stream_async_copy() {
+        let dev = CudaDevice::new(0).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+
+        fn round_trip(
+            dev: &Arc<CudaDevice>,
+            stream: &CudaStream,
+            buf: &mut CudaSlice<f32>,
+        ) -> Vec<f32> {
+            let src = [1.0, 2.0, 3.0];
+            let mut dst = std::vec![0.0; 3];
+            unsafe {
+                dev.htod_copy_into_with_event(&src, buf, stream).unwrap();
+                result::memcpy_dtoh_async(&mut dst, buf.cu_device_ptr, stream.stream).unwrap();
+            }
+            stream.synchronize().unwrap();
+            dst
+        }
+
+        let mut a = dev.alloc_zeros::<f32>(3).unwrap();
+        dev.synchronize().unwrap();
+        assert_eq!(round_trip(&dev, &stream, &mut a), [1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_sync_if_busy() {
+        use crate::driver::{LaunchAsync, LaunchConfig};
+
+        let ptx = crate::nvrtc::compile_ptx(
+            "extern \"C\" __global__ void spin(long long cycles) {
+    long long start = clock64();
+    while (clock64() - start < cycles) {}
+}",
+        )
+        .unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "sync_if_busy", &["spin"]).unwrap();
+        let f = dev.get_func("sync_if_busy", "spin").unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+        dev.synchronize().unwrap();
+        stream.synchronize().unwrap();
+        assert!(!stream.sync_if_busy().unwrap());
+
+        // spins for about a second, so the stream is still busy when it is queried
+        let clock_khz = dev
+            .attribute(sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_CLOCK_RATE)
+            .unwrap() as i64;
+        let cfg = LaunchConfig::for_num_elems(1);
+        unsafe { f.launch_on_stream(&stream, cfg, (clock_khz * 1000,)) }.unwrap();
+        assert!(stream.sync_if_busy().unwrap());
+        assert!(!stream.sync_if_busy().unwrap());
+    }
 }
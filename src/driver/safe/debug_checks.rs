@@ -0,0 +1,108 @@
+use crate::driver::{result, sys};
+
+#[cfg(feature = "no-std")]
+use spin::RwLock;
+#[cfg(not(feature = "no-std"))]
+use std::sync::RwLock;
+
+/// Receives the warnings emitted by the `debug-checks` feature. See [set_warn_hook()].
+pub type WarnHook = fn(&str);
+
+static WARN_HOOK: RwLock<Option<WarnHook>> = RwLock::new(None);
+
+/// Replaces the function that warnings from the `debug-checks` feature are passed to.
+///
+/// By default warnings are printed to stderr (and dropped with the `no-std` feature).
+/// The hook is called on the thread that triggered the warning.
+pub fn set_warn_hook(hook: WarnHook) {
+    let lock = WARN_HOOK.write();
+    #[cfg(not(feature = "no-std"))]
+    let mut lock = lock.unwrap();
+    #[cfg(feature = "no-std")]
+    let mut lock = lock;
+    *lock = Some(hook);
+}
+
+pub(crate) fn warn(msg: &str) {
+    let hook = WARN_HOOK.read();
+    #[cfg(not(feature = "no-std"))]
+    let hook = hook.unwrap();
+    match *hook {
+        Some(hook) => hook(msg),
+        #[cfg(not(feature = "no-std"))]
+        None => std::eprintln!("cudarc: {}", msg),
+        #[cfg(feature = "no-std")]
+        None => {}
+    }
+}
+
+/// Warns if `host` is pageable memory, since async copies from or to it are
+/// silently synchronous with respect to the host. `op` names the calling method.
+///
+/// Must be called with the device bound to the calling thread.
+pub(crate) fn check_page_locked<T>(host: &[T], op: &str) {
+    if host.is_empty() {
+        return;
+    }
+    let ptr = host.as_ptr() as sys::CUdeviceptr;
+    match unsafe { result::pointer_get_memory_type(ptr) } {
+        Ok(sys::CUmemorytype::CU_MEMORYTYPE_HOST | sys::CUmemorytype::CU_MEMORYTYPE_UNIFIED) => {}
+        _ => warn(&std::format!(
+            "{} was given pageable host memory at {:p}, so the copy will not overlap with the host. Use page-locked memory (e.g. CudaDevice::alloc_pinned) for asynchronous copies.",
+            op,
+            host.as_ptr()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::CudaDevice;
+    use std::{cell::RefCell, string::String, vec::Vec};
+
+    std::thread_local! {
+        static WARNINGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record(msg: &str) {
+        WARNINGS.with(|w| w.borrow_mut().push(msg.into()));
+    }
+
+    fn take_warnings() -> Vec<String> {
+        WARNINGS.with(|w| std::mem::take(&mut *w.borrow_mut()))
+    }
+
+    #[test]
+    fn test_check_page_locked_htod_copy_into_with_event() {
+        set_warn_hook(record);
+        let dev = CudaDevice::new(0).unwrap();
+        let mut dst = dev.alloc_zeros::<f32>(256).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+
+        let pageable = std::vec![1.0f32; 256];
+        let event = unsafe { dev.htod_copy_into_with_event(&pageable, &mut dst, &stream) }.unwrap();
+        event.synchronize().unwrap();
+        let warnings = take_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("pageable"));
+
+        let pinned = dev.alloc_pinned_zeros::<f32>(256).unwrap();
+        let event =
+            unsafe { dev.htod_copy_into_with_event(pinned.as_slice().unwrap(), &mut dst, &stream) }
+                .unwrap();
+        event.synchronize().unwrap();
+        assert!(take_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_no_page_locked_warning_for_htod_copy() {
+        set_warn_hook(record);
+        let dev = CudaDevice::new(0).unwrap();
+        // the owned Vec can't be page-locked, so there is nothing to warn about
+        let a = dev.htod_copy(std::vec![1.0f32; 256]).unwrap();
+        assert!(take_warnings().is_empty());
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [1.0; 256]);
+        assert!(take_warnings().is_empty());
+    }
+}
@@ -31,6 +31,23 @@ impl<'a, T> DeviceSlice<T> for CudaViewMut<'a, T> {
 }
 
 /// Abstraction over [CudaSlice]/[CudaView]
+///
+/// Only device memory implements this, while host memory is passed to copies as plain
+/// slices, so the direction of a copy is checked at compile time:
+///
+/// ```no_run
+/// # use cudarc::driver::CudaDevice;
+/// let dev = CudaDevice::new(0).unwrap();
+/// let mut host = vec![0.0f32; 10];
+/// let mut dev_buf = dev.alloc_zeros::<f32>(10).unwrap();
+/// dev.htod_sync_copy_into(&host, &mut dev_buf).unwrap();
+/// dev.dtoh_sync_copy_into(&dev_buf, &mut host).unwrap();
+/// ```
+///
+/// Swapping the arguments, e.g. passing device memory as the source of
+/// [super::CudaDevice::htod_sync_copy_into()] or host memory as the source of
+/// [super::CudaDevice::dtoh_sync_copy_into()], does not compile. This is checked by
+/// the trybuild tests in `tests/ui`.
 pub trait DevicePtr<T>: DeviceSlice<T> {
     fn device_ptr(&self) -> &sys::CUdeviceptr;
 }
@@ -0,0 +1,248 @@
+use crate::driver::{result, sys, DriverError};
+
+use super::core::{unwrap_on_drop, CudaDevice, CudaStream};
+
+use std::{sync::Arc, vec::Vec};
+
+/// A wrapper around [sys::CUevent], used to mark a point in a stream's work
+/// that can be waited on or timed.
+///
+/// Create with [CudaDevice::create_event()].
+#[derive(Debug)]
+pub struct CudaEvent {
+    pub(crate) cu_event: sys::CUevent,
+    pub(crate) device: Arc<CudaDevice>,
+}
+
+unsafe impl Send for CudaEvent {}
+unsafe impl Sync for CudaEvent {}
+
+impl Drop for CudaEvent {
+    fn drop(&mut self) {
+        unwrap_on_drop(self.device.bind_to_thread());
+        unwrap_on_drop(unsafe { result::event::destroy(self.cu_event) });
+    }
+}
+
+impl CudaDevice {
+    /// Creates a new [CudaEvent]. Pass `CU_EVENT_DISABLE_TIMING` if the event
+    /// will not be used with [CudaEvent::elapsed_ms()], since it is cheaper to record.
+    pub fn create_event(
+        self: &Arc<Self>,
+        flags: sys::CUevent_flags,
+    ) -> Result<CudaEvent, DriverError> {
+        self.bind_to_thread()?;
+        let cu_event = result::event::create(flags)?;
+        Ok(CudaEvent {
+            cu_event,
+            device: self.clone(),
+        })
+    }
+}
+
+impl CudaEvent {
+    /// Get the underlying [sys::CUevent].
+    ///
+    /// # Safety
+    /// While this function is marked as safe, actually using the
+    /// returned object is unsafe.
+    ///
+    /// **You must not destroy the event**, as it is still
+    /// owned by the [CudaEvent].
+    pub fn cu_event(&self) -> &sys::CUevent {
+        &self.cu_event
+    }
+
+    /// Records the current work of the device's work stream into this event.
+    pub fn record(&self) -> Result<(), DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe { result::event::record(self.cu_event, self.device.stream) }
+    }
+
+    /// Records the current work of `stream` into this event.
+    pub fn record_on_stream(&self, stream: &CudaStream) -> Result<(), DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe { result::event::record(self.cu_event, stream.stream) }
+    }
+
+    /// Records the current work of `stream` into this event with `flags`.
+    ///
+    /// `CU_EVENT_RECORD_DEFAULT` is the same as [CudaEvent::record_on_stream()].
+    /// `CU_EVENT_RECORD_EXTERNAL` is needed when recording inside of a stream
+    /// capture for an event that will be waited on outside of the graph (e.g. by
+    /// graphics API work). This requires CUDA 11.1+.
+    pub fn record_with_flags(
+        &self,
+        stream: &CudaStream,
+        flags: sys::CUevent_record_flags,
+    ) -> Result<(), DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe { result::event::record_with_flags(self.cu_event, stream.stream, flags) }
+    }
+
+    /// Whether all the work recorded into this event has completed.
+    /// An event that was never recorded is complete.
+    pub fn is_complete(&self) -> Result<bool, DriverError> {
+        self.device.bind_to_thread()?;
+        match unsafe { result::event::query(self.cu_event) } {
+            Ok(()) => Ok(true),
+            Err(DriverError(sys::CUresult::CUDA_ERROR_NOT_READY)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Blocks the calling thread until all the work recorded into this event has completed.
+    pub fn synchronize(&self) -> Result<(), DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe { result::event::synchronize(self.cu_event) }
+    }
+
+    /// The elapsed time in milliseconds between `self` and `end` being completed.
+    ///
+    /// Both events must have been created without `CU_EVENT_DISABLE_TIMING`, and
+    /// must have already completed.
+    pub fn elapsed_ms(&self, end: &Self) -> Result<f32, DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe { result::event::elapsed(self.cu_event, end.cu_event) }
+    }
+}
+
+impl CudaStream {
+    /// Makes all future work queued on this stream wait for the work recorded
+    /// into `event` to complete. **This is asynchronous with respect to the host.**
+    pub fn wait_event(&self, event: &CudaEvent) -> Result<(), DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe {
+            result::stream::wait_event(
+                self.stream,
+                event.cu_event,
+                sys::CUevent_wait_flags::CU_EVENT_WAIT_DEFAULT,
+            )
+        }
+    }
+}
+
+/// A join point across streams: the work queued so far on every producer stream
+/// is recorded into its own [CudaEvent], and the consumer stream waits on all of them.
+///
+/// Work queued on the consumer after [StreamBarrier::new()] runs after all of the
+/// producers' prior work. The events are kept so the host can also wait on the
+/// producers with [StreamBarrier::synchronize()].
+///
+/// Example:
+/// ```ignore
+/// let barrier = StreamBarrier::new(&[&a, &b, &c], &consumer)?;
+/// f.launch_on_stream(&consumer, cfg, (&x, &y, &z))?;
+/// ```
+#[derive(Debug)]
+pub struct StreamBarrier {
+    events: Vec<CudaEvent>,
+}
+
+impl StreamBarrier {
+    /// Records an event on each of `producers` and makes `consumer` wait on every one.
+    pub fn new(producers: &[&CudaStream], consumer: &CudaStream) -> Result<Self, DriverError> {
+        let mut events = Vec::with_capacity(producers.len());
+        for producer in producers.iter() {
+            let event = producer
+                .device
+                .create_event(sys::CUevent_flags::CU_EVENT_DISABLE_TIMING)?;
+            event.record_on_stream(producer)?;
+            consumer.wait_event(&event)?;
+            events.push(event);
+        }
+        Ok(Self { events })
+    }
+
+    /// The recorded events, in the same order as the producers.
+    pub fn events(&self) -> &[CudaEvent] {
+        &self.events
+    }
+
+    /// Takes ownership of the recorded events.
+    pub fn into_events(self) -> Vec<CudaEvent> {
+        self.events
+    }
+
+    /// Blocks the calling thread until all of the producers' recorded work has completed.
+    pub fn synchronize(&self) -> Result<(), DriverError> {
+        for event in self.events.iter() {
+            event.synchronize()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_with_default_flags() {
+        let dev = CudaDevice::new(0).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+        let start = dev
+            .create_event(sys::CUevent_flags::CU_EVENT_DEFAULT)
+            .unwrap();
+        let end = dev
+            .create_event(sys::CUevent_flags::CU_EVENT_DEFAULT)
+            .unwrap();
+
+        start
+            .record_with_flags(&stream, sys::CUevent_record_flags::CU_EVENT_RECORD_DEFAULT)
+            .unwrap();
+        end.record_with_flags(&stream, sys::CUevent_record_flags::CU_EVENT_RECORD_DEFAULT)
+            .unwrap();
+        end.synchronize().unwrap();
+
+        assert!(start.is_complete().unwrap());
+        assert!(end.is_complete().unwrap());
+        assert!(start.elapsed_ms(&end).unwrap() >= 0.0);
+    }
+
+    #[test]
+    fn test_stream_barrier() {
+        use crate::driver::{LaunchAsync, LaunchConfig};
+
+        let ptx = crate::nvrtc::compile_ptx(
+            "extern \"C\" __global__ void sum3(const unsigned char *a, const unsigned char *b, const unsigned char *c, unsigned char *out, size_t n) {
+    size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+    if (i < n) { out[i] = a[i] + b[i] + c[i]; }
+}",
+        )
+        .unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "barrier", &["sum3"]).unwrap();
+
+        const N: usize = 1 << 20;
+        let producers = [
+            dev.fork_default_stream().unwrap(),
+            dev.fork_default_stream().unwrap(),
+            dev.fork_default_stream().unwrap(),
+        ];
+        let consumer = dev.fork_default_stream().unwrap();
+        let mut bufs = [
+            dev.alloc_zeros::<u8>(N).unwrap(),
+            dev.alloc_zeros::<u8>(N).unwrap(),
+            dev.alloc_zeros::<u8>(N).unwrap(),
+        ];
+        let mut out = dev.alloc_zeros::<u8>(N).unwrap();
+        dev.synchronize().unwrap();
+
+        for (i, (buf, stream)) in bufs.iter_mut().zip(producers.iter()).enumerate() {
+            unsafe { buf.memset_range_async(.., 1 << i, stream) }.unwrap();
+        }
+        let barrier =
+            StreamBarrier::new(&[&producers[0], &producers[1], &producers[2]], &consumer).unwrap();
+        assert_eq!(barrier.events().len(), 3);
+
+        let f = dev.get_func("barrier", "sum3").unwrap();
+        let cfg = LaunchConfig::for_num_elems(N as u32);
+        unsafe { f.launch_on_stream(&consumer, cfg, (&bufs[0], &bufs[1], &bufs[2], &mut out, N)) }
+            .unwrap();
+
+        barrier.synchronize().unwrap();
+        consumer.synchronize().unwrap();
+        assert!(dev.dtoh_sync_copy(&out).unwrap().iter().all(|&x| x == 7));
+    }
+}
@@ -9,6 +9,15 @@ use crate::driver::{result, sys, DriverError};
 impl CudaDevice {
     /// Import external memory from a [`File`].
     ///
+    /// The file must wrap a handle exported by a graphics API, e.g. with `vkGetMemoryFdKHR` or
+    /// `vkGetMemoryWin32HandleKHR` in Vulkan:
+    /// - On unix this is an opaque file descriptor (`CU_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_FD`).
+    ///   Ownership of the descriptor moves to the driver if the import succeeds.
+    /// - On windows this is an opaque NT handle (`CU_EXTERNAL_MEMORY_HANDLE_TYPE_OPAQUE_WIN32`).
+    ///   The handle stays owned by the returned [ExternalMemory] and is closed on drop.
+    ///
+    /// Other kinds of files are rejected by the driver, and the file is closed.
+    ///
     /// # Safety
     /// `size` must be the size of the external memory in bytes.
     #[cfg(any(unix, windows))]
@@ -138,3 +147,17 @@ impl DevicePtr<u8> for MappedBuffer {
         &self.device_ptr
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_import_external_memory_invalid_fd() {
+        let dev = CudaDevice::new(0).unwrap();
+        let file = File::open("/dev/null").unwrap();
+        let res = unsafe { dev.import_external_memory(file, 1 << 20) };
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use core::mem::ManuallyDrop;
+use std::fs::File;
+use std::sync::Arc;
+
+use super::core::unwrap_on_drop;
+use super::{CudaDevice, CudaStream};
+use crate::driver::{result, sys, DriverError};
+
+impl CudaDevice {
+    /// Import an external semaphore from a [`File`], e.g. to order work on a
+    /// [CudaStream] with work submitted to Vulkan or DirectX.
+    ///
+    /// The file must wrap a semaphore handle exported by a graphics API, e.g. with
+    /// `vkGetSemaphoreFdKHR` or `vkGetSemaphoreWin32HandleKHR` in Vulkan, and `handle_type`
+    /// must match how it was exported:
+    /// - On unix the handle is a file descriptor, `CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD`
+    ///   for binary semaphores or `CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_TIMELINE_SEMAPHORE_FD` for
+    ///   timeline semaphores. Ownership of the descriptor moves to the driver if the import succeeds.
+    /// - On windows the handle is an NT handle, e.g. `CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32`,
+    ///   `CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_TIMELINE_SEMAPHORE_WIN32` or
+    ///   `CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_D3D12_FENCE`. The handle stays owned by the returned
+    ///   [ExternalSemaphore] and is closed on drop.
+    ///
+    /// Other kinds of files are rejected by the driver, and the file is closed.
+    ///
+    /// # Safety
+    /// `handle_type` must be the type of semaphore `file` was exported as.
+    #[cfg(any(unix, windows))]
+    pub unsafe fn import_external_semaphore(
+        self: &Arc<Self>,
+        file: File,
+        handle_type: sys::CUexternalSemaphoreHandleType,
+    ) -> Result<ExternalSemaphore, DriverError> {
+        self.bind_to_thread()?;
+
+        #[cfg(unix)]
+        let external_semaphore = unsafe {
+            use std::os::fd::AsRawFd;
+            result::external_semaphore::import_external_semaphore_fd(file.as_raw_fd(), handle_type)
+        }?;
+        #[cfg(windows)]
+        let external_semaphore = unsafe {
+            use std::os::windows::io::AsRawHandle;
+            result::external_semaphore::import_external_semaphore_win32(
+                file.as_raw_handle(),
+                handle_type,
+            )
+        }?;
+        Ok(ExternalSemaphore {
+            external_semaphore,
+            device: self.clone(),
+            _file: ManuallyDrop::new(file),
+        })
+    }
+}
+
+/// An abstraction for an imported external semaphore.
+///
+/// This struct can be created via [`CudaDevice::import_external_semaphore`].
+/// The imported semaphore will be destroyed when this struct is dropped.
+#[derive(Debug)]
+pub struct ExternalSemaphore {
+    external_semaphore: sys::CUexternalSemaphore,
+    device: Arc<CudaDevice>,
+    _file: ManuallyDrop<File>,
+}
+
+unsafe impl Send for ExternalSemaphore {}
+unsafe impl Sync for ExternalSemaphore {}
+
+impl Drop for ExternalSemaphore {
+    fn drop(&mut self) {
+        unwrap_on_drop(self.device.bind_to_thread());
+
+        unwrap_on_drop(unsafe {
+            result::external_semaphore::destroy_external_semaphore(self.external_semaphore)
+        });
+
+        // See the Drop impl of ExternalMemory, the driver only takes ownership
+        // of the handle on unix.
+        #[cfg(windows)]
+        unsafe {
+            ManuallyDrop::<File>::drop(&mut self._file)
+        };
+    }
+}
+
+impl ExternalSemaphore {
+    /// Get the underlying [sys::CUexternalSemaphore], e.g. for signaling
+    /// keyed mutexes with [result::external_semaphore::signal_external_semaphore_async()].
+    ///
+    /// # Safety
+    /// While this function is marked as safe, actually using the
+    /// returned object is unsafe.
+    ///
+    /// **You must not destroy the semaphore**, as it is still
+    /// owned by the [ExternalSemaphore].
+    pub fn cu_external_semaphore(&self) -> &sys::CUexternalSemaphore {
+        &self.external_semaphore
+    }
+
+    /// Queues a signal of the semaphore on `stream`, after all work already queued on it.
+    ///
+    /// Timeline semaphores and fences are set to `value`, binary semaphores ignore it.
+    ///
+    /// # Safety
+    /// The semaphore is destroyed when this is dropped, which the driver requires to
+    /// happen only once no signals or waits on it are outstanding. So the signal must
+    /// complete (e.g. through [CudaStream::synchronize()]) before this is dropped.
+    pub unsafe fn signal(&self, value: u64, stream: &CudaStream) -> Result<(), DriverError> {
+        let mut params = sys::CUDA_EXTERNAL_SEMAPHORE_SIGNAL_PARAMS::default();
+        params.params.fence.value = value;
+        self.device.bind_to_thread()?;
+        result::external_semaphore::signal_external_semaphore_async(
+            self.external_semaphore,
+            &params,
+            stream.stream,
+        )
+    }
+
+    /// Queues a wait on the semaphore on `stream`, so work queued on it afterwards
+    /// doesn't start until the semaphore is signaled.
+    ///
+    /// For timeline semaphores and fences this waits until the semaphore reaches `value`,
+    /// binary semaphores ignore it.
+    ///
+    /// # Safety
+    /// Like [ExternalSemaphore::signal()], the wait must complete before this is dropped.
+    pub unsafe fn wait(&self, value: u64, stream: &CudaStream) -> Result<(), DriverError> {
+        let mut params = sys::CUDA_EXTERNAL_SEMAPHORE_WAIT_PARAMS::default();
+        params.params.fence.value = value;
+        self.device.bind_to_thread()?;
+        result::external_semaphore::wait_external_semaphore_async(
+            self.external_semaphore,
+            &params,
+            stream.stream,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_import_external_semaphore_invalid_fd() {
+        let dev = CudaDevice::new(0).unwrap();
+        for handle_type in [
+            sys::CUexternalSemaphoreHandleType::CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD,
+            sys::CUexternalSemaphoreHandleType::CU_EXTERNAL_SEMAPHORE_HANDLE_TYPE_TIMELINE_SEMAPHORE_FD,
+        ] {
+            let file = File::open("/dev/null").unwrap();
+            let res = unsafe { dev.import_external_semaphore(file, handle_type) };
+            assert!(res.is_err());
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::driver::{sys, DriverError};
+
+use super::alloc::{DeviceRepr, ValidAsZeroBits};
+use super::core::{CudaDevice, CudaSlice};
+use super::device_ptr::{DevicePtr, DevicePtrMut, DeviceSlice};
+
+use std::{mem::MaybeUninit, sync::Arc};
+
+/// A [CudaSlice] whose length `N` is known at compile time, so that copies
+/// to & from the host can use stack allocated arrays `[T; N]`.
+///
+/// Create with [CudaDevice::alloc_zeros_fixed()] or [CudaDevice::htod_sync_copy_fixed()].
+///
+/// The length is enforced when copying:
+/// ```compile_fail
+/// # use cudarc::driver::*;
+/// let dev = CudaDevice::new(0).unwrap();
+/// let mut a = dev.alloc_zeros_fixed::<f32, 8>().unwrap();
+/// a.copy_from(&[0.0f32; 4]).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct CudaFixedSlice<T, const N: usize> {
+    pub(crate) slice: CudaSlice<T>,
+}
+
+impl CudaDevice {
+    /// Allocates a [CudaFixedSlice] of `N` elements and memsets it to all 0s.
+    pub fn alloc_zeros_fixed<T: ValidAsZeroBits + DeviceRepr, const N: usize>(
+        self: &Arc<Self>,
+    ) -> Result<CudaFixedSlice<T, N>, DriverError> {
+        Ok(CudaFixedSlice {
+            slice: self.alloc_zeros(N)?,
+        })
+    }
+
+    /// Allocates a [CudaFixedSlice] and synchronously copies `src` into it.
+    pub fn htod_sync_copy_fixed<T: DeviceRepr, const N: usize>(
+        self: &Arc<Self>,
+        src: &[T; N],
+    ) -> Result<CudaFixedSlice<T, N>, DriverError> {
+        Ok(CudaFixedSlice {
+            slice: self.htod_sync_copy(src)?,
+        })
+    }
+}
+
+impl<T: DeviceRepr, const N: usize> CudaFixedSlice<T, N> {
+    /// Synchronously copies `src` into this slice.
+    pub fn copy_from(&mut self, src: &[T; N]) -> Result<(), DriverError> {
+        let device = self.slice.device.clone();
+        device.htod_sync_copy_into(src, &mut self.slice)
+    }
+
+    /// Synchronously copies this slice into a new host array.
+    pub fn copy_to(&self) -> Result<[T; N], DriverError> {
+        let mut dst = MaybeUninit::<[T; N]>::uninit();
+        let host = unsafe { std::slice::from_raw_parts_mut(dst.as_mut_ptr() as *mut T, N) };
+        self.slice.device.dtoh_sync_copy_into(&self.slice, host)?;
+        Ok(unsafe { dst.assume_init() })
+    }
+}
+
+impl<T, const N: usize> CudaFixedSlice<T, N> {
+    /// Get a clone of the underlying [CudaDevice].
+    pub fn device(&self) -> Arc<CudaDevice> {
+        self.slice.device()
+    }
+
+    /// The underlying [CudaSlice], which has length `N`.
+    pub fn as_slice(&self) -> &CudaSlice<T> {
+        &self.slice
+    }
+
+    /// The underlying [CudaSlice], which has length `N`.
+    pub fn as_slice_mut(&mut self) -> &mut CudaSlice<T> {
+        &mut self.slice
+    }
+
+    /// Converts into the underlying [CudaSlice].
+    pub fn into_slice(self) -> CudaSlice<T> {
+        self.slice
+    }
+}
+
+impl<T, const N: usize> DeviceSlice<T> for CudaFixedSlice<T, N> {
+    fn len(&self) -> usize {
+        N
+    }
+}
+
+impl<T, const N: usize> DevicePtr<T> for CudaFixedSlice<T, N> {
+    fn device_ptr(&self) -> &sys::CUdeviceptr {
+        &self.slice.cu_device_ptr
+    }
+}
+
+impl<T, const N: usize> DevicePtrMut<T> for CudaFixedSlice<T, N> {
+    fn device_ptr_mut(&mut self) -> &mut sys::CUdeviceptr {
+        &mut self.slice.cu_device_ptr
+    }
+}
+
+unsafe impl<T: DeviceRepr, const N: usize> DeviceRepr for &CudaFixedSlice<T, N> {
+    #[inline(always)]
+    fn as_kernel_param(&self) -> *mut std::ffi::c_void {
+        (&self.slice.cu_device_ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
+    }
+}
+
+unsafe impl<T: DeviceRepr, const N: usize> DeviceRepr for &mut CudaFixedSlice<T, N> {
+    #[inline(always)]
+    fn as_kernel_param(&self) -> *mut std::ffi::c_void {
+        (&self.slice.cu_device_ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_round_trip() {
+        let dev = CudaDevice::new(0).unwrap();
+        let src = [1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0];
+        let a = dev.htod_sync_copy_fixed(&src).unwrap();
+        assert_eq!(a.len(), 8);
+        assert_eq!(a.copy_to().unwrap(), src);
+
+        let mut b = dev.alloc_zeros_fixed::<f32, 8>().unwrap();
+        assert_eq!(b.copy_to().unwrap(), [0.0; 8]);
+        b.copy_from(&src).unwrap();
+        assert_eq!(b.copy_to().unwrap(), src);
+    }
+}
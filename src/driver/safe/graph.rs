@@ -0,0 +1,878 @@
+use crate::driver::{result, sys, DriverError};
+
+use super::core::{unwrap_on_drop, CudaDevice, CudaStream};
+use super::event::CudaEvent;
+use super::launch::LaunchConfig;
+
+use std::{marker::PhantomData, sync::Arc, vec::Vec};
+
+/// A wrapper around [sys::CUgraph], the work captured from a [CudaStream]
+/// between [CudaStream::begin_capture()] and [CudaStream::end_capture()].
+///
+/// Use [CudaGraph::instantiate()] to create a [CudaGraphExec] that can be launched.
+#[derive(Debug)]
+pub struct CudaGraph {
+    pub(crate) cu_graph: sys::CUgraph,
+    pub(crate) device: Arc<CudaDevice>,
+}
+
+unsafe impl Send for CudaGraph {}
+unsafe impl Sync for CudaGraph {}
+
+impl Drop for CudaGraph {
+    fn drop(&mut self) {
+        unwrap_on_drop(self.device.bind_to_thread());
+        unwrap_on_drop(unsafe { result::graph::destroy(self.cu_graph) });
+    }
+}
+
+/// A wrapper around [sys::CUgraphExec], an executable [CudaGraph].
+///
+/// Create with [CudaGraph::instantiate()].
+#[derive(Debug)]
+pub struct CudaGraphExec {
+    pub(crate) cu_graph_exec: sys::CUgraphExec,
+    pub(crate) device: Arc<CudaDevice>,
+}
+
+unsafe impl Send for CudaGraphExec {}
+unsafe impl Sync for CudaGraphExec {}
+
+impl Drop for CudaGraphExec {
+    fn drop(&mut self) {
+        unwrap_on_drop(self.device.bind_to_thread());
+        unwrap_on_drop(unsafe { result::graph::exec_destroy(self.cu_graph_exec) });
+    }
+}
+
+/// Sets the calling thread's stream capture mode, and restores the previous mode on drop.
+///
+/// The mode is thread local: it decides which potentially unsafe calls (see
+/// [CudaStream::capture_status()]) *this thread* may make while any stream is capturing
+/// in `CU_STREAM_CAPTURE_MODE_GLOBAL`, or while one of this thread's captures is in
+/// `CU_STREAM_CAPTURE_MODE_THREAD_LOCAL`. `CU_STREAM_CAPTURE_MODE_RELAXED` allows them all.
+///
+/// ```ignore
+/// let _guard = CaptureModeGuard::new(sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_RELAXED)?;
+/// // ... calls that would otherwise invalidate another thread's capture ...
+/// ```
+///
+/// Since the mode belongs to the thread that created the guard, the guard is not [Send],
+/// so it can't restore the mode on another thread:
+/// ```compile_fail
+/// # use cudarc::driver::{sys, CaptureModeGuard};
+/// let mode = sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_RELAXED;
+/// let guard = CaptureModeGuard::new(mode).unwrap();
+/// std::thread::spawn(move || drop(guard));
+/// ```
+#[derive(Debug)]
+pub struct CaptureModeGuard {
+    pub(crate) previous: sys::CUstreamCaptureMode,
+    marker: PhantomData<*const ()>,
+}
+
+impl CaptureModeGuard {
+    /// Sets the calling thread's capture mode to `mode`.
+    pub fn new(mode: sys::CUstreamCaptureMode) -> Result<Self, DriverError> {
+        let previous = result::stream::exchange_capture_mode(mode)?;
+        Ok(Self {
+            previous,
+            marker: PhantomData,
+        })
+    }
+
+    /// The mode that will be restored on drop.
+    pub fn previous(&self) -> sys::CUstreamCaptureMode {
+        self.previous
+    }
+}
+
+impl Drop for CaptureModeGuard {
+    fn drop(&mut self) {
+        unwrap_on_drop(result::stream::exchange_capture_mode(self.previous).map(|_| ()));
+    }
+}
+
+impl CudaDevice {
+    /// Frees the memory cached for graph memory nodes (stream ordered allocations
+    /// made while capturing) back to the OS, except memory backing live allocations.
+    ///
+    /// This only applies to graph memory. Memory cached by the pool that
+    /// [CudaDevice::alloc()] uses outside of graphs is trimmed with [CudaDevice::trim_mem_pool()].
+    pub fn graph_mem_trim(&self) -> Result<(), DriverError> {
+        self.bind_to_thread()?;
+        unsafe { result::device::graph_mem_trim(self.cu_device) }
+    }
+
+    /// Gets the number of bytes of graph memory described by `attr`, e.g.
+    /// `CU_GRAPH_MEM_ATTR_RESERVED_MEM_CURRENT` for how much is currently reserved
+    /// from the OS, or `CU_GRAPH_MEM_ATTR_USED_MEM_HIGH` for the high watermark of live allocations.
+    pub fn graph_mem_attribute(&self, attr: sys::CUgraphMem_attribute) -> Result<u64, DriverError> {
+        unsafe { result::device::get_graph_mem_attribute(self.cu_device, attr) }
+    }
+
+    /// Resets a high watermark (`CU_GRAPH_MEM_ATTR_USED_MEM_HIGH` or
+    /// `CU_GRAPH_MEM_ATTR_RESERVED_MEM_HIGH`) to its current value.
+    pub fn reset_graph_mem_high_watermark(
+        &self,
+        attr: sys::CUgraphMem_attribute,
+    ) -> Result<(), DriverError> {
+        unsafe { result::device::set_graph_mem_attribute(self.cu_device, attr, 0) }
+    }
+}
+
+impl CudaDevice {
+    /// Creates an empty [CudaGraph], to add nodes to directly instead of capturing
+    /// them from a [CudaStream], e.g. with [CudaGraph::add_event_record_node()].
+    pub fn create_graph(self: &Arc<Self>) -> Result<CudaGraph, DriverError> {
+        self.bind_to_thread()?;
+        let cu_graph = result::graph::create()?;
+        Ok(CudaGraph {
+            cu_graph,
+            device: self.clone(),
+        })
+    }
+}
+
+impl CudaStream {
+    /// Whether this stream is currently capturing work into a graph.
+    ///
+    /// While a stream is capturing (`CU_STREAM_CAPTURE_STATUS_ACTIVE`), work queued onto it
+    /// is recorded instead of executed, and the following are illegal:
+    /// - synchronizing with the stream, e.g. [CudaDevice::synchronize()] or any `*_sync_*` copy
+    /// - querying or synchronizing with events recorded during the capture
+    /// - queueing work on the legacy null stream, which is always the device's default
+    ///   work stream, e.g. with [CudaDevice::htod_copy()]
+    /// - with `CU_STREAM_CAPTURE_MODE_GLOBAL`, potentially unsafe calls such as
+    ///   allocating or freeing with non stream-ordered functions, from any thread
+    ///
+    /// Doing any of these moves the capture to `CU_STREAM_CAPTURE_STATUS_INVALIDATED`,
+    /// and [CudaStream::end_capture()] will return an error.
+    pub fn capture_status(&self) -> Result<sys::CUstreamCaptureStatus, DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe { result::stream::is_capturing(self.stream) }
+    }
+
+    /// Begins capturing the work queued onto this stream into a graph. See
+    /// [CudaStream::capture_status()] for what is illegal during a capture.
+    pub fn begin_capture(&self, mode: sys::CUstreamCaptureMode) -> Result<(), DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe { result::stream::begin_capture(self.stream, mode) }
+    }
+
+    /// Like [CudaStream::begin_capture()], but the work is captured into the existing `graph`
+    /// instead of a new one, with the first captured work depending on `dependencies`. This
+    /// allows building up a graph incrementally.
+    ///
+    /// `graph` is moved into the capture and is returned by [CudaStream::end_capture()].
+    ///
+    /// Returns `CUDA_ERROR_NOT_FOUND` on drivers older than CUDA 12.3.
+    ///
+    /// # Safety
+    /// `dependencies` must be nodes of `graph`, e.g. from [CudaGraph::kernel_nodes()].
+    #[cfg(feature = "cuda-12030")]
+    pub unsafe fn begin_capture_to_graph(
+        &self,
+        graph: CudaGraph,
+        dependencies: &[sys::CUgraphNode],
+        mode: sys::CUstreamCaptureMode,
+    ) -> Result<(), DriverError> {
+        self.device.bind_to_thread()?;
+        result::stream::begin_capture_to_graph(self.stream, graph.cu_graph, dependencies, mode)?;
+        std::mem::forget(graph);
+        Ok(())
+    }
+
+    /// If this stream is capturing, returns the id of the capture and the nodes in the
+    /// graph being captured that the next work queued on this stream will depend on.
+    /// Returns `None` if the stream is not capturing.
+    ///
+    /// The id is unique among all captures in the process, so it can be used to tell
+    /// whether two streams are part of the same capture.
+    pub fn capture_dependencies(
+        &self,
+    ) -> Result<Option<(u64, Vec<sys::CUgraphNode>)>, DriverError> {
+        self.device.bind_to_thread()?;
+        let (status, id, deps) = unsafe { result::stream::get_capture_info(self.stream) }?;
+        Ok(match status {
+            sys::CUstreamCaptureStatus::CU_STREAM_CAPTURE_STATUS_NONE => None,
+            _ => Some((id, deps)),
+        })
+    }
+
+    /// Changes the nodes that the next work queued on this stream will depend on during a
+    /// capture. `CU_STREAM_ADD_CAPTURE_DEPENDENCIES` adds `nodes` to the current
+    /// dependencies, and `CU_STREAM_SET_CAPTURE_DEPENDENCIES` replaces them.
+    ///
+    /// # Safety
+    /// `nodes` must be nodes of the graph this stream is capturing into,
+    /// e.g. from [CudaStream::capture_dependencies()] of another stream in the same capture.
+    pub unsafe fn update_capture_dependencies(
+        &self,
+        nodes: &mut [sys::CUgraphNode],
+        flags: sys::CUstreamUpdateCaptureDependencies_flags,
+    ) -> Result<(), DriverError> {
+        self.device.bind_to_thread()?;
+        result::stream::update_capture_dependencies(self.stream, nodes, flags)
+    }
+
+    /// Ends the capture started with [CudaStream::begin_capture()], returning the captured work.
+    pub fn end_capture(&self) -> Result<CudaGraph, DriverError> {
+        self.device.bind_to_thread()?;
+        let cu_graph = unsafe { result::stream::end_capture(self.stream) }?;
+        Ok(CudaGraph {
+            cu_graph,
+            device: self.device.clone(),
+        })
+    }
+}
+
+impl CudaGraph {
+    /// Get the underlying [sys::CUgraph].
+    ///
+    /// # Safety
+    /// While this function is marked as safe, actually using the
+    /// returned object is unsafe.
+    ///
+    /// **You must not destroy the graph**, as it is still
+    /// owned by the [CudaGraph].
+    pub fn cu_graph(&self) -> &sys::CUgraph {
+        &self.cu_graph
+    }
+
+    /// Deep copies this graph, so the copy can be modified without affecting this one.
+    ///
+    /// Note that node handles from this graph do not refer to the nodes of the copy.
+    pub fn try_clone(&self) -> Result<CudaGraph, DriverError> {
+        self.device.bind_to_thread()?;
+        let cu_graph = unsafe { result::graph::clone(self.cu_graph) }?;
+        Ok(CudaGraph {
+            cu_graph,
+            device: self.device.clone(),
+        })
+    }
+
+    /// All of the nodes of this graph, in no particular order.
+    pub fn nodes(&self) -> Result<Vec<sys::CUgraphNode>, DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe { result::graph::get_nodes(self.cu_graph) }
+    }
+
+    /// All of the dependency edges of this graph as `(from, to)` pairs,
+    /// meaning `to` runs after `from` has completed.
+    pub fn edges(&self) -> Result<Vec<(sys::CUgraphNode, sys::CUgraphNode)>, DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe { result::graph::get_edges(self.cu_graph) }
+    }
+
+    /// The type of `node`, e.g. `CU_GRAPH_NODE_TYPE_KERNEL` or `CU_GRAPH_NODE_TYPE_MEMSET`.
+    ///
+    /// # Safety
+    /// `node` must be a node of this graph, e.g. from [CudaGraph::nodes()].
+    pub unsafe fn node_type(
+        &self,
+        node: sys::CUgraphNode,
+    ) -> Result<sys::CUgraphNodeType, DriverError> {
+        self.device.bind_to_thread()?;
+        result::graph::node_get_type(node)
+    }
+
+    /// The kernel nodes of this graph, e.g. for updating their parameters with
+    /// [CudaGraphExec::set_kernel_node_params()].
+    pub fn kernel_nodes(&self) -> Result<Vec<sys::CUgraphNode>, DriverError> {
+        let mut kernel_nodes = Vec::new();
+        for node in self.nodes()? {
+            if unsafe { result::graph::node_get_type(node) }?
+                == sys::CUgraphNodeType::CU_GRAPH_NODE_TYPE_KERNEL
+            {
+                kernel_nodes.push(node);
+            }
+        }
+        Ok(kernel_nodes)
+    }
+
+    /// Adds a node that records `event` once all of `dependencies` (nodes of this graph)
+    /// have completed, and returns it. With [CudaGraph::add_event_wait_node()] this orders
+    /// work across branches of the graph, or with work outside of it, without an edge.
+    ///
+    /// # Safety
+    /// - `dependencies` must be nodes of this graph.
+    /// - `event` is not owned by the graph, so it must outlive this graph and every
+    ///   [CudaGraphExec] instantiated from it.
+    pub unsafe fn add_event_record_node(
+        &mut self,
+        dependencies: &[sys::CUgraphNode],
+        event: &CudaEvent,
+    ) -> Result<sys::CUgraphNode, DriverError> {
+        self.device.bind_to_thread()?;
+        result::graph::add_event_record_node(self.cu_graph, dependencies, event.cu_event)
+    }
+
+    /// Adds a node that waits for the work recorded into `event` once all of `dependencies`
+    /// (nodes of this graph) have completed, and returns it. Nodes that depend on it run after
+    /// the recorded work, e.g. from a node added with [CudaGraph::add_event_record_node()].
+    ///
+    /// # Safety
+    /// - `dependencies` must be nodes of this graph.
+    /// - `event` is not owned by the graph, so it must outlive this graph and every
+    ///   [CudaGraphExec] instantiated from it.
+    pub unsafe fn add_event_wait_node(
+        &mut self,
+        dependencies: &[sys::CUgraphNode],
+        event: &CudaEvent,
+    ) -> Result<sys::CUgraphNode, DriverError> {
+        self.device.bind_to_thread()?;
+        result::graph::add_event_wait_node(self.cu_graph, dependencies, event.cu_event)
+    }
+
+    /// Gets the value of `attr` for the kernel node `node`, e.g.
+    /// `CU_LAUNCH_ATTRIBUTE_PRIORITY` or `CU_LAUNCH_ATTRIBUTE_CLUSTER_DIMENSION`.
+    /// Only the union member corresponding to `attr` is meaningful.
+    ///
+    /// Attributes only take effect in executables instantiated after they are set.
+    ///
+    /// # Safety
+    /// `node` must be a kernel node of this graph, e.g. from [CudaGraph::kernel_nodes()].
+    pub unsafe fn kernel_node_attribute(
+        &self,
+        node: sys::CUgraphNode,
+        attr: sys::CUkernelNodeAttrID,
+    ) -> Result<sys::CUkernelNodeAttrValue, DriverError> {
+        self.device.bind_to_thread()?;
+        result::graph::kernel_node_get_attribute(node, attr)
+    }
+
+    /// Sets the value of `attr` for the kernel node `node`, see [CudaGraph::kernel_node_attribute()].
+    ///
+    /// # Safety
+    /// - `node` must be a kernel node of this graph, e.g. from [CudaGraph::kernel_nodes()].
+    /// - `value` must have the union member corresponding to `attr` set.
+    pub unsafe fn set_kernel_node_attribute(
+        &self,
+        node: sys::CUgraphNode,
+        attr: sys::CUkernelNodeAttrID,
+        value: &sys::CUkernelNodeAttrValue,
+    ) -> Result<(), DriverError> {
+        self.device.bind_to_thread()?;
+        result::graph::kernel_node_set_attribute(node, attr, value)
+    }
+
+    /// Copies all the attributes of the kernel node `from` onto the kernel node `to`.
+    /// `from` may belong to another graph.
+    ///
+    /// # Safety
+    /// `from` and `to` must be kernel nodes of live graphs, and `to` must belong to this graph.
+    pub unsafe fn copy_kernel_node_attributes(
+        &self,
+        from: sys::CUgraphNode,
+        to: sys::CUgraphNode,
+    ) -> Result<(), DriverError> {
+        self.device.bind_to_thread()?;
+        result::graph::kernel_node_copy_attributes(to, from)
+    }
+
+    /// Creates an executable version of this graph.
+    pub fn instantiate(&self) -> Result<CudaGraphExec, DriverError> {
+        self.instantiate_with_flags(&[])
+    }
+
+    /// Creates an executable version of this graph, with all of `flags` set:
+    /// - [sys::CUgraphInstantiate_flags::CUDA_GRAPH_INSTANTIATE_FLAG_AUTO_FREE_ON_LAUNCH]:
+    ///   allocations made by the graph's memory alloc nodes that were not freed by the graph
+    ///   are freed before it is launched again. Without this, launching a graph whose
+    ///   allocations are still live fails, so set it for graphs that allocate scratch memory
+    ///   without a matching free node.
+    /// - [sys::CUgraphInstantiate_flags::CUDA_GRAPH_INSTANTIATE_FLAG_USE_NODE_PRIORITY]:
+    ///   kernel nodes run with the priority set in their launch attributes (see
+    ///   [CudaGraph::set_kernel_node_attribute()]) instead of the priority of the
+    ///   stream the graph is launched on.
+    ///
+    /// The upload and device launch flags need CUDA 12, so they are not available here;
+    /// use [CudaGraphExec::upload()] to upload ahead of the first launch instead.
+    pub fn instantiate_with_flags(
+        &self,
+        flags: &[sys::CUgraphInstantiate_flags],
+    ) -> Result<CudaGraphExec, DriverError> {
+        let flags = flags.iter().fold(0, |acc, &flag| acc | flag as u64);
+        self.device.bind_to_thread()?;
+        let cu_graph_exec = unsafe { result::graph::instantiate(self.cu_graph, flags) }?;
+        Ok(CudaGraphExec {
+            cu_graph_exec,
+            device: self.device.clone(),
+        })
+    }
+}
+
+impl CudaGraphExec {
+    /// Get the underlying [sys::CUgraphExec].
+    ///
+    /// # Safety
+    /// While this function is marked as safe, actually using the
+    /// returned object is unsafe.
+    ///
+    /// **You must not destroy the executable graph**, as it is still
+    /// owned by the [CudaGraphExec].
+    pub fn cu_graph_exec(&self) -> &sys::CUgraphExec {
+        &self.cu_graph_exec
+    }
+
+    /// Uploads this graph to the device on `stream` without running it, so the
+    /// next [CudaGraphExec::launch()] doesn't have to, which makes it cheaper.
+    pub fn upload(&self, stream: &CudaStream) -> Result<(), DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe { result::graph::upload(self.cu_graph_exec, stream.stream) }
+    }
+
+    /// Replaces the launch configuration and arguments of the kernel node `node` in this
+    /// executable graph, which is cheaper than instantiating the graph again. The graph
+    /// this was instantiated from is unchanged, and the kernel itself can't be changed.
+    ///
+    /// `params` are copied before this returns, so they only need to live for this call.
+    ///
+    /// # Safety
+    /// - `node` must be a kernel node of the graph this was instantiated from,
+    ///   e.g. from [CudaGraph::kernel_nodes()].
+    /// - `params` must be valid arguments for the node's kernel, see [crate::driver::LaunchAsync].
+    pub unsafe fn set_kernel_node_params(
+        &self,
+        node: sys::CUgraphNode,
+        cfg: LaunchConfig,
+        params: &mut [*mut std::ffi::c_void],
+    ) -> Result<(), DriverError> {
+        self.device.bind_to_thread()?;
+        let mut node_params = result::graph::kernel_node_get_params(node)?;
+        (
+            node_params.gridDimX,
+            node_params.gridDimY,
+            node_params.gridDimZ,
+        ) = cfg.grid_dim;
+        (
+            node_params.blockDimX,
+            node_params.blockDimY,
+            node_params.blockDimZ,
+        ) = cfg.block_dim;
+        node_params.sharedMemBytes = cfg.shared_mem_bytes;
+        node_params.kernelParams = params.as_mut_ptr();
+        node_params.extra = std::ptr::null_mut();
+        result::graph::exec_kernel_node_set_params(self.cu_graph_exec, node, &node_params)
+    }
+
+    /// Launches all the work in this graph on `stream`.
+    pub fn launch(&self, stream: &CudaStream) -> Result<(), DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe { result::graph::launch(self.cu_graph_exec, stream.stream) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::{DeviceRepr, LaunchAsync};
+
+    #[test]
+    fn test_capture_status() {
+        let dev = CudaDevice::new(0).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+        assert_eq!(
+            stream.capture_status().unwrap(),
+            sys::CUstreamCaptureStatus::CU_STREAM_CAPTURE_STATUS_NONE
+        );
+
+        stream
+            .begin_capture(sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_THREAD_LOCAL)
+            .unwrap();
+        assert_eq!(
+            stream.capture_status().unwrap(),
+            sys::CUstreamCaptureStatus::CU_STREAM_CAPTURE_STATUS_ACTIVE
+        );
+        let graph = stream.end_capture().unwrap();
+        assert_eq!(
+            stream.capture_status().unwrap(),
+            sys::CUstreamCaptureStatus::CU_STREAM_CAPTURE_STATUS_NONE
+        );
+
+        let exec = graph.instantiate().unwrap();
+        exec.launch(&stream).unwrap();
+    }
+
+    #[test]
+    fn test_graph_try_clone() {
+        let dev = CudaDevice::new(0).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+        let mut a = dev.alloc_zeros::<u8>(16).unwrap();
+
+        stream
+            .begin_capture(sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_THREAD_LOCAL)
+            .unwrap();
+        unsafe { result::memset_d8_async(a.cu_device_ptr, 1, a.len, stream.stream) }.unwrap();
+        let graph = stream.end_capture().unwrap();
+        let clone = graph.try_clone().unwrap();
+
+        clone.instantiate().unwrap().launch(&stream).unwrap();
+        dev.wait_for(&stream).unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [1; 16]);
+
+        dev.memset_zeros(&mut a).unwrap();
+        drop(clone);
+        stream.wait_for_default().unwrap();
+        graph.instantiate().unwrap().launch(&stream).unwrap();
+        dev.wait_for(&stream).unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [1; 16]);
+    }
+
+    #[test]
+    fn test_graph_nodes_and_edges() {
+        use sys::CUgraphNodeType::*;
+        let dev = CudaDevice::new(0).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+        let a = dev.alloc_zeros::<u8>(16).unwrap();
+        let b = dev.alloc_zeros::<u8>(16).unwrap();
+
+        stream
+            .begin_capture(sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_THREAD_LOCAL)
+            .unwrap();
+        unsafe {
+            result::memset_d8_async(a.cu_device_ptr, 1, a.len, stream.stream).unwrap();
+            result::memcpy_dtod_async(b.cu_device_ptr, a.cu_device_ptr, a.len, stream.stream)
+                .unwrap();
+        }
+        let graph = stream.end_capture().unwrap();
+
+        let nodes = graph.nodes().unwrap();
+        assert_eq!(nodes.len(), 2);
+        let types: Vec<_> = nodes
+            .iter()
+            .map(|&node| unsafe { graph.node_type(node) }.unwrap())
+            .collect();
+        assert!(types.contains(&CU_GRAPH_NODE_TYPE_MEMSET));
+        assert!(types.contains(&CU_GRAPH_NODE_TYPE_MEMCPY));
+        assert!(graph.kernel_nodes().unwrap().is_empty());
+
+        let edges = graph.edges().unwrap();
+        assert_eq!(edges.len(), 1);
+        let (from, to) = edges[0];
+        assert_eq!(
+            unsafe { graph.node_type(from) }.unwrap(),
+            CU_GRAPH_NODE_TYPE_MEMSET
+        );
+        assert_eq!(
+            unsafe { graph.node_type(to) }.unwrap(),
+            CU_GRAPH_NODE_TYPE_MEMCPY
+        );
+    }
+
+    #[test]
+    fn test_capture_dependencies() {
+        let dev = CudaDevice::new(0).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+        let a = dev.alloc_zeros::<u8>(16).unwrap();
+        assert!(stream.capture_dependencies().unwrap().is_none());
+
+        stream
+            .begin_capture(sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_THREAD_LOCAL)
+            .unwrap();
+        let (id, deps) = stream.capture_dependencies().unwrap().unwrap();
+        assert_ne!(id, 0);
+        assert!(deps.is_empty());
+
+        unsafe { result::memset_d8_async(a.cu_device_ptr, 1, a.len, stream.stream) }.unwrap();
+        let (same_id, mut deps) = stream.capture_dependencies().unwrap().unwrap();
+        assert_eq!(same_id, id);
+        assert_eq!(deps.len(), 1);
+
+        unsafe {
+            stream.update_capture_dependencies(
+                &mut deps,
+                sys::CUstreamUpdateCaptureDependencies_flags::CU_STREAM_SET_CAPTURE_DEPENDENCIES,
+            )
+        }
+        .unwrap();
+        assert_eq!(stream.capture_dependencies().unwrap().unwrap().1, deps);
+        stream.end_capture().unwrap();
+    }
+
+    #[test]
+    fn test_capture_mode_guard() {
+        let dev = CudaDevice::new(0).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+        stream
+            .begin_capture(sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_GLOBAL)
+            .unwrap();
+        {
+            let guard =
+                CaptureModeGuard::new(sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_RELAXED)
+                    .unwrap();
+            assert_eq!(
+                guard.previous(),
+                sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_GLOBAL
+            );
+            let inner =
+                CaptureModeGuard::new(sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_RELAXED)
+                    .unwrap();
+            assert_eq!(
+                inner.previous(),
+                sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_RELAXED
+            );
+        }
+        let mode = result::stream::exchange_capture_mode(
+            sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_GLOBAL,
+        )
+        .unwrap();
+        assert_eq!(
+            mode,
+            sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_GLOBAL
+        );
+        stream.end_capture().unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "cuda-12030")]
+    fn test_begin_capture_to_graph() {
+        let dev = CudaDevice::new(0).unwrap();
+        let a = dev.alloc_zeros::<u8>(16).unwrap();
+        let b = dev.alloc_zeros::<u8>(16).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+        let mode = sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_THREAD_LOCAL;
+
+        stream.begin_capture(mode).unwrap();
+        unsafe { result::memset_d8_async(a.cu_device_ptr, 1, a.len, stream.stream) }.unwrap();
+        let graph = stream.end_capture().unwrap();
+        let nodes = unsafe { result::graph::get_nodes(graph.cu_graph) }.unwrap();
+        assert_eq!(nodes.len(), 1);
+
+        match unsafe { stream.begin_capture_to_graph(graph, &nodes, mode) } {
+            Ok(()) => {}
+            Err(e) => {
+                assert_eq!(e, DriverError(sys::CUresult::CUDA_ERROR_NOT_FOUND));
+                return;
+            }
+        }
+        unsafe { result::memset_d8_async(b.cu_device_ptr, 2, b.len, stream.stream) }.unwrap();
+        let graph = stream.end_capture().unwrap();
+        let nodes = unsafe { result::graph::get_nodes(graph.cu_graph) }.unwrap();
+        assert_eq!(nodes.len(), 2);
+
+        graph.instantiate().unwrap().launch(&stream).unwrap();
+        dev.wait_for(&stream).unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [1; 16]);
+        assert_eq!(dev.dtoh_sync_copy(&b).unwrap(), [2; 16]);
+    }
+
+    #[test]
+    fn test_graph_upload() {
+        let dev = CudaDevice::new(0).unwrap();
+        let a = dev.alloc_zeros::<u8>(16).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+
+        stream
+            .begin_capture(sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_THREAD_LOCAL)
+            .unwrap();
+        unsafe { result::memset_d8_async(a.cu_device_ptr, 7, a.len, stream.stream) }.unwrap();
+        let exec = stream.end_capture().unwrap().instantiate().unwrap();
+
+        exec.upload(&stream).unwrap();
+        unsafe { result::stream::synchronize(stream.stream) }.unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [0; 16]);
+
+        exec.launch(&stream).unwrap();
+        dev.wait_for(&stream).unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [7; 16]);
+    }
+
+    #[test]
+    fn test_graph_mem_trim() {
+        let dev = CudaDevice::new(0).unwrap();
+        if !dev.is_async {
+            return;
+        }
+        let stream = dev.fork_default_stream().unwrap();
+        let reserved = sys::CUgraphMem_attribute::CU_GRAPH_MEM_ATTR_RESERVED_MEM_CURRENT;
+
+        stream
+            .begin_capture(sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_THREAD_LOCAL)
+            .unwrap();
+        unsafe {
+            let ptr = result::malloc_async(stream.stream, 1 << 24).unwrap();
+            result::memset_d8_async(ptr, 0, 1 << 24, stream.stream).unwrap();
+            result::free_async(ptr, stream.stream).unwrap();
+        }
+        let exec = stream.end_capture().unwrap().instantiate().unwrap();
+        exec.launch(&stream).unwrap();
+        unsafe { result::stream::synchronize(stream.stream) }.unwrap();
+        assert!(dev.graph_mem_attribute(reserved).unwrap() > 0);
+
+        drop(exec);
+        dev.graph_mem_trim().unwrap();
+        assert_eq!(dev.graph_mem_attribute(reserved).unwrap(), 0);
+        dev.reset_graph_mem_high_watermark(
+            sys::CUgraphMem_attribute::CU_GRAPH_MEM_ATTR_RESERVED_MEM_HIGH,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_instantiate_auto_free_on_launch() {
+        let dev = CudaDevice::new(0).unwrap();
+        if !dev.is_async {
+            return;
+        }
+        let stream = dev.fork_default_stream().unwrap();
+
+        // the allocation has no free node, so it is still live after a launch
+        stream
+            .begin_capture(sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_THREAD_LOCAL)
+            .unwrap();
+        let ptr = unsafe {
+            let ptr = result::malloc_async(stream.stream, 1 << 20).unwrap();
+            result::memset_d8_async(ptr, 0, 1 << 20, stream.stream).unwrap();
+            ptr
+        };
+        let graph = stream.end_capture().unwrap();
+
+        let exec = graph.instantiate().unwrap();
+        exec.launch(&stream).unwrap();
+        stream.synchronize().unwrap();
+        assert!(exec.launch(&stream).is_err());
+        unsafe { result::free_async(ptr, stream.stream) }.unwrap();
+        stream.synchronize().unwrap();
+        drop(exec);
+
+        // each launch frees the allocation of the one before it
+        let exec = graph
+            .instantiate_with_flags(&[
+                sys::CUgraphInstantiate_flags::CUDA_GRAPH_INSTANTIATE_FLAG_AUTO_FREE_ON_LAUNCH,
+            ])
+            .unwrap();
+        for _ in 0..10 {
+            exec.launch(&stream).unwrap();
+        }
+        unsafe { result::free_async(ptr, stream.stream) }.unwrap();
+        stream.synchronize().unwrap();
+    }
+
+    #[test]
+    fn test_set_kernel_node_params() {
+        let ptx = crate::nvrtc::compile_ptx(
+            "extern \"C\" __global__ void fill(float *out, float value) {
+    out[blockIdx.x * blockDim.x + threadIdx.x] = value;
+}",
+        )
+        .unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "graph_params", &["fill"]).unwrap();
+        let mut a = dev.alloc_zeros::<f32>(64).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+
+        stream
+            .begin_capture(sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_THREAD_LOCAL)
+            .unwrap();
+        let f = dev.get_func("graph_params", "fill").unwrap();
+        let cfg = LaunchConfig::for_num_elems(32);
+        unsafe { f.launch_on_stream(&stream, cfg, (&mut a, 1.0f32)) }.unwrap();
+        let graph = stream.end_capture().unwrap();
+        let nodes = graph.kernel_nodes().unwrap();
+        assert_eq!(nodes.len(), 1);
+
+        let exec = graph.instantiate().unwrap();
+        exec.launch(&stream).unwrap();
+        dev.wait_for(&stream).unwrap();
+        let out = dev.dtoh_sync_copy(&a).unwrap();
+        assert_eq!(out[..32], [1.0; 32]);
+        assert_eq!(out[32..], [0.0; 32]);
+
+        let cfg = LaunchConfig::for_num_elems(64);
+        let value = 2.0f32;
+        let mut params = [(&a).as_kernel_param(), value.as_kernel_param()];
+        unsafe { exec.set_kernel_node_params(nodes[0], cfg, &mut params) }.unwrap();
+        stream.wait_for_default().unwrap();
+        exec.launch(&stream).unwrap();
+        dev.wait_for(&stream).unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [2.0; 64]);
+    }
+
+    #[test]
+    fn test_kernel_node_attributes() {
+        let ptx = crate::nvrtc::compile_ptx(
+            "extern \"C\" __global__ void fill(float *out, float value) {
+    out[blockIdx.x * blockDim.x + threadIdx.x] = value;
+}",
+        )
+        .unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "graph_attrs", &["fill"]).unwrap();
+        let mut a = dev.alloc_zeros::<f32>(32).unwrap();
+        let mut b = dev.alloc_zeros::<f32>(32).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+
+        stream
+            .begin_capture(sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_THREAD_LOCAL)
+            .unwrap();
+        let cfg = LaunchConfig::for_num_elems(32);
+        let f = dev.get_func("graph_attrs", "fill").unwrap();
+        unsafe { f.launch_on_stream(&stream, cfg, (&mut a, 1.0f32)) }.unwrap();
+        let f = dev.get_func("graph_attrs", "fill").unwrap();
+        unsafe { f.launch_on_stream(&stream, cfg, (&mut b, 2.0f32)) }.unwrap();
+        let graph = stream.end_capture().unwrap();
+        let nodes = graph.kernel_nodes().unwrap();
+        assert_eq!(nodes.len(), 2);
+
+        let priority = sys::CUkernelNodeAttrID::CU_LAUNCH_ATTRIBUTE_PRIORITY;
+        let mut value: sys::CUkernelNodeAttrValue = unsafe { std::mem::zeroed() };
+        value.priority = -1;
+        unsafe { graph.set_kernel_node_attribute(nodes[0], priority, &value) }.unwrap();
+        let value = unsafe { graph.kernel_node_attribute(nodes[0], priority) }.unwrap();
+        assert_eq!(unsafe { value.priority }, -1);
+
+        unsafe { graph.copy_kernel_node_attributes(nodes[0], nodes[1]) }.unwrap();
+        let value = unsafe { graph.kernel_node_attribute(nodes[1], priority) }.unwrap();
+        assert_eq!(unsafe { value.priority }, -1);
+
+        graph.instantiate().unwrap().launch(&stream).unwrap();
+        dev.wait_for(&stream).unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [1.0; 32]);
+        assert_eq!(dev.dtoh_sync_copy(&b).unwrap(), [2.0; 32]);
+    }
+
+    #[test]
+    fn test_event_record_and_wait_nodes() {
+        let dev = CudaDevice::new(0).unwrap();
+        let a = dev.alloc_zeros::<u8>(16).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+        let event = dev
+            .create_event(sys::CUevent_flags::CU_EVENT_DISABLE_TIMING)
+            .unwrap();
+
+        stream
+            .begin_capture(sys::CUstreamCaptureMode::CU_STREAM_CAPTURE_MODE_THREAD_LOCAL)
+            .unwrap();
+        unsafe { result::memset_d8_async(a.cu_device_ptr, 3, a.len, stream.stream) }.unwrap();
+        let mut graph = stream.end_capture().unwrap();
+        let memset = graph.nodes().unwrap();
+        assert_eq!(memset.len(), 1);
+
+        let record = unsafe { graph.add_event_record_node(&memset, &event) }.unwrap();
+        let wait = unsafe { graph.add_event_wait_node(&[record], &event) }.unwrap();
+        assert_eq!(
+            unsafe { graph.node_type(record) }.unwrap(),
+            sys::CUgraphNodeType::CU_GRAPH_NODE_TYPE_EVENT_RECORD
+        );
+        assert_eq!(
+            unsafe { graph.node_type(wait) }.unwrap(),
+            sys::CUgraphNodeType::CU_GRAPH_NODE_TYPE_WAIT_EVENT
+        );
+        assert_eq!(graph.edges().unwrap().len(), 2);
+
+        let exec = graph.instantiate().unwrap();
+        exec.launch(&stream).unwrap();
+        exec.launch(&stream).unwrap();
+        dev.wait_for(&stream).unwrap();
+        event.synchronize().unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&a).unwrap(), [3; 16]);
+
+        let mut empty = dev.create_graph().unwrap();
+        let record = unsafe { empty.add_event_record_node(&[], &event) }.unwrap();
+        unsafe { empty.add_event_wait_node(&[record], &event) }.unwrap();
+        empty.instantiate().unwrap().launch(&stream).unwrap();
+        stream.synchronize().unwrap();
+    }
+}
@@ -0,0 +1,275 @@
+use crate::driver::{result, sys, DriverError};
+
+use super::alloc::DeviceRepr;
+use super::core::{unwrap_on_drop, CudaDevice, CudaSlice};
+use super::device_ptr::{DevicePtr, DevicePtrMut, DeviceSlice};
+use super::event::CudaEvent;
+
+use std::{marker::PhantomData, sync::Arc};
+
+/// A handle to device memory that another process can open with
+/// [CudaDevice::open_ipc_mem_handle()], created with [CudaSlice::ipc_mem_handle()].
+///
+/// This is plain bytes, so it can be sent to the other process in any way, and
+/// with the `serde` feature it implements `Serialize` and `Deserialize`.
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IpcMemHandle(#[cfg_attr(feature = "serde", serde(with = "bytes64"))] pub [u8; 64]);
+
+/// A handle to an event that another process can open with
+/// [CudaDevice::open_ipc_event_handle()], created with [CudaEvent::ipc_handle()].
+///
+/// This is plain bytes, so it can be sent to the other process in any way, and
+/// with the `serde` feature it implements `Serialize` and `Deserialize`.
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IpcEventHandle(#[cfg_attr(feature = "serde", serde(with = "bytes64"))] pub [u8; 64]);
+
+impl IpcMemHandle {
+    /// The raw bytes, e.g. to write to a socket.
+    pub fn to_bytes(self) -> [u8; 64] {
+        self.0
+    }
+
+    /// Reconstructs the handle from [IpcMemHandle::to_bytes()].
+    pub fn from_bytes(bytes: [u8; 64]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl IpcEventHandle {
+    /// The raw bytes, e.g. to write to a socket.
+    pub fn to_bytes(self) -> [u8; 64] {
+        self.0
+    }
+
+    /// Reconstructs the handle from [IpcEventHandle::to_bytes()].
+    pub fn from_bytes(bytes: [u8; 64]) -> Self {
+        Self(bytes)
+    }
+}
+
+/// (De)serializes `[u8; 64]` as a fixed size tuple, since serde only implements
+/// arrays of up to 32 elements. With bincode this is exactly the 64 bytes.
+#[cfg(feature = "serde")]
+pub(crate) mod bytes64 {
+    use serde::{de, ser::SerializeTuple, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8; 64], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(64)?;
+        for b in bytes {
+            tuple.serialize_element(b)?;
+        }
+        tuple.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 64], D::Error> {
+        struct Visitor;
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = [u8; 64];
+            fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                f.write_str("64 bytes")
+            }
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<[u8; 64], A::Error> {
+                let mut bytes = [0; 64];
+                for (i, b) in bytes.iter_mut().enumerate() {
+                    *b = seq
+                        .next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+                }
+                Ok(bytes)
+            }
+        }
+        deserializer.deserialize_tuple(64, Visitor)
+    }
+}
+
+/// Device memory of another process, opened with [CudaDevice::open_ipc_mem_handle()].
+///
+/// It is unmapped from this process on drop. The memory itself stays owned by the
+/// process that allocated it.
+#[derive(Debug)]
+pub struct CudaIpcSlice<T> {
+    pub(crate) cu_device_ptr: sys::CUdeviceptr,
+    pub(crate) len: usize,
+    pub(crate) device: Arc<CudaDevice>,
+    marker: PhantomData<*const T>,
+}
+
+unsafe impl<T: Send> Send for CudaIpcSlice<T> {}
+unsafe impl<T: Sync> Sync for CudaIpcSlice<T> {}
+
+impl<T> Drop for CudaIpcSlice<T> {
+    fn drop(&mut self) {
+        unwrap_on_drop(self.device.bind_to_thread());
+        unwrap_on_drop(unsafe { result::ipc::close_mem_handle(self.cu_device_ptr) });
+    }
+}
+
+impl<T> CudaIpcSlice<T> {
+    /// Get a clone of the underlying [CudaDevice].
+    pub fn device(&self) -> Arc<CudaDevice> {
+        self.device.clone()
+    }
+}
+
+impl<T> DeviceSlice<T> for CudaIpcSlice<T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<T> DevicePtr<T> for CudaIpcSlice<T> {
+    fn device_ptr(&self) -> &sys::CUdeviceptr {
+        &self.cu_device_ptr
+    }
+}
+
+impl<T> DevicePtrMut<T> for CudaIpcSlice<T> {
+    fn device_ptr_mut(&mut self) -> &mut sys::CUdeviceptr {
+        &mut self.cu_device_ptr
+    }
+}
+
+unsafe impl<T: DeviceRepr> DeviceRepr for &CudaIpcSlice<T> {
+    #[inline(always)]
+    fn as_kernel_param(&self) -> *mut std::ffi::c_void {
+        (&self.cu_device_ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
+    }
+}
+
+unsafe impl<T: DeviceRepr> DeviceRepr for &mut CudaIpcSlice<T> {
+    #[inline(always)]
+    fn as_kernel_param(&self) -> *mut std::ffi::c_void {
+        (&self.cu_device_ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
+    }
+}
+
+impl<T> CudaSlice<T> {
+    /// Gets a handle that another process can open with [CudaDevice::open_ipc_mem_handle()].
+    ///
+    /// Only allocations made with `cuMemAlloc` can be shared this way. On devices that
+    /// support memory pools [CudaDevice::alloc()] allocates from a pool instead, and the
+    /// driver returns an error. `self` must stay alive while the other process uses it.
+    pub fn ipc_mem_handle(&self) -> Result<IpcMemHandle, DriverError> {
+        self.device.bind_to_thread()?;
+        let handle = unsafe { result::ipc::get_mem_handle(self.cu_device_ptr) }?;
+        Ok(IpcMemHandle(handle.reserved.map(|b| b as u8)))
+    }
+}
+
+impl CudaEvent {
+    /// Gets a handle that another process can open with [CudaDevice::open_ipc_event_handle()].
+    /// The event must have been created with [CudaDevice::create_ipc_event()].
+    pub fn ipc_handle(&self) -> Result<IpcEventHandle, DriverError> {
+        self.device.bind_to_thread()?;
+        let handle = unsafe { result::ipc::get_event_handle(self.cu_event) }?;
+        Ok(IpcEventHandle(handle.reserved.map(|b| b as u8)))
+    }
+}
+
+impl CudaDevice {
+    /// Creates a [CudaEvent] that can be shared with other processes through
+    /// [CudaEvent::ipc_handle()]. It can't be used for timing.
+    pub fn create_ipc_event(self: &Arc<Self>) -> Result<CudaEvent, DriverError> {
+        self.bind_to_thread()?;
+        let cu_event = result::ipc::create_event()?;
+        Ok(CudaEvent {
+            cu_event,
+            device: self.clone(),
+        })
+    }
+
+    /// Opens an event from another process, e.g. to wait for its work with
+    /// [CudaStream::wait_event()](super::CudaStream::wait_event).
+    ///
+    /// # Safety
+    /// `handle` must be from [CudaEvent::ipc_handle()] in another process, whose
+    /// event is still alive.
+    pub unsafe fn open_ipc_event_handle(
+        self: &Arc<Self>,
+        handle: &IpcEventHandle,
+    ) -> Result<CudaEvent, DriverError> {
+        let handle = sys::CUipcEventHandle {
+            reserved: handle.0.map(|b| b as _),
+        };
+        self.bind_to_thread()?;
+        let cu_event = result::ipc::open_event_handle(handle)?;
+        Ok(CudaEvent {
+            cu_event,
+            device: self.clone(),
+        })
+    }
+
+    /// Maps `len` elements of device memory from another process into this one.
+    ///
+    /// # Safety
+    /// 1. `handle` must be from [CudaSlice::ipc_mem_handle()] in another process, and
+    ///    `len` must be at most the length of that slice.
+    /// 2. The other process must not free the slice until the returned
+    ///    [CudaIpcSlice] has been dropped.
+    pub unsafe fn open_ipc_mem_handle<T>(
+        self: &Arc<Self>,
+        handle: &IpcMemHandle,
+        len: usize,
+    ) -> Result<CudaIpcSlice<T>, DriverError> {
+        let handle = sys::CUipcMemHandle {
+            reserved: handle.0.map(|b| b as _),
+        };
+        self.bind_to_thread()?;
+        let cu_device_ptr = result::ipc::open_mem_handle(
+            handle,
+            sys::CUipcMem_flags::CU_IPC_MEM_LAZY_ENABLE_PEER_ACCESS,
+        )?;
+        Ok(CudaIpcSlice {
+            cu_device_ptr,
+            len,
+            device: self.clone(),
+            marker: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ipc_event_handle() {
+        let dev = CudaDevice::new(0).unwrap();
+        let event = dev.create_ipc_event().unwrap();
+        event.record().unwrap();
+        let handle = event.ipc_handle().unwrap();
+        assert_eq!(IpcEventHandle::from_bytes(handle.to_bytes()), handle);
+
+        let timed = dev
+            .create_event(sys::CUevent_flags::CU_EVENT_DEFAULT)
+            .unwrap();
+        assert!(timed.ipc_handle().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_ipc_handles_bincode_round_trip() {
+        let mut bytes = [0u8; 64];
+        for (i, b) in bytes.iter_mut().enumerate() {
+            *b = 255 - i as u8;
+        }
+        let handle = IpcMemHandle::from_bytes(bytes);
+        let encoded = bincode::serialize(&handle).unwrap();
+        assert_eq!(encoded, bytes);
+        let decoded: IpcMemHandle = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+
+        let dev = CudaDevice::new(0).unwrap();
+        let handle = dev.create_ipc_event().unwrap().ipc_handle().unwrap();
+        let encoded = bincode::serialize(&handle).unwrap();
+        assert_eq!(encoded, handle.to_bytes());
+        let decoded: IpcEventHandle = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(decoded, handle);
+
+        assert!(bincode::deserialize::<IpcMemHandle>(&bytes[..63]).is_err());
+    }
+}
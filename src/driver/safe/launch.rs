@@ -1,9 +1,10 @@
-use crate::driver::{result, sys};
+use crate::driver::{result, sys, DriverError};
 
 use super::alloc::DeviceRepr;
 use super::core::{CudaDevice, CudaFunction, CudaModule, CudaStream};
 
-use std::sync::Arc;
+use std::ffi::CString;
+use std::{sync::Arc, vec::Vec};
 
 impl CudaDevice {
     /// Whether a module and function are currently loaded into the device.
@@ -31,6 +32,86 @@ impl CudaDevice {
                 device: self.clone(),
             })
     }
+
+    /// The address and size in bytes of the `__device__` global `global_name` in the
+    /// module registered under `module_name`.
+    ///
+    /// Returns `CUDA_ERROR_NOT_FOUND` if the module isn't loaded or has no such global.
+    fn get_global(
+        self: &Arc<Self>,
+        module_name: &str,
+        global_name: &str,
+    ) -> Result<(sys::CUdeviceptr, usize), DriverError> {
+        let cu_module = {
+            let modules = self.modules.read();
+            #[cfg(not(feature = "no-std"))]
+            let modules = modules.unwrap();
+            modules
+                .get(module_name)
+                .map(|m| m.cu_module)
+                .ok_or(DriverError(sys::CUresult::CUDA_ERROR_NOT_FOUND))?
+        };
+        let name_c = CString::new(global_name)
+            .map_err(|_| DriverError(sys::CUresult::CUDA_ERROR_NOT_FOUND))?;
+        self.bind_to_thread()?;
+        unsafe { result::module::get_global(cu_module, name_c) }
+    }
+
+    /// The size in bytes of the `__device__` global `global_name` in the module
+    /// registered under `module_name`.
+    pub fn global_size(
+        self: &Arc<Self>,
+        module_name: &str,
+        global_name: &str,
+    ) -> Result<usize, DriverError> {
+        self.get_global(module_name, global_name)
+            .map(|(_, bytes)| bytes)
+    }
+
+    /// Synchronously copies `src` into the `__device__` global `global_name`, starting
+    /// `offset_bytes` into the global.
+    ///
+    /// Returns `CUDA_ERROR_INVALID_VALUE` without copying anything if the copy would
+    /// write past the end of the global.
+    pub fn htod_copy_to_global<T: DeviceRepr>(
+        self: &Arc<Self>,
+        module_name: &str,
+        global_name: &str,
+        offset_bytes: usize,
+        src: &[T],
+    ) -> Result<(), DriverError> {
+        let (dptr, size) = self.get_global(module_name, global_name)?;
+        check_global_bounds(offset_bytes, std::mem::size_of_val(src), size)?;
+        unsafe { result::memcpy_htod_sync(dptr + offset_bytes as u64, src) }
+    }
+
+    /// Synchronously copies out of the `__device__` global `global_name` into `dst`,
+    /// starting `offset_bytes` into the global.
+    ///
+    /// Returns `CUDA_ERROR_INVALID_VALUE` without copying anything if the copy would
+    /// read past the end of the global.
+    pub fn dtoh_copy_from_global<T: DeviceRepr>(
+        self: &Arc<Self>,
+        module_name: &str,
+        global_name: &str,
+        offset_bytes: usize,
+        dst: &mut [T],
+    ) -> Result<(), DriverError> {
+        let (dptr, size) = self.get_global(module_name, global_name)?;
+        check_global_bounds(offset_bytes, std::mem::size_of_val(dst), size)?;
+        unsafe { result::memcpy_dtoh_sync(dst, dptr + offset_bytes as u64) }
+    }
+}
+
+fn check_global_bounds(
+    offset_bytes: usize,
+    num_bytes: usize,
+    size: usize,
+) -> Result<(), DriverError> {
+    match offset_bytes.checked_add(num_bytes) {
+        Some(end) if end <= size => Ok(()),
+        _ => Err(DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE)),
+    }
 }
 
 impl CudaModule {
@@ -64,6 +145,26 @@ impl CudaFunction {
         )
     }
 
+    #[inline(always)]
+    unsafe fn launch_ex_impl(
+        self,
+        stream: sys::CUstream,
+        cfg: &LaunchConfigEx,
+        params: &mut [*mut std::ffi::c_void],
+    ) -> Result<(), result::DriverError> {
+        self.device.bind_to_thread()?;
+        let mut attrs = cfg.attrs.clone();
+        result::launch_kernel_ex(
+            self.cu_function,
+            cfg.cfg.grid_dim,
+            cfg.cfg.block_dim,
+            cfg.cfg.shared_mem_bytes,
+            stream,
+            &mut attrs,
+            params,
+        )
+    }
+
     #[inline(always)]
     unsafe fn par_launch_async_impl(
         self,
@@ -115,6 +216,111 @@ impl LaunchConfig {
     }
 }
 
+/// A [LaunchConfig] with extended launch attributes, for use with [LaunchAsyncEx::launch_ex()].
+///
+/// ```ignore
+/// let cfg = LaunchConfigEx::new(LaunchConfig::for_num_elems(n))
+///     .cluster_dim((2, 1, 1))
+///     .priority(1);
+/// ```
+///
+/// This is the driver API's `CUlaunchConfig` for `cuLaunchKernelEx`. A [LaunchConfigEx]
+/// without attributes launches the same as [LaunchAsync::launch()], so keep using that for
+/// the common case. The attributes available with these bindings (`sys::CUlaunchAttributeID`) are:
+/// - `CU_LAUNCH_ATTRIBUTE_CLUSTER_DIMENSION`, see [LaunchConfigEx::cluster_dim()]
+/// - `CU_LAUNCH_ATTRIBUTE_PROGRAMMATIC_STREAM_SERIALIZATION`, for programmatic dependent
+///   launch, see [LaunchConfigEx::programmatic_stream_serialization()]
+/// - `CU_LAUNCH_ATTRIBUTE_PRIORITY`, see [LaunchConfigEx::priority()]
+/// - `CU_LAUNCH_ATTRIBUTE_CLUSTER_SCHEDULING_POLICY_PREFERENCE`, whether clusters are spread
+///   across or packed onto the GPU's multiprocessors
+/// - `CU_LAUNCH_ATTRIBUTE_COOPERATIVE`, a cooperative launch whose blocks can all
+///   synchronize with each other, which requires every block to be resident at once
+/// - `CU_LAUNCH_ATTRIBUTE_PROGRAMMATIC_EVENT`, an event triggered by programmatic completion
+/// - `CU_LAUNCH_ATTRIBUTE_ACCESS_POLICY_WINDOW` and `CU_LAUNCH_ATTRIBUTE_SYNCHRONIZATION_POLICY`,
+///   which override the stream's own (see [CudaStream::attribute()])
+///
+/// The ones without a helper can be added with [LaunchConfigEx::attribute()].
+///
+/// See [cuda docs](https://docs.nvidia.com/cuda/cuda-driver-api/group__CUDA__TYPES.html#group__CUDA__TYPES_1g0c5ba4cb3ba7d99190d8240f4a4c7ab6)
+/// for the details of each attribute.
+#[derive(Clone)]
+pub struct LaunchConfigEx {
+    pub cfg: LaunchConfig,
+    pub attrs: Vec<sys::CUlaunchAttribute>,
+}
+
+impl LaunchConfigEx {
+    /// A [LaunchConfigEx] with no attributes, which launches the same as `cfg`.
+    pub fn new(cfg: LaunchConfig) -> Self {
+        Self {
+            cfg,
+            attrs: Vec::new(),
+        }
+    }
+
+    /// Adds a raw attribute.
+    pub fn attribute(mut self, attr: sys::CUlaunchAttribute) -> Self {
+        self.attrs.push(attr);
+        self
+    }
+
+    /// Launches the blocks in clusters of `(x, y, z)` blocks, which are guaranteed to be
+    /// co-scheduled and can access each other's shared memory.
+    ///
+    /// This requires compute capability 9.0+ (see `CU_DEVICE_ATTRIBUTE_CLUSTER_LAUNCH`),
+    /// the grid dimensions must be multiples of the cluster dimensions, and at most 8 blocks
+    /// per cluster are portable.
+    pub fn cluster_dim(self, (x, y, z): (u32, u32, u32)) -> Self {
+        let mut value: sys::CUlaunchAttributeValue = unsafe { std::mem::zeroed() };
+        value.clusterDim.x = x;
+        value.clusterDim.y = y;
+        value.clusterDim.z = z;
+        self.attribute_value(
+            sys::CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_CLUSTER_DIMENSION,
+            value,
+        )
+    }
+
+    /// Allows this kernel to begin before the previous kernel on the stream completes, once
+    /// the previous kernel triggers programmatic completion. This requires compute capability 9.0+.
+    pub fn programmatic_stream_serialization(self, allowed: bool) -> Self {
+        let mut value: sys::CUlaunchAttributeValue = unsafe { std::mem::zeroed() };
+        value.programmaticStreamSerializationAllowed = allowed as i32;
+        self.attribute_value(
+            sys::CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_PROGRAMMATIC_STREAM_SERIALIZATION,
+            value,
+        )
+    }
+
+    /// The scheduling priority of this kernel, see `CU_CTX_STREAM_PRIORITY_RANGE`.
+    pub fn priority(self, priority: i32) -> Self {
+        let mut value: sys::CUlaunchAttributeValue = unsafe { std::mem::zeroed() };
+        value.priority = priority;
+        self.attribute_value(
+            sys::CUlaunchAttributeID::CU_LAUNCH_ATTRIBUTE_PRIORITY,
+            value,
+        )
+    }
+
+    fn attribute_value(
+        self,
+        id: sys::CUlaunchAttributeID,
+        value: sys::CUlaunchAttributeValue,
+    ) -> Self {
+        self.attribute(sys::CUlaunchAttribute {
+            id,
+            pad: Default::default(),
+            value,
+        })
+    }
+}
+
+impl From<LaunchConfig> for LaunchConfigEx {
+    fn from(cfg: LaunchConfig) -> Self {
+        Self::new(cfg)
+    }
+}
+
 /// Consumes a [CudaFunction] to execute asychronously on the device with
 /// params determined by generic parameter `Params`.
 ///
@@ -205,6 +411,86 @@ pub unsafe trait LaunchAsync<Params> {
         cfg: LaunchConfig,
         params: Params,
     ) -> Result<(), result::DriverError>;
+
+    /// Launches the function on `stream` like [LaunchAsync::launch_on_stream], and then
+    /// queries `stream` so that an error left behind by earlier work on it is
+    /// returned here instead of from some later, unrelated call.
+    ///
+    /// Invalid launch configurations (e.g. too many threads per block) are reported
+    /// by the launch itself. Errors the kernel hits while running are only seen
+    /// once it finishes; use [LaunchAsync::launch_checked_sync] to wait for those.
+    ///
+    /// # Safety
+    /// All the same rules as [LaunchAsync::launch_on_stream] apply.
+    unsafe fn launch_checked(
+        self,
+        stream: &CudaStream,
+        cfg: LaunchConfig,
+        params: Params,
+    ) -> Result<(), result::DriverError>
+    where
+        Self: Sized,
+    {
+        self.launch_on_stream(stream, cfg, params)?;
+        match result::stream::query(stream.stream) {
+            Err(result::DriverError(sys::CUresult::CUDA_ERROR_NOT_READY)) => Ok(()),
+            r => r,
+        }
+    }
+
+    /// Like [LaunchAsync::launch_checked], but also synchronizes `stream`, so any error
+    /// raised while the kernel runs is returned immediately.
+    ///
+    /// This blocks the host until all work on `stream` has finished, which removes
+    /// any overlap between host and device. Prefer it for debugging or tests rather
+    /// than in hot loops.
+    ///
+    /// # Safety
+    /// All the same rules as [LaunchAsync::launch_on_stream] apply.
+    unsafe fn launch_checked_sync(
+        self,
+        stream: &CudaStream,
+        cfg: LaunchConfig,
+        params: Params,
+    ) -> Result<(), result::DriverError>
+    where
+        Self: Sized,
+    {
+        self.launch_on_stream(stream, cfg, params)?;
+        stream.synchronize()
+    }
+}
+
+/// Launches a [CudaFunction] with the extended launch attributes in [LaunchConfigEx].
+///
+/// This is impl'd for the same `Params` as [LaunchAsync].
+///
+/// # Safety
+///
+/// All the same caveats as [LaunchAsync] apply.
+pub unsafe trait LaunchAsyncEx<Params> {
+    /// Launches the [CudaFunction] with the extended attributes in [LaunchConfigEx].
+    ///
+    /// # Safety
+    /// All the same rules as [LaunchAsync::launch] apply, and the device must
+    /// support all the attributes.
+    unsafe fn launch_ex(
+        self,
+        cfg: &LaunchConfigEx,
+        params: Params,
+    ) -> Result<(), result::DriverError>;
+
+    /// Launches the [CudaFunction] on `stream` with the extended attributes in [LaunchConfigEx].
+    ///
+    /// # Safety
+    /// All the same rules as [LaunchAsync::launch_on_stream] apply, and the device must
+    /// support all the attributes.
+    unsafe fn launch_ex_on_stream(
+        self,
+        stream: &CudaStream,
+        cfg: &LaunchConfigEx,
+        params: Params,
+    ) -> Result<(), result::DriverError>;
 }
 
 macro_rules! impl_launch {
@@ -231,6 +517,30 @@ unsafe impl<$($Vars: DeviceRepr),*> LaunchAsync<($($Vars, )*)> for CudaFunction
         self.par_launch_async_impl(stream, cfg, params)
     }
 }
+
+unsafe impl<$($Vars: DeviceRepr),*> LaunchAsyncEx<($($Vars, )*)> for CudaFunction {
+    #[inline(always)]
+    unsafe fn launch_ex(
+        self,
+        cfg: &LaunchConfigEx,
+        args: ($($Vars, )*)
+    ) -> Result<(), result::DriverError> {
+        let params = &mut [$(args.$Idx.as_kernel_param(), )*];
+        let stream = self.device.stream;
+        self.launch_ex_impl(stream, cfg, params)
+    }
+
+    #[inline(always)]
+    unsafe fn launch_ex_on_stream(
+        self,
+        stream: &CudaStream,
+        cfg: &LaunchConfigEx,
+        args: ($($Vars, )*)
+    ) -> Result<(), result::DriverError> {
+        let params = &mut [$(args.$Idx.as_kernel_param(), )*];
+        self.launch_ex_impl(stream.stream, cfg, params)
+    }
+}
     };
 }
 
@@ -380,6 +690,40 @@ extern \"C\" __global__ void sin_kernel(float *out, const float *inp, size_t num
         drop(a_dev);
     }
 
+    #[test]
+    fn test_launch_ex() {
+        let ptx = compile_ptx_with_opts(SIN_CU, Default::default()).unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "sin", &["sin_kernel"]).unwrap();
+
+        let a = dev.htod_copy(vec![1.0f32; 2048]).unwrap();
+        let mut b = dev.alloc_zeros::<f32>(2048).unwrap();
+        let cfg = LaunchConfigEx::new(LaunchConfig::for_num_elems(2048));
+        let f = dev.get_func("sin", "sin_kernel").unwrap();
+        unsafe { f.launch_ex(&cfg, (&mut b, &a, 2048usize)) }.unwrap();
+        assert!(dev
+            .dtoh_sync_copy(&b)
+            .unwrap()
+            .iter()
+            .all(|&v| v == 0.841471));
+
+        let cluster = dev
+            .attribute(sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_CLUSTER_LAUNCH)
+            .unwrap();
+        if cluster == 0 {
+            return;
+        }
+        let mut c = dev.alloc_zeros::<f32>(2048).unwrap();
+        let cfg = cfg.cluster_dim((2, 1, 1));
+        let f = dev.get_func("sin", "sin_kernel").unwrap();
+        unsafe { f.launch_ex(&cfg, (&mut c, &a, 2048usize)) }.unwrap();
+        assert!(dev
+            .dtoh_sync_copy(&c)
+            .unwrap()
+            .iter()
+            .all(|&v| v == 0.841471));
+    }
+
     const TEST_KERNELS: &str = "
 extern \"C\" __global__ void int_8bit(signed char s_min, char s_max, unsigned char u_min, unsigned char u_max) {
     assert(s_min == -128);
@@ -584,4 +928,79 @@ extern \"C\" __global__ void slow_worker(const float *data, const size_t len, fl
         );
         Ok(())
     }
+
+    #[test]
+    fn test_global_copies_are_bounds_checked() {
+        let ptx = crate::nvrtc::compile_ptx(
+            "__device__ float values[16];
+extern \"C\" __global__ void sum_values(float *out) {
+    float s = 0.0f;
+    for (int i = 0; i < 16; i++) { s += values[i]; }
+    *out = s;
+}",
+        )
+        .unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "globals", &["sum_values"]).unwrap();
+        assert_eq!(dev.global_size("globals", "values").unwrap(), 64);
+
+        let src: Vec<f32> = (0..16).map(|i| i as f32).collect();
+        dev.htod_copy_to_global("globals", "values", 0, &src)
+            .unwrap();
+        let mut out = dev.alloc_zeros::<f32>(1).unwrap();
+        let f = dev.get_func("globals", "sum_values").unwrap();
+        unsafe { f.launch(LaunchConfig::for_num_elems(1), (&mut out,)) }.unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&out).unwrap(), [120.0]);
+
+        let mut tail = [0.0f32; 4];
+        dev.dtoh_copy_from_global("globals", "values", 48, &mut tail)
+            .unwrap();
+        assert_eq!(tail, [12.0, 13.0, 14.0, 15.0]);
+
+        assert_eq!(
+            dev.htod_copy_to_global("globals", "values", 4, &src),
+            Err(DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE))
+        );
+        assert_eq!(
+            dev.dtoh_copy_from_global("globals", "values", 52, &mut tail),
+            Err(DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE))
+        );
+        assert_eq!(
+            dev.global_size("globals", "missing"),
+            Err(DriverError(sys::CUresult::CUDA_ERROR_NOT_FOUND))
+        );
+    }
+
+    #[test]
+    fn test_launch_checked() {
+        let ptx = crate::nvrtc::compile_ptx(
+            "extern \"C\" __global__ void fill(float *out, size_t n) {
+    size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+    if (i < n) { out[i] = 1.0f; }
+}",
+        )
+        .unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "checked", &["fill"]).unwrap();
+        let mut out = dev.alloc_zeros::<f32>(100).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+
+        let f = dev.get_func("checked", "fill").unwrap();
+        let cfg = LaunchConfig::for_num_elems(100);
+        unsafe { f.launch_checked(&stream, cfg, (&mut out, 100usize)) }.unwrap();
+        let f = dev.get_func("checked", "fill").unwrap();
+        unsafe { f.launch_checked_sync(&stream, cfg, (&mut out, 100usize)) }.unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&out).unwrap(), [1.0; 100]);
+
+        let too_big = LaunchConfig {
+            grid_dim: (1, 1, 1),
+            block_dim: (4096, 1, 1),
+            shared_mem_bytes: 0,
+        };
+        let f = dev.get_func("checked", "fill").unwrap();
+        assert_eq!(
+            unsafe { f.launch_checked(&stream, too_big, (&mut out, 100usize)) },
+            Err(DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE))
+        );
+    }
 }
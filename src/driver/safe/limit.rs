@@ -0,0 +1,120 @@
+use crate::driver::{result, sys, DriverError};
+
+use super::core::{unwrap_on_drop, CudaDevice};
+
+use std::sync::Arc;
+
+impl CudaDevice {
+    /// Gets the current value of `limit` for this device's context, e.g.
+    /// `CU_LIMIT_MALLOC_HEAP_SIZE` for the size in bytes of the heap used by in-kernel `malloc()`.
+    pub fn limit(&self, limit: sys::CUlimit) -> Result<usize, DriverError> {
+        self.bind_to_thread()?;
+        result::ctx::get_limit(limit)
+    }
+
+    /// Sets `limit` to `value` for this device's context. The driver may round `value`
+    /// (e.g. up to a multiple of the page size), so read it back with [CudaDevice::limit()]
+    /// if the exact value matters.
+    ///
+    /// This changes global state for every user of the context. Prefer [LimitGuard] to
+    /// change a limit only for a scope.
+    pub fn set_limit(&self, limit: sys::CUlimit, value: usize) -> Result<(), DriverError> {
+        self.bind_to_thread()?;
+        result::ctx::set_limit(limit, value)
+    }
+
+    /// The size in bytes of the FIFO that in-kernel `printf()` output is buffered in before
+    /// it is printed. See [CudaDevice::set_printf_fifo_size()].
+    pub fn printf_fifo_size(&self) -> Result<usize, DriverError> {
+        self.limit(sys::CUlimit::CU_LIMIT_PRINTF_FIFO_SIZE)
+    }
+
+    /// Sets the size in bytes of the FIFO that in-kernel `printf()` output is buffered in.
+    /// When the FIFO fills up older output is overwritten, so raise this if output
+    /// from kernels that print a lot is truncated.
+    ///
+    /// This must be set before the first kernel that uses `printf()` is launched, after that
+    /// the driver returns an error. Use [LimitGuard] to raise it only for a scope.
+    pub fn set_printf_fifo_size(&self, num_bytes: usize) -> Result<(), DriverError> {
+        self.set_limit(sys::CUlimit::CU_LIMIT_PRINTF_FIFO_SIZE, num_bytes)
+    }
+}
+
+/// Sets a limit of a [CudaDevice]'s context, and restores the previous value on drop.
+///
+/// Some limits can't be changed once the context has started using them, and the driver
+/// returns an error instead. For example `CU_LIMIT_MALLOC_HEAP_SIZE` can't be changed after
+/// a kernel that uses in-kernel `malloc()` has been launched, and `CU_LIMIT_STACK_SIZE` can't be
+/// changed while kernels are running. In those cases [LimitGuard::new()] returns the error and
+/// nothing is changed.
+///
+/// ```ignore
+/// let _guard = LimitGuard::new(&dev, sys::CUlimit::CU_LIMIT_PRINTF_FIFO_SIZE, 16 << 20)?;
+/// // ... launch kernels that print a lot ...
+/// ```
+///
+/// # Panics
+///
+/// Dropping the guard panics if the previous value can't be restored.
+#[derive(Debug)]
+pub struct LimitGuard {
+    pub(crate) device: Arc<CudaDevice>,
+    pub(crate) limit: sys::CUlimit,
+    pub(crate) previous: usize,
+}
+
+impl LimitGuard {
+    /// Sets `limit` of `device` to `value`, remembering the current value.
+    pub fn new(
+        device: &Arc<CudaDevice>,
+        limit: sys::CUlimit,
+        value: usize,
+    ) -> Result<Self, DriverError> {
+        let previous = device.limit(limit)?;
+        device.set_limit(limit, value)?;
+        Ok(Self {
+            device: device.clone(),
+            limit,
+            previous,
+        })
+    }
+
+    /// The value that will be restored on drop.
+    pub fn previous(&self) -> usize {
+        self.previous
+    }
+}
+
+impl Drop for LimitGuard {
+    fn drop(&mut self) {
+        unwrap_on_drop(self.device.set_limit(self.limit, self.previous));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_guard_restores_malloc_heap_size() {
+        let dev = CudaDevice::new(0).unwrap();
+        let limit = sys::CUlimit::CU_LIMIT_MALLOC_HEAP_SIZE;
+        let original = dev.limit(limit).unwrap();
+        {
+            let guard = LimitGuard::new(&dev, limit, 2 * original).unwrap();
+            assert_eq!(guard.previous(), original);
+            assert!(dev.limit(limit).unwrap() >= 2 * original);
+        }
+        assert_eq!(dev.limit(limit).unwrap(), original);
+    }
+
+    #[test]
+    fn test_printf_fifo_size() {
+        let dev = CudaDevice::new(0).unwrap();
+        let original = dev.printf_fifo_size().unwrap();
+        let raised = 2 * original.max(1 << 20);
+        dev.set_printf_fifo_size(raised).unwrap();
+        assert_eq!(dev.printf_fifo_size().unwrap(), raised);
+        dev.set_printf_fifo_size(original).unwrap();
+    }
+}
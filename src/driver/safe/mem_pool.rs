@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use super::alloc::size_of_len;
+use super::core::{unwrap_on_drop, CudaDevice, CudaSlice};
+use super::device_ptr::DevicePtr;
+use crate::driver::{result, sys, DriverError};
+
+/// A wrapper around [sys::CUmemoryPool] for stream ordered allocations that can
+/// be shared with other processes.
+///
+/// Sharing a pool allocation with another process takes two steps, **in this order**:
+/// 1. The pool itself is exported with [CudaMemPool::export_to_shareable_handle()] and
+///    imported in the other process with [CudaDevice::import_mem_pool()].
+/// 2. Each allocation is exported with [CudaMemPool::export_pointer()], and imported
+///    with [CudaMemPool::import_pointer()] on the **imported pool** from step 1.
+///
+/// The export data from step 2 is plain bytes ([MemPoolPtrExportData]) that can be sent
+/// alongside the file descriptor from step 1.
+///
+/// Create with [CudaDevice::create_exportable_mem_pool()].
+#[derive(Debug)]
+pub struct CudaMemPool {
+    pub(crate) cu_pool: sys::CUmemoryPool,
+    pub(crate) imported: bool,
+    pub(crate) device: Arc<CudaDevice>,
+}
+
+unsafe impl Send for CudaMemPool {}
+unsafe impl Sync for CudaMemPool {}
+
+impl Drop for CudaMemPool {
+    fn drop(&mut self) {
+        unwrap_on_drop(self.device.bind_to_thread());
+        unwrap_on_drop(unsafe { result::mem_pool::destroy(self.cu_pool) });
+    }
+}
+
+/// The data needed to import one pool allocation into another process,
+/// see [CudaMemPool::export_pointer()]. This is plain bytes, so it can be
+/// sent to the other process in any way, and with the `serde` feature it
+/// implements `Serialize` and `Deserialize`.
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemPoolPtrExportData(
+    #[cfg_attr(feature = "serde", serde(with = "super::ipc::bytes64"))] pub [u8; 64],
+);
+
+impl MemPoolPtrExportData {
+    /// The raw bytes, e.g. to write to a socket.
+    pub fn to_bytes(self) -> [u8; 64] {
+        self.0
+    }
+
+    /// Reconstructs the export data from [MemPoolPtrExportData::to_bytes()].
+    pub fn from_bytes(bytes: [u8; 64]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl CudaDevice {
+    /// Creates a [CudaMemPool] on this device whose allocations can be shared with
+    /// other processes through posix file descriptors.
+    #[cfg(unix)]
+    pub fn create_exportable_mem_pool(self: &Arc<Self>) -> Result<CudaMemPool, DriverError> {
+        let props = sys::CUmemPoolProps {
+            allocType: sys::CUmemAllocationType::CU_MEM_ALLOCATION_TYPE_PINNED,
+            handleTypes: sys::CUmemAllocationHandleType::CU_MEM_HANDLE_TYPE_POSIX_FILE_DESCRIPTOR,
+            location: sys::CUmemLocation {
+                type_: sys::CUmemLocationType::CU_MEM_LOCATION_TYPE_DEVICE,
+                id: self.ordinal as i32,
+            },
+            win32SecurityAttributes: std::ptr::null_mut(),
+            reserved: [0; 64],
+        };
+        self.bind_to_thread()?;
+        let cu_pool = unsafe { result::mem_pool::create(&props) }?;
+        Ok(CudaMemPool {
+            cu_pool,
+            imported: false,
+            device: self.clone(),
+        })
+    }
+
+    /// Imports a pool exported with [CudaMemPool::export_to_shareable_handle()], usually
+    /// by another process. This must happen before importing any of its allocations with
+    /// [CudaMemPool::import_pointer()].
+    ///
+    /// The file descriptor stays owned by the caller. Imported pools can't be allocated from.
+    ///
+    /// # Safety
+    /// `file` must wrap a file descriptor exported from a memory pool.
+    #[cfg(unix)]
+    pub unsafe fn import_mem_pool(
+        self: &Arc<Self>,
+        file: &std::fs::File,
+    ) -> Result<CudaMemPool, DriverError> {
+        use std::os::fd::AsRawFd;
+        self.bind_to_thread()?;
+        let cu_pool = result::mem_pool::import_from_shareable_handle_fd(file.as_raw_fd())?;
+        Ok(CudaMemPool {
+            cu_pool,
+            imported: true,
+            device: self.clone(),
+        })
+    }
+}
+
+impl CudaMemPool {
+    /// Get the underlying [sys::CUmemoryPool].
+    ///
+    /// # Safety
+    /// While this function is marked as safe, actually using the
+    /// returned object is unsafe.
+    ///
+    /// **You must not destroy the pool**, as it is still
+    /// owned by the [CudaMemPool].
+    pub fn cu_pool(&self) -> &sys::CUmemoryPool {
+        &self.cu_pool
+    }
+
+    /// Allocates `len` elements from this pool, ordered on the device's default work stream.
+    /// The returned [CudaSlice] is freed like any other.
+    ///
+    /// Returns `CUDA_ERROR_INVALID_VALUE` for imported pools.
+    ///
+    /// # Safety
+    /// This is unsafe because the device memory is unset after this call.
+    pub unsafe fn alloc<T>(&self, len: usize) -> Result<CudaSlice<T>, DriverError> {
+        if self.imported {
+            return Err(DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE));
+        }
+        let num_bytes = size_of_len::<T>(len)?;
+        self.device.bind_to_thread()?;
+        let cu_device_ptr =
+            result::mem_pool::alloc_async(self.cu_pool, num_bytes, self.device.stream)?;
+        Ok(self.device.upgrade_device_ptr(cu_device_ptr, len))
+    }
+
+    /// Exports this pool as a posix file descriptor, to be sent to another process
+    /// (e.g. over a unix socket) and imported there with [CudaDevice::import_mem_pool()].
+    /// The caller owns the returned file.
+    #[cfg(unix)]
+    pub fn export_to_shareable_handle(&self) -> Result<std::fs::File, DriverError> {
+        use std::os::fd::FromRawFd;
+        self.device.bind_to_thread()?;
+        let fd = unsafe { result::mem_pool::export_to_shareable_handle_fd(self.cu_pool) }?;
+        Ok(unsafe { std::fs::File::from_raw_fd(fd) })
+    }
+
+    /// Exports the data needed to import `slice`, which must have been allocated from
+    /// this pool, with [CudaMemPool::import_pointer()].
+    pub fn export_pointer<T, Src: DevicePtr<T>>(
+        &self,
+        slice: &Src,
+    ) -> Result<MemPoolPtrExportData, DriverError> {
+        self.device.bind_to_thread()?;
+        let data = unsafe { result::mem_pool::export_pointer(*slice.device_ptr()) }?;
+        Ok(MemPoolPtrExportData(data.reserved))
+    }
+
+    /// Imports an allocation of `len` elements exported with [CudaMemPool::export_pointer()].
+    /// `self` must be the imported pool it was allocated from, see [CudaMemPool].
+    ///
+    /// # Safety
+    /// 1. `len` must be at most the length of the exported allocation.
+    /// 2. The exporting process must not free the allocation until the returned
+    ///    [CudaSlice] has been dropped.
+    pub unsafe fn import_pointer<T>(
+        &self,
+        data: &MemPoolPtrExportData,
+        len: usize,
+    ) -> Result<CudaSlice<T>, DriverError> {
+        let data = sys::CUmemPoolPtrExportData { reserved: data.0 };
+        self.device.bind_to_thread()?;
+        let cu_device_ptr = result::mem_pool::import_pointer(self.cu_pool, &data)?;
+        Ok(self.device.upgrade_device_ptr(cu_device_ptr, len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_export_import_pool_pointer() {
+        let dev = CudaDevice::new(0).unwrap();
+        let pool = dev.create_exportable_mem_pool().unwrap();
+        let mut a = unsafe { pool.alloc::<f32>(100) }.unwrap();
+        dev.htod_sync_copy_into(&[1.0; 100], &mut a).unwrap();
+
+        let file = pool.export_to_shareable_handle().unwrap();
+        let data = pool.export_pointer(&a).unwrap();
+        let data = MemPoolPtrExportData::from_bytes(data.to_bytes());
+
+        let imported_pool = unsafe { dev.import_mem_pool(&file) }.unwrap();
+        assert!(unsafe { imported_pool.alloc::<f32>(1) }.is_err());
+        let b = unsafe { imported_pool.import_pointer::<f32>(&data, 100) }.unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&b).unwrap(), [1.0; 100]);
+
+        drop(b);
+        drop(imported_pool);
+        drop(a);
+    }
+}
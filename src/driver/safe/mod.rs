@@ -1,19 +1,63 @@
 //! Safe abstractions over [crate::driver::result] provided by [CudaSlice], [CudaDevice], [CudaStream], and more.
 
 pub(crate) mod alloc;
+pub(crate) mod arena;
+pub(crate) mod array;
+pub(crate) mod attributes;
+pub(crate) mod cache;
 pub(crate) mod core;
+#[cfg(feature = "debug-checks")]
+pub(crate) mod debug_checks;
 pub(crate) mod device_ptr;
+pub(crate) mod event;
 pub(crate) mod external_memory;
+pub(crate) mod external_semaphore;
+pub(crate) mod fixed;
+pub(crate) mod graph;
+pub(crate) mod ipc;
 pub(crate) mod launch;
+pub(crate) mod limit;
+pub(crate) mod mem_pool;
+pub(crate) mod pinned;
+pub(crate) mod pitched;
 pub(crate) mod profile;
 pub(crate) mod ptx;
+pub(crate) mod read_only;
+pub(crate) mod report;
+pub(crate) mod scratch;
+pub(crate) mod texture;
 pub(crate) mod threading;
+pub(crate) mod timing;
+#[cfg(feature = "track-allocations")]
+pub(crate) mod tracking;
+pub(crate) mod unified;
 
 pub use self::alloc::{DeviceRepr, ValidAsZeroBits};
+pub use self::arena::{ArenaSlice, BatchSlice, DeviceArena, ARENA_ALIGNMENT};
+pub use self::array::{ArrayInfo, CudaArray, CudaMipmappedArray, MipmapLevel};
+pub use self::attributes::CachedAttributes;
+pub use self::cache::{BufferCache, CachedSlice};
 pub use self::core::{CudaDevice, CudaFunction, CudaSlice, CudaStream, CudaView, CudaViewMut};
+#[cfg(feature = "debug-checks")]
+pub use self::debug_checks::{set_warn_hook, WarnHook};
 pub use self::device_ptr::{DevicePtr, DevicePtrMut, DeviceSlice};
+pub use self::event::{CudaEvent, StreamBarrier};
 pub use self::external_memory::{ExternalMemory, MappedBuffer};
-pub use self::launch::{LaunchAsync, LaunchConfig};
+pub use self::external_semaphore::ExternalSemaphore;
+pub use self::fixed::CudaFixedSlice;
+pub use self::graph::{CaptureModeGuard, CudaGraph, CudaGraphExec};
+pub use self::ipc::{CudaIpcSlice, IpcEventHandle, IpcMemHandle};
+pub use self::launch::{LaunchAsync, LaunchAsyncEx, LaunchConfig, LaunchConfigEx};
+pub use self::limit::LimitGuard;
+pub use self::mem_pool::{CudaMemPool, MemPoolPtrExportData};
+pub use self::pinned::{DtoHChunks, PinnedHostSlice, PinnedRing, StagingPipeline, StagingTicket};
+pub use self::pitched::Pitched3DSlice;
 pub use self::profile::{profiler_start, profiler_stop, Profiler};
+pub use self::read_only::CudaReadOnlySlice;
+pub use self::report::DeviceInfo;
+pub use self::scratch::ScratchSlice;
+pub use self::texture::TextureDescBuilder;
+pub use self::timing::TimedScope;
+pub use self::unified::{MemLocation, UnifiedSlice};
 
 pub use crate::driver::result::DriverError;
@@ -0,0 +1,793 @@
+use crate::driver::{result, sys, DriverError};
+
+use super::alloc::{size_of_len, DeviceRepr, ValidAsZeroBits};
+use super::core::{unwrap_on_drop, CudaDevice, CudaStream, CudaView, CudaViewMut};
+use super::device_ptr::{DevicePtr, DevicePtrMut};
+use super::event::CudaEvent;
+
+use std::{marker::PhantomData, sync::Arc, vec::Vec};
+
+/// Page-locked host memory allocated with [result::malloc_host].
+///
+/// Page-locked memory can be copied to and from the device asynchronously,
+/// and if it was allocated with [CudaDevice::alloc_pinned_mapped()] it
+/// can be read & written directly by kernels through [PinnedHostSlice::device_view()].
+///
+/// Create with [CudaDevice::alloc_pinned()] or [CudaDevice::alloc_pinned_mapped()].
+///
+/// # Safety
+/// On drop this synchronizes the device's work stream before freeing, so that
+/// any work queued there that uses this memory completes first.
+#[derive(Debug)]
+pub struct PinnedHostSlice<T> {
+    pub(crate) host_ptr: *mut T,
+    pub(crate) len: usize,
+    /// The mapped device pointer of `host_ptr`, or `0` if the memory is not mapped.
+    pub(crate) cu_device_ptr: sys::CUdeviceptr,
+    pub(crate) device: Arc<CudaDevice>,
+}
+
+unsafe impl<T: Send> Send for PinnedHostSlice<T> {}
+unsafe impl<T: Sync> Sync for PinnedHostSlice<T> {}
+
+impl<T> Drop for PinnedHostSlice<T> {
+    fn drop(&mut self) {
+        unwrap_on_drop(self.device.synchronize());
+        unwrap_on_drop(unsafe { result::free_host(self.host_ptr as *mut _) });
+    }
+}
+
+impl CudaDevice {
+    /// Allocates page-locked host memory for `len` elements of `T`.
+    ///
+    /// The memory is **not** zeroed, and may hold anything, including data left over
+    /// from a previous allocation. Use [CudaDevice::alloc_pinned_zeros()] unless every
+    /// element is written before it is read:
+    ///
+    /// ```ignore
+    /// let mut host = unsafe { dev.alloc_pinned::<f32>(n) }?;
+    /// host.as_mut_slice()?[..n / 2].fill(1.0);
+    /// // BUG: copies n / 2 garbage values as well
+    /// dev.htod_sync_copy_into(host.as_slice()?, &mut dst)?;
+    /// ```
+    ///
+    /// # Safety
+    /// This is unsafe because the host memory is unset after this call.
+    pub unsafe fn alloc_pinned<T: DeviceRepr>(
+        self: &Arc<Self>,
+        len: usize,
+    ) -> Result<PinnedHostSlice<T>, DriverError> {
+        self.alloc_pinned_with_flags(len, 0)
+    }
+
+    /// Allocates page-locked host memory for `len` elements of `T`, and sets all of
+    /// its bytes to 0.
+    pub fn alloc_pinned_zeros<T: ValidAsZeroBits + DeviceRepr>(
+        self: &Arc<Self>,
+        len: usize,
+    ) -> Result<PinnedHostSlice<T>, DriverError> {
+        let slice = unsafe { self.alloc_pinned_with_flags(len, 0) }?;
+        // the driver may hand back a null pointer for an empty allocation
+        if len > 0 {
+            unsafe { std::ptr::write_bytes(slice.host_ptr, 0, len) };
+        }
+        Ok(slice)
+    }
+
+    /// Allocates page-locked host memory for `len` elements of `T` that is also
+    /// mapped into the device's address space, so kernels can access it directly
+    /// through [PinnedHostSlice::device_view()].
+    ///
+    /// Returns `CUDA_ERROR_NOT_SUPPORTED` if the device can't map host memory
+    /// (see `CU_DEVICE_ATTRIBUTE_CAN_MAP_HOST_MEMORY`).
+    ///
+    /// # Safety
+    /// This is unsafe because the host memory is unset after this call.
+    pub unsafe fn alloc_pinned_mapped<T: DeviceRepr>(
+        self: &Arc<Self>,
+        len: usize,
+    ) -> Result<PinnedHostSlice<T>, DriverError> {
+        let can_map =
+            self.attribute(sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_CAN_MAP_HOST_MEMORY)?;
+        if can_map == 0 {
+            return Err(DriverError(sys::CUresult::CUDA_ERROR_NOT_SUPPORTED));
+        }
+        let mut slice = self.alloc_pinned_with_flags(len, sys::CU_MEMHOSTALLOC_DEVICEMAP)?;
+        slice.cu_device_ptr = result::host_get_device_ptr(slice.host_ptr as *mut _)?;
+        Ok(slice)
+    }
+
+    unsafe fn alloc_pinned_with_flags<T: DeviceRepr>(
+        self: &Arc<Self>,
+        len: usize,
+        flags: std::ffi::c_uint,
+    ) -> Result<PinnedHostSlice<T>, DriverError> {
+        let num_bytes = size_of_len::<T>(len)?;
+        self.bind_to_thread()?;
+        let host_ptr = result::malloc_host(num_bytes, flags)?;
+        Ok(PinnedHostSlice {
+            host_ptr: host_ptr as *mut T,
+            len,
+            cu_device_ptr: 0,
+            device: self.clone(),
+        })
+    }
+}
+
+impl<T> PinnedHostSlice<T> {
+    /// Get a clone of the underlying [CudaDevice].
+    pub fn device(&self) -> Arc<CudaDevice> {
+        self.device.clone()
+    }
+
+    /// The number of elements of `T` in this allocation.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether this memory is mapped into the device's address space.
+    pub fn is_mapped(&self) -> bool {
+        self.cu_device_ptr != 0
+    }
+
+    /// Synchronizes the device's work stream, then returns the host memory as a slice.
+    pub fn as_slice(&self) -> Result<&[T], DriverError> {
+        self.device.synchronize()?;
+        Ok(unsafe { std::slice::from_raw_parts(self.host_ptr, self.len) })
+    }
+
+    /// Synchronizes the device's work stream, then returns the host memory as a mutable slice.
+    pub fn as_mut_slice(&mut self) -> Result<&mut [T], DriverError> {
+        self.device.synchronize()?;
+        Ok(unsafe { std::slice::from_raw_parts_mut(self.host_ptr, self.len) })
+    }
+
+    /// A [CudaView] of the mapped memory that can be passed to kernels in place of
+    /// device memory. Returns `None` if this memory is not mapped.
+    ///
+    /// Accesses from kernels go over the PCIe bus for every read/write, so this
+    /// is best suited to data that is touched once.
+    pub fn device_view(&self) -> Option<CudaView<'_, T>> {
+        self.is_mapped().then_some(CudaView {
+            root: &self.cu_device_ptr,
+            ptr: self.cu_device_ptr,
+            len: self.len,
+            marker: PhantomData,
+        })
+    }
+
+    /// A [CudaViewMut] of the mapped memory that can be passed to kernels in place of
+    /// device memory. Returns `None` if this memory is not mapped.
+    pub fn device_view_mut(&mut self) -> Option<CudaViewMut<'_, T>> {
+        self.is_mapped().then_some(CudaViewMut {
+            ptr: self.cu_device_ptr,
+            root: &mut self.cu_device_ptr,
+            len: self.len,
+            marker: PhantomData,
+        })
+    }
+}
+
+impl CudaDevice {
+    /// Copies the elements of `iter` into `dst` in blocks of up to `staging.len()`,
+    /// without collecting `iter` on the host first. Each block is written to `staging`
+    /// and then copied, so `staging` can be reused across calls instead of allocating
+    /// page-locked memory every time. This returns after all the copies have completed,
+    /// with the number of elements that were copied.
+    ///
+    /// If `iter` has fewer elements than `dst`, the rest of `dst` is left unchanged.
+    /// If `iter` has more elements than `dst`, the first `dst.len()` elements are
+    /// copied and `CUDA_ERROR_INVALID_VALUE` is returned. `CUDA_ERROR_INVALID_VALUE`
+    /// is also returned if `staging` is empty and `dst` isn't.
+    pub fn htod_copy_from_iter<T: DeviceRepr, I: IntoIterator<Item = T>, Dst: DevicePtrMut<T>>(
+        self: &Arc<Self>,
+        iter: I,
+        dst: &mut Dst,
+        staging: &mut PinnedHostSlice<T>,
+    ) -> Result<usize, DriverError> {
+        if staging.is_empty() && !dst.is_empty() {
+            return Err(DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE));
+        }
+        let chunk_len = staging.len();
+        staging.device.synchronize()?;
+        self.bind_to_thread()?;
+        let dst_ptr = *dst.device_ptr_mut();
+        let mut iter = iter.into_iter();
+        let mut num_copied = 0;
+        while num_copied < dst.len() {
+            let n = chunk_len.min(dst.len() - num_copied);
+            let mut filled = 0;
+            for (i, x) in iter.by_ref().take(n).enumerate() {
+                unsafe { staging.host_ptr.add(i).write(x) };
+                filled += 1;
+            }
+            if filled == 0 {
+                break;
+            }
+            let offset = (num_copied * std::mem::size_of::<T>()) as sys::CUdeviceptr;
+            let src = unsafe { std::slice::from_raw_parts(staging.host_ptr, filled) };
+            unsafe { result::memcpy_htod_async(dst_ptr + offset, src, self.stream) }?;
+            self.synchronize()?;
+            num_copied += filled;
+            if filled < n {
+                break;
+            }
+        }
+        if num_copied == dst.len() && iter.next().is_some() {
+            return Err(DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE));
+        }
+        Ok(num_copied)
+    }
+}
+
+/// Double buffered device to host copies through two [PinnedHostSlice]s, so that
+/// the host can process one buffer while the next one is being copied.
+///
+/// Each call to [StagingPipeline::submit()] alternates between two pinned buffers and two
+/// [CudaStream]s, and returns a [StagingTicket] that is passed to [StagingPipeline::collect()]
+/// to wait for the copy & access the host data.
+///
+/// ```ignore
+/// let mut pipeline = StagingPipeline::new(&dev, 1024)?;
+/// let mut ticket = pipeline.submit(&bufs[0])?;
+/// for buf in bufs[1..].iter() {
+///     let next = pipeline.submit(buf)?; // copies while the previous result is processed
+///     process(pipeline.collect(ticket)?);
+///     ticket = next;
+/// }
+/// process(pipeline.collect(ticket)?);
+/// ```
+///
+/// # Double buffering invariant
+///
+/// At most two submissions can be outstanding. The oldest ticket must be collected
+/// before submitting a third time, otherwise [StagingPipeline::submit()] panics.
+///
+/// A ticket can only be used with the pipeline that issued it, otherwise
+/// [StagingPipeline::is_ready()] and [StagingPipeline::collect()] panic.
+#[derive(Debug)]
+pub struct StagingPipeline<T> {
+    id: usize,
+    buffers: [PinnedHostSlice<T>; 2],
+    streams: [CudaStream; 2],
+    events: [CudaEvent; 2],
+    pending: [bool; 2],
+    next: usize,
+    device: Arc<CudaDevice>,
+}
+
+/// A submission to a [StagingPipeline], redeemed with [StagingPipeline::collect()].
+#[derive(Debug)]
+#[must_use]
+pub struct StagingTicket {
+    pipeline: usize,
+    slot: usize,
+    len: usize,
+}
+
+impl<T: DeviceRepr> StagingPipeline<T> {
+    /// Creates a pipeline whose two pinned buffers each hold `capacity` elements.
+    pub fn new(device: &Arc<CudaDevice>, capacity: usize) -> Result<Self, DriverError> {
+        let buffers = unsafe {
+            [
+                device.alloc_pinned(capacity)?,
+                device.alloc_pinned(capacity)?,
+            ]
+        };
+        let streams = [device.fork_default_stream()?, device.fork_default_stream()?];
+        let flags = sys::CUevent_flags::CU_EVENT_DISABLE_TIMING;
+        let events = [device.create_event(flags)?, device.create_event(flags)?];
+        static NEXT_ID: core::sync::atomic::AtomicUsize = core::sync::atomic::AtomicUsize::new(0);
+        Ok(Self {
+            id: NEXT_ID.fetch_add(1, core::sync::atomic::Ordering::Relaxed),
+            buffers,
+            streams,
+            events,
+            pending: [false; 2],
+            next: 0,
+            device: device.clone(),
+        })
+    }
+
+    /// The number of elements each pinned buffer holds.
+    pub fn capacity(&self) -> usize {
+        self.buffers[0].len()
+    }
+
+    /// Queues an asynchronous copy of `src` into the next pinned buffer.
+    ///
+    /// The copy waits for all work already queued on the device's work stream, and
+    /// work queued on the device's work stream afterwards waits for the copy, so `src`
+    /// can't be mutated or freed while it is being copied.
+    ///
+    /// # Panics
+    /// - If `src` is longer than [StagingPipeline::capacity()].
+    /// - If two tickets are already outstanding.
+    pub fn submit<Src: DevicePtr<T>>(&mut self, src: &Src) -> Result<StagingTicket, DriverError> {
+        assert!(src.len() <= self.capacity());
+        let slot = self.next;
+        assert!(
+            !self.pending[slot],
+            "The oldest StagingTicket must be collected before submitting again"
+        );
+        let stream = &self.streams[slot];
+        stream.wait_for_default()?;
+        unsafe {
+            let dst = std::slice::from_raw_parts_mut(self.buffers[slot].host_ptr, src.len());
+            result::memcpy_dtoh_async(dst, *src.device_ptr(), stream.stream)?;
+        }
+        let event = &self.events[slot];
+        event.record_on_stream(stream)?;
+        unsafe {
+            result::stream::wait_event(
+                self.device.stream,
+                event.cu_event,
+                sys::CUevent_wait_flags::CU_EVENT_WAIT_DEFAULT,
+            )
+        }?;
+        self.pending[slot] = true;
+        self.next = 1 - slot;
+        Ok(StagingTicket {
+            pipeline: self.id,
+            slot,
+            len: src.len(),
+        })
+    }
+
+    fn check_ticket(&self, ticket: &StagingTicket) {
+        assert_eq!(
+            ticket.pipeline, self.id,
+            "StagingTicket was issued by another StagingPipeline"
+        );
+        assert!(self.pending[ticket.slot]);
+        assert!(ticket.len <= self.capacity());
+    }
+
+    /// Whether the copy for `ticket` has completed, without blocking.
+    ///
+    /// # Panics
+    /// If `ticket` was issued by another pipeline.
+    pub fn is_ready(&self, ticket: &StagingTicket) -> Result<bool, DriverError> {
+        self.check_ticket(ticket);
+        self.events[ticket.slot].is_complete()
+    }
+
+    /// Blocks until the copy for `ticket` has completed and returns the copied host data.
+    ///
+    /// # Panics
+    /// If `ticket` was issued by another pipeline.
+    pub fn collect(&mut self, ticket: StagingTicket) -> Result<&[T], DriverError> {
+        self.check_ticket(&ticket);
+        self.events[ticket.slot].synchronize()?;
+        self.pending[ticket.slot] = false;
+        let buf = &self.buffers[ticket.slot];
+        Ok(unsafe { std::slice::from_raw_parts(buf.host_ptr, ticket.len) })
+    }
+}
+
+impl CudaDevice {
+    /// Copies `src` to the host in chunks of `chunk_len` elements, returning an iterator
+    /// over the chunks. The last chunk holds the remaining elements if `chunk_len` doesn't
+    /// divide `src.len()`.
+    ///
+    /// This is double buffered with a [StagingPipeline], so the copy of the next chunk is
+    /// already queued while the host processes the current one. This uses two pinned
+    /// buffers of `chunk_len` elements in addition to the returned [Vec]s.
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_len` is 0, this method panics.
+    pub fn dtoh_copy_chunks<'a, T: DeviceRepr, Src: DevicePtr<T>>(
+        self: &Arc<Self>,
+        src: &'a Src,
+        chunk_len: usize,
+    ) -> Result<DtoHChunks<'a, T>, DriverError> {
+        assert!(chunk_len > 0);
+        let len = chunk_len.min(src.len()).max(1);
+        let mut chunks = DtoHChunks {
+            pipeline: StagingPipeline::new(self, len)?,
+            src: CudaView {
+                root: src.device_ptr(),
+                ptr: *src.device_ptr(),
+                len: src.len(),
+                marker: PhantomData,
+            },
+            chunk_len,
+            next_start: 0,
+            pending: None,
+        };
+        chunks.pending = chunks.submit_next()?;
+        Ok(chunks)
+    }
+}
+
+/// An iterator over chunks of device memory copied to the host, created with
+/// [CudaDevice::dtoh_copy_chunks()].
+#[derive(Debug)]
+pub struct DtoHChunks<'a, T> {
+    pipeline: StagingPipeline<T>,
+    src: CudaView<'a, T>,
+    chunk_len: usize,
+    next_start: usize,
+    pending: Option<StagingTicket>,
+}
+
+impl<'a, T: DeviceRepr> DtoHChunks<'a, T> {
+    fn submit_next(&mut self) -> Result<Option<StagingTicket>, DriverError> {
+        if self.next_start >= self.src.len {
+            return Ok(None);
+        }
+        let end = (self.next_start + self.chunk_len).min(self.src.len);
+        let chunk = self.src.slice(self.next_start..end);
+        self.next_start = end;
+        self.pipeline.submit(&chunk).map(Some)
+    }
+}
+
+impl<'a, T: DeviceRepr> Iterator for DtoHChunks<'a, T> {
+    type Item = Result<Vec<T>, DriverError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ticket = self.pending.take()?;
+        self.pending = match self.submit_next() {
+            Ok(next) => next,
+            Err(e) => return Some(Err(e)),
+        };
+        Some(self.pipeline.collect(ticket).map(|host| {
+            let mut chunk = Vec::with_capacity(host.len());
+            unsafe {
+                std::ptr::copy_nonoverlapping(host.as_ptr(), chunk.as_mut_ptr(), host.len());
+                chunk.set_len(host.len());
+            }
+            chunk
+        }))
+    }
+}
+
+/// A ring of [PinnedHostSlice] chunks for streaming host to device copies, which caps
+/// the pinned host memory used while letting filling & copying overlap.
+///
+/// Fill the next chunk with [PinnedRing::acquire()] (or [PinnedRing::try_acquire()]),
+/// then copy it to the device with [PinnedRing::submit()], which moves on to the next chunk.
+///
+/// ```ignore
+/// let mut ring = PinnedRing::new(&dev, 3, 1024)?;
+/// for dst in dsts.iter_mut() {
+///     read_input(ring.acquire()?);
+///     unsafe { ring.submit(dst, &stream) }?;
+/// }
+/// dev.wait_for(&stream)?;
+/// ```
+///
+/// # Backpressure
+///
+/// At most [PinnedRing::num_chunks()] copies are in flight. Once every chunk has been
+/// submitted, [PinnedRing::acquire()] blocks until the copy out of the oldest chunk completes,
+/// and [PinnedRing::try_acquire()] returns `None` instead of blocking.
+///
+/// On drop this waits for every copy still in flight, since they run on the streams
+/// passed to [PinnedRing::submit()] rather than the device's work stream.
+#[derive(Debug)]
+pub struct PinnedRing<T> {
+    chunks: Vec<PinnedHostSlice<T>>,
+    events: Vec<CudaEvent>,
+    in_flight: Vec<bool>,
+    acquired: bool,
+    next: usize,
+}
+
+impl<T> Drop for PinnedRing<T> {
+    fn drop(&mut self) {
+        // copies may still be running on the caller's streams, which dropping the
+        // chunks doesn't synchronize
+        for (event, &in_flight) in self.events.iter().zip(self.in_flight.iter()) {
+            if in_flight {
+                unwrap_on_drop(event.synchronize());
+            }
+        }
+    }
+}
+
+impl<T: DeviceRepr + ValidAsZeroBits> PinnedRing<T> {
+    /// Creates a ring of `num_chunks` pinned chunks that each hold `chunk_len` zeroed elements.
+    pub fn new(
+        device: &Arc<CudaDevice>,
+        num_chunks: usize,
+        chunk_len: usize,
+    ) -> Result<Self, DriverError> {
+        assert!(num_chunks > 0);
+        let mut chunks = Vec::with_capacity(num_chunks);
+        let mut events = Vec::with_capacity(num_chunks);
+        for _ in 0..num_chunks {
+            chunks.push(device.alloc_pinned_zeros(chunk_len)?);
+            events.push(device.create_event(sys::CUevent_flags::CU_EVENT_DISABLE_TIMING)?);
+        }
+        Ok(Self {
+            chunks,
+            events,
+            in_flight: std::vec![false; num_chunks],
+            acquired: false,
+            next: 0,
+        })
+    }
+
+    /// The number of chunks in the ring, i.e. the maximum number of copies in flight.
+    pub fn num_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// The number of elements each chunk holds.
+    pub fn chunk_len(&self) -> usize {
+        self.chunks[0].len()
+    }
+
+    /// Returns the next chunk to fill, blocking until its previous copy has completed.
+    ///
+    /// The contents of the chunk are whatever was last written to it, or zeros.
+    pub fn acquire(&mut self) -> Result<&mut [T], DriverError> {
+        let slot = self.next;
+        if self.in_flight[slot] {
+            self.events[slot].synchronize()?;
+            self.in_flight[slot] = false;
+        }
+        self.acquired = true;
+        let chunk = &mut self.chunks[slot];
+        Ok(unsafe { std::slice::from_raw_parts_mut(chunk.host_ptr, chunk.len) })
+    }
+
+    /// Like [PinnedRing::acquire()], but returns `None` instead of blocking if the
+    /// next chunk's previous copy hasn't completed.
+    pub fn try_acquire(&mut self) -> Result<Option<&mut [T]>, DriverError> {
+        let slot = self.next;
+        if self.in_flight[slot] && !self.events[slot].is_complete()? {
+            return Ok(None);
+        }
+        self.acquire().map(Some)
+    }
+
+    /// Queues a copy of the first `dst.len()` elements of the acquired chunk into `dst`
+    /// on `stream`, and moves on to the next chunk.
+    ///
+    /// # Safety
+    /// `stream` is not synchronized with the device's default stream, and `dst` is only
+    /// borrowed for this call, so the caller must order the copy with all other uses of `dst`:
+    /// 1. Work on other streams that uses `dst` must not run until the copy completes, e.g.
+    ///    through [CudaDevice::wait_for()] or [CudaStream::synchronize()].
+    /// 2. `dst` must not be dropped (which frees it on the device's default stream) until
+    ///    the copy completes.
+    ///
+    /// # Panics
+    /// - If the chunk wasn't acquired with [PinnedRing::acquire()] or [PinnedRing::try_acquire()].
+    /// - If `dst` is longer than [PinnedRing::chunk_len()].
+    pub unsafe fn submit<Dst: DevicePtrMut<T>>(
+        &mut self,
+        dst: &mut Dst,
+        stream: &CudaStream,
+    ) -> Result<(), DriverError> {
+        assert!(
+            self.acquired,
+            "PinnedRing::acquire must be called before submit"
+        );
+        assert!(dst.len() <= self.chunk_len());
+        let slot = self.next;
+        let src = std::slice::from_raw_parts(self.chunks[slot].host_ptr, dst.len());
+        result::memcpy_htod_async(*dst.device_ptr_mut(), src, stream.stream)?;
+        self.events[slot].record_on_stream(stream)?;
+        self.in_flight[slot] = true;
+        self.acquired = false;
+        self.next = (slot + 1) % self.num_chunks();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::{LaunchAsync, LaunchConfig};
+    use crate::nvrtc::compile_ptx;
+
+    #[test]
+    fn test_htod_copy_from_iter() {
+        let dev = CudaDevice::new(0).unwrap();
+        let mut staging = unsafe { dev.alloc_pinned::<f32>(1 << 10) }.unwrap();
+        let n = 3 * staging.len() + 7;
+        let mut dst = dev.alloc_zeros::<f32>(n).unwrap();
+        let num_copied = dev
+            .htod_copy_from_iter((0..n).map(|i| i as f32), &mut dst, &mut staging)
+            .unwrap();
+        assert_eq!(num_copied, n);
+        let host = dev.dtoh_sync_copy(&dst).unwrap();
+        assert!(host.iter().enumerate().all(|(i, &x)| x == i as f32));
+
+        let mut small = dev.alloc_zeros::<f32>(4).unwrap();
+        assert_eq!(
+            dev.htod_copy_from_iter([1.0, 2.0], &mut small, &mut staging),
+            Ok(2)
+        );
+        assert_eq!(dev.dtoh_sync_copy(&small).unwrap(), [1.0, 2.0, 0.0, 0.0]);
+
+        let mut staging = unsafe { dev.alloc_pinned::<i32>(2) }.unwrap();
+        let mut dst = dev.alloc_zeros::<i32>(4).unwrap();
+        assert_eq!(
+            dev.htod_copy_from_iter(0..5, &mut dst, &mut staging),
+            Err(DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE))
+        );
+        assert_eq!(dev.dtoh_sync_copy(&dst).unwrap(), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_pinned_mapped_kernel_read() {
+        let dev = CudaDevice::new(0).unwrap();
+        let ptx = compile_ptx(
+            "
+extern \"C\" __global__ void double_it(float *out, const float *inp, size_t numel) {
+    size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+    if (i < numel) {
+        out[i] = inp[i] * 2.0;
+    }
+}",
+        )
+        .unwrap();
+        dev.load_ptx(ptx, "pinned", &["double_it"]).unwrap();
+        let f = dev.get_func("pinned", "double_it").unwrap();
+
+        let mut host = match unsafe { dev.alloc_pinned_mapped::<f32>(10) } {
+            Ok(host) => host,
+            Err(DriverError(sys::CUresult::CUDA_ERROR_NOT_SUPPORTED)) => return,
+            Err(e) => panic!("{e:?}"),
+        };
+        for (i, x) in host.as_mut_slice().unwrap().iter_mut().enumerate() {
+            *x = i as f32;
+        }
+
+        let mut out = dev.alloc_zeros::<f32>(10).unwrap();
+        let inp = host.device_view().unwrap();
+        unsafe { f.launch(LaunchConfig::for_num_elems(10), (&mut out, &inp, 10usize)) }.unwrap();
+
+        let out = dev.sync_reclaim(out).unwrap();
+        assert_eq!(out, [0.0, 2.0, 4.0, 6.0, 8.0, 10.0, 12.0, 14.0, 16.0, 18.0]);
+    }
+
+    #[test]
+    fn test_alloc_pinned_zeros() {
+        let dev = CudaDevice::new(0).unwrap();
+        let mut dirty = unsafe { dev.alloc_pinned::<u32>(1024) }.unwrap();
+        dirty.as_mut_slice().unwrap().fill(u32::MAX);
+        drop(dirty);
+
+        let host = dev.alloc_pinned_zeros::<u32>(1024).unwrap();
+        assert!(host.as_slice().unwrap().iter().all(|&x| x == 0));
+        assert!(dev.alloc_pinned_zeros::<f64>(0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_pinned_unmapped_has_no_device_view() {
+        let dev = CudaDevice::new(0).unwrap();
+        let host = unsafe { dev.alloc_pinned::<f32>(10) }.unwrap();
+        assert!(!host.is_mapped());
+        assert!(host.device_view().is_none());
+    }
+
+    #[test]
+    fn test_staging_pipeline_in_order() {
+        let dev = CudaDevice::new(0).unwrap();
+        let bufs: Vec<_> = (0..5)
+            .map(|i| dev.htod_copy(std::vec![i as f32; 8]).unwrap())
+            .collect();
+
+        let mut pipeline = StagingPipeline::new(&dev, 8).unwrap();
+        let mut results = Vec::new();
+        let mut ticket = pipeline.submit(&bufs[0]).unwrap();
+        for buf in bufs[1..].iter() {
+            let next = pipeline.submit(buf).unwrap();
+            results.push(pipeline.collect(ticket).unwrap().to_vec());
+            ticket = next;
+        }
+        assert!(pipeline.is_ready(&ticket).is_ok());
+        results.push(pipeline.collect(ticket).unwrap().to_vec());
+
+        assert_eq!(results.len(), 5);
+        for (i, r) in results.iter().enumerate() {
+            assert_eq!(r, &[i as f32; 8]);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_staging_pipeline_third_submit_panics() {
+        let dev = CudaDevice::new(0).unwrap();
+        let buf = dev.alloc_zeros::<f32>(4).unwrap();
+        let mut pipeline = StagingPipeline::new(&dev, 4).unwrap();
+        let _a = pipeline.submit(&buf).unwrap();
+        let _b = pipeline.submit(&buf).unwrap();
+        let _c = pipeline.submit(&buf).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_staging_pipeline_foreign_ticket_panics() {
+        let dev = CudaDevice::new(0).unwrap();
+        let buf = dev.alloc_zeros::<f32>(64).unwrap();
+        let mut small = StagingPipeline::new(&dev, 4).unwrap();
+        let mut large = StagingPipeline::new(&dev, 64).unwrap();
+        let _small_ticket = small.submit(&buf.slice(..4)).unwrap();
+        let ticket = large.submit(&buf).unwrap();
+        let _ = small.collect(ticket);
+    }
+
+    #[test]
+    fn test_pinned_ring() {
+        let dev = CudaDevice::new(0).unwrap();
+        let mut dsts: Vec<_> = (0..5)
+            .map(|_| dev.alloc_zeros::<u32>(256).unwrap())
+            .collect();
+        let stream = dev.fork_default_stream().unwrap();
+        let mut ring = PinnedRing::<u32>::new(&dev, 2, 256).unwrap();
+
+        for (i, dst) in dsts.iter_mut().enumerate() {
+            ring.acquire().unwrap().fill(i as u32);
+            unsafe { ring.submit(dst, &stream) }.unwrap();
+        }
+        dev.wait_for(&stream).unwrap();
+        for (i, dst) in dsts.iter().enumerate() {
+            assert_eq!(dev.dtoh_sync_copy(dst).unwrap(), [i as u32; 256]);
+        }
+
+        // once every chunk is in flight, try_acquire only succeeds after the oldest finishes
+        let mut dst = dev.alloc_zeros::<u32>(256).unwrap();
+        stream.wait_for_default().unwrap();
+        for _ in 0..2 {
+            ring.acquire().unwrap();
+            unsafe { ring.submit(&mut dst, &stream) }.unwrap();
+        }
+        stream.synchronize().unwrap();
+        assert!(ring.try_acquire().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_pinned_ring_drop_waits_for_copies() {
+        let dev = CudaDevice::new(0).unwrap();
+        let mut dst = dev.alloc_zeros::<u32>(1 << 20).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+        stream.wait_for_default().unwrap();
+        let mut ring = PinnedRing::<u32>::new(&dev, 2, 1 << 20).unwrap();
+        ring.acquire().unwrap().fill(7);
+        unsafe { ring.submit(&mut dst, &stream) }.unwrap();
+        drop(ring);
+        assert!(!stream.sync_if_busy().unwrap());
+        assert_eq!(dev.dtoh_sync_copy(&dst).unwrap(), std::vec![7; 1 << 20]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_pinned_ring_submit_without_acquire_panics() {
+        let dev = CudaDevice::new(0).unwrap();
+        let mut dst = dev.alloc_zeros::<f32>(4).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+        let mut ring = PinnedRing::<f32>::new(&dev, 2, 4).unwrap();
+        unsafe { ring.submit(&mut dst, &stream) }.unwrap();
+    }
+
+    #[test]
+    fn test_dtoh_copy_chunks() {
+        let dev = CudaDevice::new(0).unwrap();
+        let data: Vec<f32> = (0..1000).map(|i| i as f32).collect();
+        let src = dev.htod_sync_copy(&data).unwrap();
+
+        let chunks: Vec<Vec<f32>> = dev
+            .dtoh_copy_chunks(&src, 256)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let lens: Vec<usize> = chunks.iter().map(Vec::len).collect();
+        assert_eq!(lens, [256, 256, 256, 232]);
+        assert_eq!(chunks.concat(), data);
+
+        let empty = dev.alloc_zeros::<f32>(0).unwrap();
+        assert_eq!(dev.dtoh_copy_chunks(&empty, 256).unwrap().count(), 0);
+    }
+}
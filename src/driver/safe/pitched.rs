@@ -0,0 +1,216 @@
+use crate::driver::{result, sys, DriverError};
+
+use super::alloc::{size_of_len, DeviceRepr};
+use super::core::{unwrap_on_drop, CudaDevice};
+
+use std::{marker::PhantomData, sync::Arc, vec::Vec};
+
+/// A 3D volume of `width * height * depth` elements of `T` in linear device memory,
+/// where every row is padded to [Pitched3DSlice::pitch()] bytes.
+///
+/// The pitch is chosen by the driver so that every row starts at an aligned address,
+/// which makes accesses to rows coalesce. Element `(x, y, z)` is at byte offset
+/// `(z * height + y) * pitch + x * size_of::<T>()`.
+///
+/// Create with [CudaDevice::alloc_3d()].
+#[derive(Debug)]
+pub struct Pitched3DSlice<T> {
+    pub(crate) cu_device_ptr: sys::CUdeviceptr,
+    pub(crate) pitch: usize,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) depth: usize,
+    pub(crate) device: Arc<CudaDevice>,
+    pub(crate) marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for Pitched3DSlice<T> {}
+unsafe impl<T: Sync> Sync for Pitched3DSlice<T> {}
+
+impl<T> Drop for Pitched3DSlice<T> {
+    fn drop(&mut self) {
+        unwrap_on_drop(self.device.bind_to_thread());
+        unwrap_on_drop(unsafe { result::free_sync(self.cu_device_ptr) });
+    }
+}
+
+impl CudaDevice {
+    /// Allocates a [Pitched3DSlice] of `width * height * depth` elements of `T`.
+    ///
+    /// # Safety
+    /// This is unsafe because the device memory is unset after this call.
+    pub unsafe fn alloc_3d<T: DeviceRepr>(
+        self: &Arc<Self>,
+        (width, height, depth): (usize, usize, usize),
+    ) -> Result<Pitched3DSlice<T>, DriverError> {
+        let width_bytes = size_of_len::<T>(width)?;
+        let num_rows = height
+            .checked_mul(depth)
+            .ok_or(DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE))?;
+        let element_size_bytes = match std::mem::size_of::<T>() {
+            8 => 8,
+            16 => 16,
+            _ => 4,
+        };
+        self.bind_to_thread()?;
+        let (cu_device_ptr, pitch) =
+            result::malloc_pitch(width_bytes, num_rows, element_size_bytes)?;
+        Ok(Pitched3DSlice {
+            cu_device_ptr,
+            pitch,
+            width,
+            height,
+            depth,
+            device: self.clone(),
+            marker: PhantomData,
+        })
+    }
+
+    /// Allocates a pitched 2D buffer for a `rows x cols` row-major matrix and synchronously
+    /// copies the densely packed `data` into it, so the pitch is handled by the driver.
+    ///
+    /// The result is a [Pitched3DSlice] with an extent of `(cols, rows, 1)`. Copy it back
+    /// with [Pitched3DSlice::to_host_matrix()].
+    ///
+    /// Returns `CUDA_ERROR_INVALID_VALUE` if `data.len() != rows * cols`.
+    pub fn htod_matrix_copy<T: DeviceRepr>(
+        self: &Arc<Self>,
+        data: &[T],
+        rows: usize,
+        cols: usize,
+    ) -> Result<Pitched3DSlice<T>, DriverError> {
+        if rows.checked_mul(cols) != Some(data.len()) {
+            return Err(DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE));
+        }
+        let mut matrix = unsafe { self.alloc_3d((cols, rows, 1)) }?;
+        matrix.copy_from_host(data)?;
+        Ok(matrix)
+    }
+}
+
+impl<T> Pitched3DSlice<T> {
+    /// Get a clone of the underlying [CudaDevice].
+    pub fn device(&self) -> Arc<CudaDevice> {
+        self.device.clone()
+    }
+
+    /// Get the underlying [sys::CUdeviceptr], e.g. for [result::Memcpy3DParams::dst_device()]
+    /// together with [Pitched3DSlice::pitch()] and [Pitched3DSlice::height()].
+    ///
+    /// # Safety
+    /// While this function is marked as safe, actually using the
+    /// returned object is unsafe.
+    ///
+    /// **You must not free the memory**, as it is still
+    /// owned by the [Pitched3DSlice].
+    pub fn cu_device_ptr(&self) -> &sys::CUdeviceptr {
+        &self.cu_device_ptr
+    }
+
+    /// The number of bytes between the start of each row, which is at least
+    /// `width * size_of::<T>()`.
+    pub fn pitch(&self) -> usize {
+        self.pitch
+    }
+
+    /// The (width, height, depth) in elements.
+    pub fn extent(&self) -> (usize, usize, usize) {
+        (self.width, self.height, self.depth)
+    }
+
+    /// The number of rows per 2D slice.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn copy_params<'a>(&self) -> result::Memcpy3DParams<'a> {
+        result::Memcpy3DParams::new(
+            self.width * std::mem::size_of::<T>(),
+            self.height,
+            self.depth,
+        )
+    }
+
+    /// Synchronously copies `src`, a densely packed volume of the same extent, into this one.
+    ///
+    /// # Panics
+    ///
+    /// If `src` doesn't have `width * height * depth` elements, this method panics.
+    pub fn copy_from_host(&mut self, src: &[T]) -> Result<(), DriverError> {
+        assert_eq!(src.len(), self.width * self.height * self.depth);
+        let params = self
+            .copy_params()
+            .src_host(src, self.width * std::mem::size_of::<T>(), self.height)
+            .dst_device(self.cu_device_ptr, self.pitch, self.height);
+        self.device.bind_to_thread()?;
+        unsafe { result::memcpy_3d(&params) }
+    }
+
+    /// Synchronously copies this volume into `dst`, densely packed.
+    ///
+    /// # Panics
+    ///
+    /// If `dst` doesn't have `width * height * depth` elements, this method panics.
+    pub fn copy_to_host(&self, dst: &mut [T]) -> Result<(), DriverError> {
+        assert_eq!(dst.len(), self.width * self.height * self.depth);
+        let params = self
+            .copy_params()
+            .src_device(self.cu_device_ptr, self.pitch, self.height)
+            .dst_host(dst, self.width * std::mem::size_of::<T>(), self.height);
+        self.device.bind_to_thread()?;
+        unsafe { result::memcpy_3d(&params) }
+    }
+
+    /// Synchronously copies this volume into a new densely packed [Vec], e.g. a row-major
+    /// matrix from [CudaDevice::htod_matrix_copy()].
+    #[allow(clippy::uninit_vec)]
+    pub fn to_host_matrix(&self) -> Result<Vec<T>, DriverError> {
+        let len = self.width * self.height * self.depth;
+        let mut dst = Vec::with_capacity(len);
+        unsafe { dst.set_len(len) };
+        self.copy_to_host(&mut dst)?;
+        Ok(dst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pitched_3d_round_trip() {
+        let dev = CudaDevice::new(0).unwrap();
+        let (w, h, d) = (7, 5, 3);
+        let mut volume = unsafe { dev.alloc_3d::<f32>((w, h, d)) }.unwrap();
+        assert_eq!(volume.extent(), (w, h, d));
+        assert!(volume.pitch() >= w * std::mem::size_of::<f32>());
+
+        let src: Vec<f32> = (0..w * h * d).map(|i| i as f32).collect();
+        volume.copy_from_host(&src).unwrap();
+        let mut dst = std::vec![0.0; w * h * d];
+        volume.copy_to_host(&mut dst).unwrap();
+        assert_eq!(dst, src);
+        drop(volume);
+    }
+
+    #[test]
+    fn test_host_matrix_round_trip() {
+        let dev = CudaDevice::new(0).unwrap();
+        for (rows, cols) in [(1, 1), (3, 17), (33, 129), (8, 1000)] {
+            let src: Vec<f32> = (0..rows * cols).map(|i| i as f32).collect();
+            let matrix = dev.htod_matrix_copy(&src, rows, cols).unwrap();
+            assert_eq!(matrix.extent(), (cols, rows, 1));
+            assert!(matrix.pitch() >= cols * std::mem::size_of::<f32>());
+            assert_eq!(matrix.to_host_matrix().unwrap(), src);
+        }
+
+        let src: Vec<u8> = (0..3 * 5).map(|i| i as u8).collect();
+        let matrix = dev.htod_matrix_copy(&src, 3, 5).unwrap();
+        assert_eq!(matrix.to_host_matrix().unwrap(), src);
+
+        assert_eq!(
+            dev.htod_matrix_copy(&[0.0f32; 10], 3, 4).unwrap_err(),
+            DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE)
+        );
+    }
+}
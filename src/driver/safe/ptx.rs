@@ -55,3 +55,74 @@ impl CudaDevice {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::{LaunchAsync, LaunchConfig};
+    use crate::nvrtc::Ptx;
+
+    const ADD_PTX: &str = "
+.version 7.0
+.target sm_52
+.address_size 64
+
+.visible .entry add(
+    .param .u64 out,
+    .param .u64 a,
+    .param .u64 b,
+    .param .u64 n
+)
+{
+    .reg .pred %p<2>;
+    .reg .f32 %f<4>;
+    .reg .b32 %r<5>;
+    .reg .b64 %rd<12>;
+
+    ld.param.u64 %rd1, [out];
+    ld.param.u64 %rd2, [a];
+    ld.param.u64 %rd3, [b];
+    ld.param.u64 %rd4, [n];
+    mov.u32 %r1, %ctaid.x;
+    mov.u32 %r2, %ntid.x;
+    mov.u32 %r3, %tid.x;
+    mad.lo.u32 %r4, %r1, %r2, %r3;
+    cvt.u64.u32 %rd5, %r4;
+    setp.ge.u64 %p1, %rd5, %rd4;
+    @%p1 bra DONE;
+    cvta.to.global.u64 %rd6, %rd1;
+    cvta.to.global.u64 %rd7, %rd2;
+    cvta.to.global.u64 %rd8, %rd3;
+    shl.b64 %rd9, %rd5, 2;
+    add.s64 %rd10, %rd7, %rd9;
+    ld.global.f32 %f1, [%rd10];
+    add.s64 %rd10, %rd8, %rd9;
+    ld.global.f32 %f2, [%rd10];
+    add.f32 %f3, %f1, %f2;
+    add.s64 %rd11, %rd6, %rd9;
+    st.global.f32 [%rd11], %f3;
+DONE:
+    ret;
+}
+";
+
+    #[test]
+    fn test_load_embedded_ptx_and_launch() {
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(Ptx::from_src(ADD_PTX), "embedded", &["add"])
+            .unwrap();
+        assert!(dev.has_func("embedded", "add"));
+        let f = dev.get_func("embedded", "add").unwrap();
+
+        let a = dev.htod_copy(vec![1.0f32, 2.0, 3.0, 4.0, 5.0]).unwrap();
+        let b = dev
+            .htod_copy(vec![10.0f32, 20.0, 30.0, 40.0, 50.0])
+            .unwrap();
+        let mut out = dev.alloc_zeros::<f32>(5).unwrap();
+        unsafe { f.launch(LaunchConfig::for_num_elems(5), (&mut out, &a, &b, 5usize)) }.unwrap();
+        assert_eq!(
+            dev.dtoh_sync_copy(&out).unwrap(),
+            [11.0, 22.0, 33.0, 44.0, 55.0]
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use crate::driver::{sys, DriverError};
+
+use super::alloc::DeviceRepr;
+use super::core::{CudaDevice, CudaSlice};
+use super::device_ptr::{DevicePtr, DeviceSlice};
+
+use std::sync::Arc;
+
+/// A [CudaSlice] that can only be read once it is created, e.g. for lookup tables
+/// or weights that kernels must not modify.
+///
+/// Create with [CudaDevice::htod_sync_copy_read_only()] or from an owned [CudaSlice].
+///
+/// It implements [DevicePtr] but not [DevicePtrMut](super::DevicePtrMut), and only
+/// `&CudaReadOnlySlice` can be passed to kernels, so nothing can be written to it:
+/// ```compile_fail
+/// # use cudarc::driver::*;
+/// let dev = CudaDevice::new(0).unwrap();
+/// let mut a = dev.htod_sync_copy_read_only(&[1.0f32; 8]).unwrap();
+/// dev.memset_zeros(&mut a).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct CudaReadOnlySlice<T> {
+    pub(crate) slice: CudaSlice<T>,
+}
+
+impl CudaDevice {
+    /// Allocates a [CudaReadOnlySlice] and synchronously copies `src` into it.
+    pub fn htod_sync_copy_read_only<T: DeviceRepr>(
+        self: &Arc<Self>,
+        src: &[T],
+    ) -> Result<CudaReadOnlySlice<T>, DriverError> {
+        Ok(CudaReadOnlySlice {
+            slice: self.htod_sync_copy(src)?,
+        })
+    }
+}
+
+impl<T> From<CudaSlice<T>> for CudaReadOnlySlice<T> {
+    fn from(slice: CudaSlice<T>) -> Self {
+        Self { slice }
+    }
+}
+
+impl<T> CudaReadOnlySlice<T> {
+    /// Get a clone of the underlying [CudaDevice].
+    pub fn device(&self) -> Arc<CudaDevice> {
+        self.slice.device()
+    }
+
+    /// The underlying [CudaSlice].
+    pub fn as_slice(&self) -> &CudaSlice<T> {
+        &self.slice
+    }
+}
+
+impl<T> DeviceSlice<T> for CudaReadOnlySlice<T> {
+    fn len(&self) -> usize {
+        self.slice.len
+    }
+}
+
+impl<T> DevicePtr<T> for CudaReadOnlySlice<T> {
+    fn device_ptr(&self) -> &sys::CUdeviceptr {
+        &self.slice.cu_device_ptr
+    }
+}
+
+unsafe impl<T: DeviceRepr> DeviceRepr for &CudaReadOnlySlice<T> {
+    #[inline(always)]
+    fn as_kernel_param(&self) -> *mut std::ffi::c_void {
+        (&self.slice.cu_device_ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::{LaunchAsync, LaunchConfig};
+
+    #[test]
+    fn test_read_only_kernel_param() {
+        let ptx = crate::nvrtc::compile_ptx(
+            "extern \"C\" __global__ void scale(const float *table, float *out, size_t n) {
+    size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+    if (i < n) { out[i] = 2.0f * table[i]; }
+}",
+        )
+        .unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "read_only", &["scale"]).unwrap();
+
+        let table = dev
+            .htod_sync_copy_read_only(&[1.0f32, 2.0, 3.0, 4.0])
+            .unwrap();
+        assert_eq!(table.len(), 4);
+        let mut out = dev.alloc_zeros::<f32>(4).unwrap();
+
+        let f = dev.get_func("read_only", "scale").unwrap();
+        let cfg = LaunchConfig::for_num_elems(4);
+        unsafe { f.launch(cfg, (&table, &mut out, 4usize)) }.unwrap();
+        assert_eq!(dev.dtoh_sync_copy(&out).unwrap(), [2.0, 4.0, 6.0, 8.0]);
+        assert_eq!(
+            dev.dtoh_sync_copy(table.as_slice()).unwrap(),
+            [1.0, 2.0, 3.0, 4.0]
+        );
+
+        let from_slice = CudaReadOnlySlice::from(out);
+        assert_eq!(from_slice.len(), 4);
+    }
+}
@@ -0,0 +1,242 @@
+use crate::driver::{result, sys, DriverError};
+
+use super::core::CudaDevice;
+use super::device_ptr::DevicePtr;
+
+use std::{fmt::Write, string::String, sync::Arc};
+
+/// A summary of a device, returned by [CudaDevice::info()].
+///
+/// Ordinals depend on `CUDA_VISIBLE_DEVICES`, so to find the same device from another
+/// process compare the PCI ids instead. With the `serde` feature this implements
+/// `Serialize` and `Deserialize`, e.g. to send it to another process.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceInfo {
+    /// The ordinal of the device in the process that created this.
+    pub ordinal: usize,
+    /// The name of the device.
+    pub name: String,
+    /// The (major, minor) compute capability.
+    pub compute_capability: (i32, i32),
+    /// Total memory in bytes.
+    pub total_mem: usize,
+    /// The PCI domain id.
+    pub pci_domain_id: i32,
+    /// The PCI bus id.
+    pub pci_bus_id: i32,
+    /// The PCI device id.
+    pub pci_device_id: i32,
+}
+
+impl CudaDevice {
+    /// A [DeviceInfo] summary of this device.
+    pub fn info(&self) -> Result<DeviceInfo, DriverError> {
+        use sys::CUdevice_attribute::*;
+        let attr = |attrib| unsafe { result::device::get_attribute(self.cu_device, attrib) };
+        Ok(DeviceInfo {
+            ordinal: self.ordinal,
+            name: unsafe { result::device::get_name(self.cu_device) }?,
+            compute_capability: (
+                attr(CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MAJOR)?,
+                attr(CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MINOR)?,
+            ),
+            total_mem: unsafe { result::device::total_mem(self.cu_device) }?,
+            pci_domain_id: attr(CU_DEVICE_ATTRIBUTE_PCI_DOMAIN_ID)?,
+            pci_bus_id: attr(CU_DEVICE_ATTRIBUTE_PCI_BUS_ID)?,
+            pci_device_id: attr(CU_DEVICE_ATTRIBUTE_PCI_DEVICE_ID)?,
+        })
+    }
+}
+
+impl CudaDevice {
+    /// A human readable summary of the driver and every device on the machine, including
+    /// each device's name, compute capability, memory, multiprocessor count, clock rates
+    /// and a few capability flags. This is meant to be pasted into bug reports.
+    ///
+    /// No contexts are created, so this is safe to call before any [CudaDevice] exists.
+    /// If the driver can't be initialized or there are no devices, the report says so
+    /// instead of returning an error. If querying a device fails, the error is appended
+    /// after what was already written for it, and the report moves on to the next device.
+    pub fn report() -> String {
+        let mut report = String::new();
+        let count = match result::init().and_then(|_| result::device::get_count()) {
+            Ok(0) => Err(DriverError(sys::CUresult::CUDA_ERROR_NO_DEVICE)),
+            count => count,
+        };
+        let count = match count {
+            Ok(count) => count,
+            Err(e) => {
+                writeln!(report, "no CUDA devices: {:?}", e).unwrap();
+                return report;
+            }
+        };
+        match result::driver_version() {
+            Ok(version) => writeln!(
+                report,
+                "CUDA driver version: {}.{}",
+                version / 1000,
+                (version % 1000) / 10
+            ),
+            Err(e) => writeln!(report, "CUDA driver version: error: {:?}", e),
+        }
+        .unwrap();
+        for ordinal in 0..count {
+            writeln!(report, "Device {}:", ordinal).unwrap();
+            if let Err(e) = write_device_report(&mut report, ordinal) {
+                writeln!(report, "  error: {:?}", e).unwrap();
+            }
+        }
+        report
+    }
+}
+
+impl CudaDevice {
+    /// Copies `num_bytes` bytes of `src`, starting `offset_bytes` into it, to the host and
+    /// formats them like `hexdump -C`: 16 bytes per line, prefixed by their offset into
+    /// `src` and followed by their printable ASCII characters.
+    ///
+    /// ```text
+    /// 00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 00 00 00  |Hello, world!...|
+    /// ```
+    ///
+    /// Returns `CUDA_ERROR_INVALID_VALUE` if the range is out of bounds of `src`.
+    pub fn dtoh_hex_dump<T, Src: DevicePtr<T>>(
+        self: &Arc<Self>,
+        src: &Src,
+        offset_bytes: usize,
+        num_bytes: usize,
+    ) -> Result<String, DriverError> {
+        match offset_bytes.checked_add(num_bytes) {
+            Some(end) if end <= src.num_bytes() => {}
+            _ => return Err(DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE)),
+        }
+        let mut bytes = std::vec![0u8; num_bytes];
+        self.bind_to_thread()?;
+        let ptr = *src.device_ptr() + offset_bytes as sys::CUdeviceptr;
+        unsafe { result::memcpy_dtoh_sync(&mut bytes, ptr) }?;
+
+        let mut dump = String::new();
+        for (i, line) in bytes.chunks(16).enumerate() {
+            write!(dump, "{:08x} ", offset_bytes + 16 * i).unwrap();
+            for j in 0..16 {
+                if j == 8 {
+                    dump.push(' ');
+                }
+                match line.get(j) {
+                    Some(b) => write!(dump, " {:02x}", b).unwrap(),
+                    None => dump.push_str("   "),
+                }
+            }
+            dump.push_str("  |");
+            for &b in line {
+                dump.push(if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                });
+            }
+            dump.push_str("|\n");
+        }
+        Ok(dump)
+    }
+}
+
+fn write_device_report(report: &mut String, ordinal: i32) -> Result<(), DriverError> {
+    use sys::CUdevice_attribute::*;
+
+    let dev = result::device::get(ordinal)?;
+    let attr = |attrib| unsafe { result::device::get_attribute(dev, attrib) };
+    let flag = |attrib| attr(attrib).map(|v| v != 0);
+
+    let name = unsafe { result::device::get_name(dev) }?;
+    writeln!(report, "  name: {}", name).unwrap();
+    let total_mem = unsafe { result::device::total_mem(dev) }?;
+    writeln!(
+        report,
+        "  compute capability: {}.{}",
+        attr(CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MAJOR)?,
+        attr(CU_DEVICE_ATTRIBUTE_COMPUTE_CAPABILITY_MINOR)?
+    )
+    .unwrap();
+    writeln!(report, "  total memory: {} MiB", total_mem >> 20).unwrap();
+    let num_sms = attr(CU_DEVICE_ATTRIBUTE_MULTIPROCESSOR_COUNT)?;
+    writeln!(report, "  multiprocessors: {}", num_sms).unwrap();
+    let clock_khz = attr(CU_DEVICE_ATTRIBUTE_CLOCK_RATE)?;
+    writeln!(report, "  clock rate: {} MHz", clock_khz / 1000).unwrap();
+    let mem_clock_khz = attr(CU_DEVICE_ATTRIBUTE_MEMORY_CLOCK_RATE)?;
+    writeln!(report, "  memory clock rate: {} MHz", mem_clock_khz / 1000).unwrap();
+    let bus_width = attr(CU_DEVICE_ATTRIBUTE_GLOBAL_MEMORY_BUS_WIDTH)?;
+    writeln!(report, "  memory bus width: {} bits", bus_width).unwrap();
+    for (label, attrib) in [
+        ("integrated", CU_DEVICE_ATTRIBUTE_INTEGRATED),
+        ("ecc enabled", CU_DEVICE_ATTRIBUTE_ECC_ENABLED),
+        ("unified addressing", CU_DEVICE_ATTRIBUTE_UNIFIED_ADDRESSING),
+        ("managed memory", CU_DEVICE_ATTRIBUTE_MANAGED_MEMORY),
+        (
+            "concurrent managed access",
+            CU_DEVICE_ATTRIBUTE_CONCURRENT_MANAGED_ACCESS,
+        ),
+        ("memory pools", CU_DEVICE_ATTRIBUTE_MEMORY_POOLS_SUPPORTED),
+        ("cooperative launch", CU_DEVICE_ATTRIBUTE_COOPERATIVE_LAUNCH),
+    ] {
+        writeln!(report, "  {}: {}", label, flag(attrib)?).unwrap();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_contains_device_name() {
+        let dev = CudaDevice::new(0).unwrap();
+        let report = CudaDevice::report();
+        assert!(report.starts_with("CUDA driver version: "));
+        assert!(report.contains(&dev.name().unwrap()));
+        assert!(report.contains("Device 0:\n  name: "));
+        assert!(report.contains("compute capability: "));
+        assert!(!report.contains("error"));
+    }
+
+    #[test]
+    fn test_device_info() {
+        let dev = CudaDevice::new(0).unwrap();
+        let info = dev.info().unwrap();
+        assert_eq!(info.ordinal, 0);
+        assert_eq!(info.name, dev.name().unwrap());
+        assert!(info.compute_capability.0 > 0);
+        assert!(info.total_mem > 0);
+
+        #[cfg(feature = "serde")]
+        {
+            let encoded = bincode::serialize(&info).unwrap();
+            assert_eq!(bincode::deserialize::<DeviceInfo>(&encoded).unwrap(), info);
+        }
+    }
+
+    #[test]
+    fn test_dtoh_hex_dump() {
+        let dev = CudaDevice::new(0).unwrap();
+        let mut host = std::vec![0u8; 20];
+        host[..13].copy_from_slice(b"Hello, world!");
+        host[16..].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+        let buf = dev.htod_sync_copy(&host).unwrap();
+
+        assert_eq!(
+            dev.dtoh_hex_dump(&buf, 0, 20).unwrap(),
+            "00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 00 00 00  |Hello, world!...|\n\
+             00000010  de ad be ef                                       |....|\n"
+        );
+        assert_eq!(
+            dev.dtoh_hex_dump(&buf, 16, 2).unwrap(),
+            "00000010  de ad                                             |..|\n"
+        );
+        assert_eq!(dev.dtoh_hex_dump(&buf, 0, 0).unwrap(), "");
+
+        let invalid = Err(DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE));
+        assert_eq!(dev.dtoh_hex_dump(&buf, 16, 5), invalid);
+        assert_eq!(dev.dtoh_hex_dump(&buf, usize::MAX, 2), invalid);
+    }
+}
@@ -0,0 +1,130 @@
+use crate::driver::{result, sys, DriverError};
+
+use super::alloc::{size_of_len, DeviceRepr};
+use super::core::{unwrap_on_drop, CudaStream};
+use super::device_ptr::{DevicePtr, DevicePtrMut, DeviceSlice};
+
+use std::marker::PhantomData;
+
+/// Temporary device memory allocated & freed in order on a [CudaStream],
+/// created with [CudaStream::alloc_scratch()].
+///
+/// It borrows the stream, so it can't outlive it, and dropping it queues the
+/// free on the stream, so work already queued on the stream can still use it.
+///
+/// ```ignore
+/// let mut scratch = unsafe { stream.alloc_scratch::<f32>(n) }?;
+/// unsafe { f.launch_on_stream(&stream, cfg, (&mut scratch, n)) }?;
+/// drop(scratch); // freed after the kernel finishes
+/// ```
+#[derive(Debug)]
+pub struct ScratchSlice<'a, T> {
+    pub(crate) cu_device_ptr: sys::CUdeviceptr,
+    pub(crate) len: usize,
+    pub(crate) stream: &'a CudaStream,
+    pub(crate) marker: PhantomData<T>,
+}
+
+impl CudaStream {
+    /// Allocates `len` elements of `T` in stream order on this stream.
+    ///
+    /// Returns `CUDA_ERROR_NOT_SUPPORTED` if the device doesn't support stream ordered
+    /// allocations (see `CU_DEVICE_ATTRIBUTE_MEMORY_POOLS_SUPPORTED`).
+    ///
+    /// # Safety
+    /// This is unsafe because the memory is unset after this call.
+    pub unsafe fn alloc_scratch<T: DeviceRepr>(
+        &self,
+        len: usize,
+    ) -> Result<ScratchSlice<'_, T>, DriverError> {
+        if !self.device.is_async {
+            return Err(DriverError(sys::CUresult::CUDA_ERROR_NOT_SUPPORTED));
+        }
+        let num_bytes = size_of_len::<T>(len)?;
+        self.device.bind_to_thread()?;
+        let cu_device_ptr = result::malloc_async(self.stream, num_bytes)?;
+        Ok(ScratchSlice {
+            cu_device_ptr,
+            len,
+            stream: self,
+            marker: PhantomData,
+        })
+    }
+}
+
+impl<'a, T> Drop for ScratchSlice<'a, T> {
+    fn drop(&mut self) {
+        unwrap_on_drop(self.stream.device.bind_to_thread());
+        unwrap_on_drop(unsafe { result::free_async(self.cu_device_ptr, self.stream.stream) });
+    }
+}
+
+impl<'a, T> DeviceSlice<T> for ScratchSlice<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'a, T> DevicePtr<T> for ScratchSlice<'a, T> {
+    fn device_ptr(&self) -> &sys::CUdeviceptr {
+        &self.cu_device_ptr
+    }
+}
+
+impl<'a, T> DevicePtrMut<T> for ScratchSlice<'a, T> {
+    fn device_ptr_mut(&mut self) -> &mut sys::CUdeviceptr {
+        &mut self.cu_device_ptr
+    }
+}
+
+unsafe impl<'a, T: DeviceRepr> DeviceRepr for &ScratchSlice<'a, T> {
+    #[inline(always)]
+    fn as_kernel_param(&self) -> *mut std::ffi::c_void {
+        (&self.cu_device_ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
+    }
+}
+
+unsafe impl<'a, T: DeviceRepr> DeviceRepr for &mut ScratchSlice<'a, T> {
+    #[inline(always)]
+    fn as_kernel_param(&self) -> *mut std::ffi::c_void {
+        (&self.cu_device_ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::CudaDevice;
+
+    #[test]
+    fn test_scratch_freed_on_drop() {
+        let dev = CudaDevice::new(0).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+        if !dev.is_async {
+            assert!(unsafe { stream.alloc_scratch::<f32>(10) }.is_err());
+            return;
+        }
+        let pool = unsafe { result::device::get_mem_pool(dev.cu_device) }.unwrap();
+        let used = || {
+            unsafe { result::stream::synchronize(stream.stream) }.unwrap();
+            unsafe {
+                result::mem_pool::get_mem_attribute(
+                    pool,
+                    sys::CUmemPool_attribute::CU_MEMPOOL_ATTR_USED_MEM_CURRENT,
+                )
+            }
+            .unwrap()
+        };
+        let before = used();
+
+        let scratch = unsafe { stream.alloc_scratch::<u32>(1 << 20) }.unwrap();
+        unsafe {
+            result::memset_d8_async(scratch.cu_device_ptr, 0, scratch.num_bytes(), stream.stream)
+        }
+        .unwrap();
+        assert!(used() >= before + (4 << 20));
+
+        drop(scratch);
+        assert_eq!(used(), before);
+    }
+}
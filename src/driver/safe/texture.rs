@@ -0,0 +1,166 @@
+use crate::driver::{sys, DriverError};
+
+/// Builds a [sys::CUDA_TEXTURE_DESC], which describes how a texture object samples
+/// its memory, so that the struct doesn't have to be filled by hand.
+///
+/// By default a texture clamps out of range coordinates, uses point filtering, takes
+/// unnormalized coordinates and returns integer formats as normalized floats. Every
+/// other field (mipmapping, anisotropy, border color) is zeroed.
+///
+/// ```ignore
+/// let desc = TextureDescBuilder::new()
+///     .filter_mode(sys::CUfilter_mode::CU_TR_FILTER_MODE_LINEAR)
+///     .normalized_coords(true)
+///     .build()?;
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct TextureDescBuilder {
+    pub(crate) raw: sys::CUDA_TEXTURE_DESC,
+}
+
+impl Default for TextureDescBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextureDescBuilder {
+    /// A builder with the defaults described on [TextureDescBuilder].
+    pub fn new() -> Self {
+        let raw = sys::CUDA_TEXTURE_DESC {
+            addressMode: [sys::CUaddress_mode::CU_TR_ADDRESS_MODE_CLAMP; 3],
+            filterMode: sys::CUfilter_mode::CU_TR_FILTER_MODE_POINT,
+            ..Default::default()
+        };
+        Self { raw }
+    }
+
+    /// How out of range coordinates are handled, in every dimension.
+    pub fn address_mode(self, mode: sys::CUaddress_mode) -> Self {
+        self.address_modes([mode; 3])
+    }
+
+    /// How out of range coordinates are handled, separately for each dimension.
+    pub fn address_modes(mut self, modes: [sys::CUaddress_mode; 3]) -> Self {
+        self.raw.addressMode = modes;
+        self
+    }
+
+    /// Whether to return the nearest element (`CU_TR_FILTER_MODE_POINT`) or interpolate
+    /// between the nearest elements (`CU_TR_FILTER_MODE_LINEAR`).
+    pub fn filter_mode(mut self, mode: sys::CUfilter_mode) -> Self {
+        self.raw.filterMode = mode;
+        self
+    }
+
+    /// Whether integer formats are read as integers (`CU_TRSF_READ_AS_INTEGER`), rather than
+    /// as floats normalized to `[0, 1]` (or `[-1, 1]` for signed formats).
+    pub fn read_as_integer(self, read_as_integer: bool) -> Self {
+        self.flag(sys::CU_TRSF_READ_AS_INTEGER, read_as_integer)
+    }
+
+    /// Whether coordinates are normalized to `[0, 1)` (`CU_TRSF_NORMALIZED_COORDINATES`),
+    /// rather than ranging over the width, height and depth in elements.
+    pub fn normalized_coords(self, normalized: bool) -> Self {
+        self.flag(sys::CU_TRSF_NORMALIZED_COORDINATES, normalized)
+    }
+
+    /// The color returned for out of range coordinates with `CU_TR_ADDRESS_MODE_BORDER`.
+    pub fn border_color(mut self, color: [f32; 4]) -> Self {
+        self.raw.borderColor = color;
+        self
+    }
+
+    fn flag(mut self, flag: u32, enabled: bool) -> Self {
+        if enabled {
+            self.raw.flags |= flag;
+        } else {
+            self.raw.flags &= !flag;
+        }
+        self
+    }
+
+    /// Validates and returns the descriptor.
+    ///
+    /// Returns `CUDA_ERROR_INVALID_VALUE` if a dimension uses `CU_TR_ADDRESS_MODE_WRAP` or
+    /// `CU_TR_ADDRESS_MODE_MIRROR` without normalized coordinates, or if linear filtering is
+    /// combined with [TextureDescBuilder::read_as_integer()], since both are unsupported by CUDA.
+    pub fn build(self) -> Result<sys::CUDA_TEXTURE_DESC, DriverError> {
+        use sys::CUaddress_mode::*;
+        let invalid = DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE);
+        let normalized = self.raw.flags & sys::CU_TRSF_NORMALIZED_COORDINATES != 0;
+        let needs_normalized = self
+            .raw
+            .addressMode
+            .iter()
+            .any(|m| matches!(m, CU_TR_ADDRESS_MODE_WRAP | CU_TR_ADDRESS_MODE_MIRROR));
+        if needs_normalized && !normalized {
+            return Err(invalid);
+        }
+        if self.raw.filterMode == sys::CUfilter_mode::CU_TR_FILTER_MODE_LINEAR
+            && self.raw.flags & sys::CU_TRSF_READ_AS_INTEGER != 0
+        {
+            return Err(invalid);
+        }
+        Ok(self.raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_texture_desc_defaults() {
+        let desc = TextureDescBuilder::new().build().unwrap();
+        assert_eq!(
+            desc.addressMode,
+            [sys::CUaddress_mode::CU_TR_ADDRESS_MODE_CLAMP; 3]
+        );
+        assert_eq!(desc.filterMode, sys::CUfilter_mode::CU_TR_FILTER_MODE_POINT);
+        assert_eq!(desc.flags, 0);
+        assert_eq!(desc.maxAnisotropy, 0);
+        assert_eq!(desc.borderColor, [0.0; 4]);
+    }
+
+    #[test]
+    fn test_texture_desc_linear_normalized() {
+        let desc = TextureDescBuilder::new()
+            .address_mode(sys::CUaddress_mode::CU_TR_ADDRESS_MODE_WRAP)
+            .filter_mode(sys::CUfilter_mode::CU_TR_FILTER_MODE_LINEAR)
+            .normalized_coords(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            desc.addressMode,
+            [sys::CUaddress_mode::CU_TR_ADDRESS_MODE_WRAP; 3]
+        );
+        assert_eq!(
+            desc.filterMode,
+            sys::CUfilter_mode::CU_TR_FILTER_MODE_LINEAR
+        );
+        assert_eq!(desc.flags, sys::CU_TRSF_NORMALIZED_COORDINATES);
+
+        let desc = TextureDescBuilder::new()
+            .read_as_integer(true)
+            .normalized_coords(true)
+            .normalized_coords(false)
+            .build()
+            .unwrap();
+        assert_eq!(desc.flags, sys::CU_TRSF_READ_AS_INTEGER);
+    }
+
+    #[test]
+    fn test_texture_desc_validation() {
+        let invalid = Err(DriverError(sys::CUresult::CUDA_ERROR_INVALID_VALUE));
+        let wrap = TextureDescBuilder::new()
+            .address_mode(sys::CUaddress_mode::CU_TR_ADDRESS_MODE_MIRROR)
+            .build();
+        assert_eq!(wrap.map(|d| d.flags), invalid);
+        let linear_int = TextureDescBuilder::new()
+            .filter_mode(sys::CUfilter_mode::CU_TR_FILTER_MODE_LINEAR)
+            .read_as_integer(true)
+            .build();
+        assert_eq!(linear_int.map(|d| d.flags), invalid);
+    }
+}
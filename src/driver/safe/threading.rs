@@ -8,6 +8,16 @@ impl CudaDevice {
     pub fn bind_to_thread(&self) -> Result<(), DriverError> {
         unsafe { result::ctx::set_current(self.cu_primary_ctx) }
     }
+
+    /// Whether the calling thread has a current CUDA context, which is a cheap way to
+    /// diagnose `CUDA_ERROR_INVALID_CONTEXT` in e.g. thread pools.
+    ///
+    /// Unlike the runtime API, the driver API never creates a context lazily: the current
+    /// context is per thread, and a new thread has none until [CudaDevice::bind_to_thread()]
+    /// (or [CudaDevice::new()]) is called on it. Returns `false` if the driver can't be queried.
+    pub fn has_active_context() -> bool {
+        matches!(result::ctx::get_current(), Ok(Some(_)))
+    }
 }
 
 #[cfg(test)]
@@ -33,4 +43,17 @@ mod tests {
         let _: crate::driver::CudaSlice<f32> = thread1.join().unwrap().unwrap();
         let _: crate::driver::CudaSlice<f32> = thread2.join().unwrap().unwrap();
     }
+
+    #[test]
+    fn test_has_active_context() {
+        let dev = CudaDevice::new(0).unwrap();
+        assert!(CudaDevice::has_active_context());
+
+        let fresh = thread::spawn(move || {
+            let before = CudaDevice::has_active_context();
+            dev.bind_to_thread().unwrap();
+            (before, CudaDevice::has_active_context())
+        });
+        assert_eq!(fresh.join().unwrap(), (false, true));
+    }
 }
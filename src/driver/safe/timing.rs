@@ -0,0 +1,134 @@
+use crate::driver::{result, sys, DriverError};
+
+use super::core::{CudaFunction, CudaStream};
+use super::event::CudaEvent;
+
+use std::time::{Duration, Instant};
+
+/// Times the work queued on a [CudaStream] between [TimedScope::start()] and
+/// [TimedScope::stop()] using a pair of [CudaEvent]s. See also [crate::time_gpu].
+///
+/// ```ignore
+/// let scope = TimedScope::start(&stream)?;
+/// unsafe { f.launch_on_stream(&stream, cfg, params) }?;
+/// let ms = scope.stop()?;
+/// ```
+#[derive(Debug)]
+pub struct TimedScope<'a> {
+    pub(crate) stream: &'a CudaStream,
+    pub(crate) start: CudaEvent,
+    pub(crate) end: CudaEvent,
+}
+
+impl<'a> TimedScope<'a> {
+    /// Records the start event on `stream`.
+    pub fn start(stream: &'a CudaStream) -> Result<Self, DriverError> {
+        let start = stream
+            .device
+            .create_event(sys::CUevent_flags::CU_EVENT_DEFAULT)?;
+        let end = stream
+            .device
+            .create_event(sys::CUevent_flags::CU_EVENT_DEFAULT)?;
+        start.record_on_stream(stream)?;
+        Ok(Self { stream, start, end })
+    }
+
+    /// Records the end event on the stream, blocks until it completes, and returns
+    /// the milliseconds elapsed on the stream since [TimedScope::start()].
+    ///
+    /// If no work was queued in between this is close to 0.
+    pub fn stop(self) -> Result<f32, DriverError> {
+        self.end.record_on_stream(self.stream)?;
+        self.end.synchronize()?;
+        self.start.elapsed_ms(&self.end)
+    }
+}
+
+/// Runs `$body` inside a [crate::driver::TimedScope] on `$stream`, returning the
+/// milliseconds elapsed on the stream as a `Result<f32, DriverError>`.
+///
+/// ```ignore
+/// let ms = time_gpu!(&stream, {
+///     unsafe { f.launch_on_stream(&stream, cfg, params) }?;
+/// })?;
+/// ```
+#[macro_export]
+macro_rules! time_gpu {
+    ($stream:expr, $body:block) => {
+        match $crate::driver::TimedScope::start($stream) {
+            Ok(scope) => {
+                $body;
+                scope.stop()
+            }
+            Err(e) => Err(e),
+        }
+    };
+}
+
+impl CudaFunction {
+    /// The average host wall clock time of one launch of this kernel, measured over
+    /// `iters` back to back launches with a 1x1 grid on the null stream.
+    ///
+    /// This measures the cost of **submitting** a launch on the host, not of executing
+    /// it: the launches are not synchronized in between, and the device is only
+    /// synchronized after the clock has stopped. Use an empty kernel to isolate launch
+    /// overhead, and [TimedScope] to time execution on the device.
+    ///
+    /// Returns [Duration::ZERO] if `iters` is 0.
+    ///
+    /// # Safety
+    /// The kernel must take no parameters, since it is launched without any.
+    pub unsafe fn measure_launch_overhead(&self, iters: u32) -> Result<Duration, DriverError> {
+        if iters == 0 {
+            return Ok(Duration::ZERO);
+        }
+        self.device.bind_to_thread()?;
+        let stream = result::stream::null();
+        let start = Instant::now();
+        for _ in 0..iters {
+            result::launch_kernel(self.cu_function, (1, 1, 1), (1, 1, 1), 0, stream, &mut [])?;
+        }
+        let elapsed = start.elapsed();
+        result::stream::synchronize(stream)?;
+        Ok(elapsed / iters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::driver::{result, CudaDevice, DevicePtrMut, DeviceSlice};
+    use std::time::Duration;
+
+    #[test]
+    fn test_time_gpu() {
+        let dev = CudaDevice::new(0).unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+        let mut a = dev.alloc_zeros::<u8>(1 << 28).unwrap();
+
+        let ms = crate::time_gpu!(&stream, {
+            unsafe { result::memset_d8_async(*a.device_ptr_mut(), 1, a.len(), stream.stream) }
+                .unwrap();
+        })
+        .unwrap();
+        assert!(ms > 0.0);
+
+        let ms = crate::time_gpu!(&stream, {}).unwrap();
+        assert!(ms >= 0.0);
+    }
+
+    #[test]
+    fn test_measure_launch_overhead() {
+        let ptx = crate::nvrtc::compile_ptx("extern \"C\" __global__ void empty() { }").unwrap();
+        let dev = CudaDevice::new(0).unwrap();
+        dev.load_ptx(ptx, "bench", &["empty"]).unwrap();
+        let f = dev.get_func("bench", "empty").unwrap();
+
+        let overhead = unsafe { f.measure_launch_overhead(100) }.unwrap();
+        assert!(overhead > Duration::ZERO);
+        assert!(overhead < Duration::from_millis(100));
+        assert_eq!(
+            unsafe { f.measure_launch_overhead(0) }.unwrap(),
+            Duration::ZERO
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use super::core::CudaDevice;
+
+use core::sync::atomic::Ordering;
+
+impl CudaDevice {
+    /// The number of bytes currently owned by [super::CudaSlice]s allocated through this
+    /// [CudaDevice], including slices created with [CudaDevice::upgrade_device_ptr()] and
+    /// excluding slices given up with [super::CudaSlice::leak()]. Every [CudaDevice::new()]
+    /// counts separately, so sum this over every [CudaDevice] for the usage of the whole process.
+    ///
+    /// Only allocations made through this crate's [super::CudaSlice]s are counted. Memory
+    /// allocated by other libraries, by [super::Pitched3DSlice], [super::UnifiedSlice] or
+    /// [super::ScratchSlice], and memory cached by the driver's memory pool, are not.
+    ///
+    /// Requires the `track-allocations` feature, which costs an atomic add on every allocation and free.
+    pub fn tracked_allocated_bytes(&self) -> usize {
+        self.allocated_bytes.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn track_alloc(&self, num_bytes: usize) {
+        self.allocated_bytes.fetch_add(num_bytes, Ordering::Relaxed);
+    }
+
+    pub(crate) fn track_free(&self, num_bytes: usize) {
+        self.allocated_bytes.fetch_sub(num_bytes, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracked_allocated_bytes() {
+        let dev = CudaDevice::new(0).unwrap();
+        assert_eq!(dev.tracked_allocated_bytes(), 0);
+
+        let a = dev.alloc_zeros::<f32>(256).unwrap();
+        let b = dev.alloc_zeros::<u8>(100).unwrap();
+        assert_eq!(dev.tracked_allocated_bytes(), 256 * 4 + 100);
+
+        drop(a);
+        assert_eq!(dev.tracked_allocated_bytes(), 100);
+
+        let ptr = b.leak();
+        assert_eq!(dev.tracked_allocated_bytes(), 0);
+        drop(unsafe { dev.upgrade_device_ptr::<u8>(ptr, 100) });
+        assert_eq!(dev.tracked_allocated_bytes(), 0);
+    }
+}
@@ -0,0 +1,346 @@
+use crate::driver::{result, sys, DriverError};
+
+use super::alloc::{size_of_len, DeviceRepr};
+use super::core::{unwrap_on_drop, CudaDevice, CudaSlice, CudaStream};
+use super::device_ptr::{DevicePtr, DevicePtrMut, DeviceSlice};
+
+use std::{marker::PhantomData, sync::Arc, vec::Vec};
+
+/// Managed memory allocated with [result::malloc_managed], which the driver
+/// migrates between the host and devices on demand.
+///
+/// It can be passed to kernels like a [super::CudaSlice], and read & written
+/// on the host with [UnifiedSlice::as_slice()] and [UnifiedSlice::as_mut_slice()].
+///
+/// Create with [CudaDevice::alloc_unified()].
+#[derive(Debug)]
+pub struct UnifiedSlice<T> {
+    pub(crate) cu_device_ptr: sys::CUdeviceptr,
+    pub(crate) len: usize,
+    pub(crate) device: Arc<CudaDevice>,
+    pub(crate) marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for UnifiedSlice<T> {}
+unsafe impl<T: Sync> Sync for UnifiedSlice<T> {}
+
+impl<T> Drop for UnifiedSlice<T> {
+    fn drop(&mut self) {
+        unwrap_on_drop(self.device.synchronize());
+        unwrap_on_drop(unsafe { result::free_sync(self.cu_device_ptr) });
+    }
+}
+
+/// Where managed memory is located or advised to be, as reported by
+/// [UnifiedSlice::preferred_location()] and friends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemLocation {
+    Host,
+    Device(sys::CUdevice),
+}
+
+impl MemLocation {
+    fn from_raw(device: sys::CUdevice) -> Option<Self> {
+        match device {
+            result::DEVICE_INVALID => None,
+            result::DEVICE_CPU => Some(Self::Host),
+            device => Some(Self::Device(device)),
+        }
+    }
+
+    fn to_raw(self) -> sys::CUdevice {
+        match self {
+            Self::Host => result::DEVICE_CPU,
+            Self::Device(device) => device,
+        }
+    }
+}
+
+impl CudaDevice {
+    /// Allocates managed memory for `len` elements of `T`, which is accessible from the host
+    /// and from any device.
+    ///
+    /// Returns `CUDA_ERROR_NOT_SUPPORTED` if the device doesn't support managed memory
+    /// (see `CU_DEVICE_ATTRIBUTE_MANAGED_MEMORY`).
+    ///
+    /// # Safety
+    /// This is unsafe because the memory is unset after this call.
+    pub unsafe fn alloc_unified<T: DeviceRepr>(
+        self: &Arc<Self>,
+        len: usize,
+    ) -> Result<UnifiedSlice<T>, DriverError> {
+        if self.attribute(sys::CUdevice_attribute::CU_DEVICE_ATTRIBUTE_MANAGED_MEMORY)? == 0 {
+            return Err(DriverError(sys::CUresult::CUDA_ERROR_NOT_SUPPORTED));
+        }
+        let num_bytes = size_of_len::<T>(len)?;
+        self.bind_to_thread()?;
+        let cu_device_ptr =
+            result::malloc_managed(num_bytes, sys::CUmemAttach_flags::CU_MEM_ATTACH_GLOBAL)?;
+        Ok(UnifiedSlice {
+            cu_device_ptr,
+            len,
+            device: self.clone(),
+            marker: PhantomData,
+        })
+    }
+}
+
+impl<T> UnifiedSlice<T> {
+    /// Get a clone of the underlying [CudaDevice].
+    pub fn device(&self) -> Arc<CudaDevice> {
+        self.device.clone()
+    }
+
+    /// Synchronizes the device's work stream, then returns the memory as a slice.
+    pub fn as_slice(&self) -> Result<&[T], DriverError> {
+        self.device.synchronize()?;
+        Ok(unsafe { std::slice::from_raw_parts(self.cu_device_ptr as *const T, self.len) })
+    }
+
+    /// Synchronizes the device's work stream, then returns the memory as a mutable slice.
+    pub fn as_mut_slice(&mut self) -> Result<&mut [T], DriverError> {
+        self.device.synchronize()?;
+        Ok(unsafe { std::slice::from_raw_parts_mut(self.cu_device_ptr as *mut T, self.len) })
+    }
+
+    /// Advises the driver about how this memory will be used from `location`, e.g.
+    /// `CU_MEM_ADVISE_SET_READ_MOSTLY`. This only affects performance, not correctness.
+    pub fn advise(
+        &self,
+        advice: sys::CUmem_advise,
+        location: MemLocation,
+    ) -> Result<(), DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe {
+            result::mem_advise(
+                self.cu_device_ptr,
+                self.num_bytes(),
+                advice,
+                location.to_raw(),
+            )
+        }
+    }
+
+    /// Migrates this memory to `location` on the device's work stream.
+    pub fn prefetch_async(&self, location: MemLocation) -> Result<(), DriverError> {
+        self.device.bind_to_thread()?;
+        unsafe {
+            result::mem_prefetch_async(
+                self.cu_device_ptr,
+                self.num_bytes(),
+                location.to_raw(),
+                self.device.stream,
+            )
+        }
+    }
+
+    /// Whether `CU_MEM_ADVISE_SET_READ_MOSTLY` is set for all of this memory.
+    pub fn is_read_mostly(&self) -> Result<bool, DriverError> {
+        let [read_mostly] = self
+            .range_attribute::<1>(sys::CUmem_range_attribute::CU_MEM_RANGE_ATTRIBUTE_READ_MOSTLY)?;
+        Ok(read_mostly != 0)
+    }
+
+    /// The location set with `CU_MEM_ADVISE_SET_PREFERRED_LOCATION` for all of this memory,
+    /// or `None` if it is unset or differs across the memory.
+    pub fn preferred_location(&self) -> Result<Option<MemLocation>, DriverError> {
+        let [location] = self.range_attribute::<1>(
+            sys::CUmem_range_attribute::CU_MEM_RANGE_ATTRIBUTE_PREFERRED_LOCATION,
+        )?;
+        Ok(MemLocation::from_raw(location))
+    }
+
+    /// The location all of this memory was last prefetched to with [UnifiedSlice::prefetch_async()],
+    /// or `None` if it was never prefetched or differs across the memory.
+    pub fn last_prefetch_location(&self) -> Result<Option<MemLocation>, DriverError> {
+        let [location] = self.range_attribute::<1>(
+            sys::CUmem_range_attribute::CU_MEM_RANGE_ATTRIBUTE_LAST_PREFETCH_LOCATION,
+        )?;
+        Ok(MemLocation::from_raw(location))
+    }
+
+    /// All the locations that have `CU_MEM_ADVISE_SET_ACCESSED_BY` set for all of this memory.
+    pub fn accessed_by(&self) -> Result<Vec<MemLocation>, DriverError> {
+        let num_devices = CudaDevice::count()? as usize;
+        let mut data = std::vec![result::DEVICE_INVALID; num_devices + 1];
+        self.device.bind_to_thread()?;
+        unsafe {
+            result::mem_range_get_attribute(
+                self.cu_device_ptr,
+                self.num_bytes(),
+                sys::CUmem_range_attribute::CU_MEM_RANGE_ATTRIBUTE_ACCESSED_BY,
+                &mut data,
+            )
+        }?;
+        Ok(data.into_iter().filter_map(MemLocation::from_raw).collect())
+    }
+
+    fn range_attribute<const N: usize>(
+        &self,
+        attribute: sys::CUmem_range_attribute,
+    ) -> Result<[i32; N], DriverError> {
+        let mut data = [0; N];
+        self.device.bind_to_thread()?;
+        unsafe {
+            result::mem_range_get_attribute(
+                self.cu_device_ptr,
+                self.num_bytes(),
+                attribute,
+                &mut data,
+            )
+        }?;
+        Ok(data)
+    }
+}
+
+impl<T: DeviceRepr> CudaSlice<T> {
+    /// Copies all of `src` into this slice on `stream`.
+    ///
+    /// # Safety
+    /// `stream` is not synchronized with the device's default stream, and both slices are
+    /// only borrowed for this call, so the caller must order the copy with all other uses:
+    /// 1. Neither slice may be dropped (which frees it on the device's default stream),
+    ///    or used by work on other streams, until the copy completes, e.g. through
+    ///    [CudaDevice::wait_for()] or [CudaStream::synchronize()].
+    /// 2. The host must not access `src` until the copy completes. Since
+    ///    [UnifiedSlice::as_slice()] only synchronizes the device's default stream,
+    ///    synchronize `stream` first.
+    ///
+    /// # Panics
+    ///
+    /// If the lengths of the slices differ, this method panics.
+    pub unsafe fn copy_from_managed_async(
+        &mut self,
+        src: &UnifiedSlice<T>,
+        stream: &CudaStream,
+    ) -> Result<(), DriverError> {
+        assert_eq!(src.len(), self.len());
+        self.device.bind_to_thread()?;
+        result::memcpy_dtod_async(
+            self.cu_device_ptr,
+            src.cu_device_ptr,
+            self.num_bytes(),
+            stream.stream,
+        )
+    }
+
+    /// Copies all of this slice into `dst` on `stream`.
+    ///
+    /// # Safety
+    /// The same as [CudaSlice::copy_from_managed_async()], with the host not accessing
+    /// `dst` until the copy completes.
+    ///
+    /// # Panics
+    ///
+    /// If the lengths of the slices differ, this method panics.
+    pub unsafe fn copy_to_managed_async(
+        &self,
+        dst: &mut UnifiedSlice<T>,
+        stream: &CudaStream,
+    ) -> Result<(), DriverError> {
+        assert_eq!(self.len(), dst.len());
+        self.device.bind_to_thread()?;
+        result::memcpy_dtod_async(
+            dst.cu_device_ptr,
+            self.cu_device_ptr,
+            self.num_bytes(),
+            stream.stream,
+        )
+    }
+}
+
+impl<T> DeviceSlice<T> for UnifiedSlice<T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<T> DevicePtr<T> for UnifiedSlice<T> {
+    fn device_ptr(&self) -> &sys::CUdeviceptr {
+        &self.cu_device_ptr
+    }
+}
+
+impl<T> DevicePtrMut<T> for UnifiedSlice<T> {
+    fn device_ptr_mut(&mut self) -> &mut sys::CUdeviceptr {
+        &mut self.cu_device_ptr
+    }
+}
+
+unsafe impl<T: DeviceRepr> DeviceRepr for &UnifiedSlice<T> {
+    #[inline(always)]
+    fn as_kernel_param(&self) -> *mut std::ffi::c_void {
+        (&self.cu_device_ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
+    }
+}
+
+unsafe impl<T: DeviceRepr> DeviceRepr for &mut UnifiedSlice<T> {
+    #[inline(always)]
+    fn as_kernel_param(&self) -> *mut std::ffi::c_void {
+        (&self.cu_device_ptr) as *const sys::CUdeviceptr as *mut std::ffi::c_void
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_read_mostly() {
+        let dev = CudaDevice::new(0).unwrap();
+        let mut a = unsafe { dev.alloc_unified::<f32>(1024) }.unwrap();
+        a.as_mut_slice().unwrap().fill(1.0);
+        assert!(!a.is_read_mostly().unwrap());
+        assert_eq!(a.preferred_location().unwrap(), None);
+
+        a.advise(
+            sys::CUmem_advise::CU_MEM_ADVISE_SET_READ_MOSTLY,
+            MemLocation::Device(dev.cu_device),
+        )
+        .unwrap();
+        assert!(a.is_read_mostly().unwrap());
+        assert_eq!(a.as_slice().unwrap(), &[1.0; 1024]);
+    }
+
+    #[test]
+    fn test_unified_preferred_location() {
+        let dev = CudaDevice::new(0).unwrap();
+        let a = unsafe { dev.alloc_unified::<f32>(1024) }.unwrap();
+        a.advise(
+            sys::CUmem_advise::CU_MEM_ADVISE_SET_PREFERRED_LOCATION,
+            MemLocation::Host,
+        )
+        .unwrap();
+        assert_eq!(a.preferred_location().unwrap(), Some(MemLocation::Host));
+
+        a.advise(
+            sys::CUmem_advise::CU_MEM_ADVISE_SET_ACCESSED_BY,
+            MemLocation::Device(dev.cu_device),
+        )
+        .unwrap();
+        assert_eq!(
+            a.accessed_by().unwrap(),
+            [MemLocation::Device(dev.cu_device)]
+        );
+    }
+
+    #[test]
+    fn test_copy_between_managed_and_device() {
+        let dev = CudaDevice::new(0).unwrap();
+        let mut src = unsafe { dev.alloc_unified::<f32>(256) }.unwrap();
+        for (i, x) in src.as_mut_slice().unwrap().iter_mut().enumerate() {
+            *x = i as f32;
+        }
+        let mut buf = dev.alloc_zeros::<f32>(256).unwrap();
+        let mut dst = unsafe { dev.alloc_unified::<f32>(256) }.unwrap();
+        let stream = dev.fork_default_stream().unwrap();
+
+        unsafe { buf.copy_from_managed_async(&src, &stream) }.unwrap();
+        unsafe { buf.copy_to_managed_async(&mut dst, &stream) }.unwrap();
+        stream.synchronize().unwrap();
+
+        let expected: Vec<f32> = (0..256).map(|i| i as f32).collect();
+        assert_eq!(dev.dtoh_sync_copy(&buf).unwrap(), expected);
+        assert_eq!(dst.as_slice().unwrap(), &expected[..]);
+    }
+}
@@ -32,6 +32,16 @@ impl std::fmt::Display for NvrtcError {
 #[cfg(feature = "std")]
 impl std::error::Error for NvrtcError {}
 
+/// Returns the `(major, minor)` CUDA version of the loaded nvrtc library, e.g. `(12, 3)`.
+///
+/// See [nvrtcVersion() docs](https://docs.nvidia.com/cuda/nvrtc/index.html#group__query)
+pub fn version() -> Result<(c_int, c_int), NvrtcError> {
+    let mut major = 0;
+    let mut minor = 0;
+    unsafe { sys::nvrtcVersion(&mut major, &mut minor) }.result()?;
+    Ok((major, minor))
+}
+
 /// Creates a program from source code `src`. This should be source code from a .cu file.
 ///
 /// See [nvrtcCreateProgram() docs](https://docs.nvidia.com/cuda/nvrtc/index.html#group__compilation_1g9ae65f68911d1cf0adda2af4ad8cb458)
@@ -0,0 +1,12 @@
+//! Compile time checks that host and device memory can't be swapped in copies.
+//!
+//! The `pass` cases are only compiled and run without touching the GPU, so these
+//! tests don't need a device.
+
+#[test]
+fn copy_direction() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/copy_direction_pass.rs");
+    t.compile_fail("tests/ui/htod_from_device.rs");
+    t.compile_fail("tests/ui/dtoh_into_device.rs");
+}
@@ -0,0 +1,15 @@
+use cudarc::driver::{CudaDevice, DriverError};
+use std::sync::Arc;
+
+#[allow(dead_code)]
+fn round_trip(dev: &Arc<CudaDevice>) -> Result<(), DriverError> {
+    let mut host = vec![0.0f32; 10];
+    let mut dev_buf = dev.alloc_zeros::<f32>(10)?;
+    dev.htod_sync_copy_into(&host, &mut dev_buf)?;
+    dev.dtoh_sync_copy_into(&dev_buf, &mut host)?;
+    let mut view = dev_buf.slice_mut(2..4);
+    dev.htod_sync_copy_into(&host[..2], &mut view)?;
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use cudarc::driver::{CudaDevice, DriverError};
+use std::sync::Arc;
+
+fn into_device(dev: &Arc<CudaDevice>) -> Result<(), DriverError> {
+    let src = dev.alloc_zeros::<f32>(10)?;
+    let mut dst = dev.alloc_zeros::<f32>(10)?;
+    dev.dtoh_sync_copy_into(&src, &mut dst)?;
+    Ok(())
+}
+
+fn main() {}